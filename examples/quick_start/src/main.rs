@@ -70,6 +70,13 @@ fn late_update() {
     // });
 }
 
+fn on_shutdown() {
+    log_debug!(format!(
+        "shutdown reason: {:?}",
+        NetworkServerStatic::shutdown_reason()
+    ));
+}
+
 fn on_disable() {}
 
 fn on_destroy() {}
@@ -103,6 +110,8 @@ fn main() {
     NetworkLoop::add_update_function(update);
     // 添加 late_update 函数
     NetworkLoop::add_late_update_function(late_update);
+    // 添加 on_shutdown 函数
+    NetworkLoop::add_on_shutdown_function(on_shutdown);
     // 添加 on_disable 函数
     NetworkLoop::add_on_disable_function(on_disable);
     // 添加 on_destroy 函数
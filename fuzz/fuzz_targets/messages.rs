@@ -0,0 +1,41 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mirror_rust::mirror::authenticators::basic_authenticator::{AuthRequestMessage, AuthResponseMessage};
+use mirror_rust::mirror::components::network_transform::transform_sync_data::SyncData;
+use mirror_rust::mirror::core::messages::*;
+use mirror_rust::mirror::core::network_reader::NetworkReader;
+
+fn deserialize<T: NetworkMessageTrait>(data: &[u8]) {
+    let mut reader = NetworkReader::new_with_array_segment(data);
+    let _ = T::deserialize(&mut reader);
+}
+
+fuzz_target!(|data: &[u8]| {
+    deserialize::<TimeSnapshotMessage>(data);
+    deserialize::<ReadyMessage>(data);
+    deserialize::<NotReadyMessage>(data);
+    deserialize::<AddPlayerMessage>(data);
+    deserialize::<SceneMessage>(data);
+    deserialize::<CommandMessage>(data);
+    deserialize::<RpcMessage>(data);
+    deserialize::<SpawnMessage>(data);
+    deserialize::<ChangeOwnerMessage>(data);
+    deserialize::<ObjectSpawnStartedMessage>(data);
+    deserialize::<ObjectSpawnFinishedMessage>(data);
+    deserialize::<ObjectDestroyMessage>(data);
+    deserialize::<ObjectHideMessage>(data);
+    deserialize::<EntityStateMessage>(data);
+    deserialize::<NetworkPingMessage>(data);
+    deserialize::<NetworkPongMessage>(data);
+    deserialize::<DisconnectMessage>(data);
+    deserialize::<ServerRestartMessage>(data);
+    deserialize::<UnreliableStateMessage>(data);
+    deserialize::<StateAckMessage>(data);
+    deserialize::<InputMessage>(data);
+    deserialize::<InputAckMessage>(data);
+    deserialize::<RequestAuthorityMessage>(data);
+    deserialize::<SyncData>(data);
+    deserialize::<AuthRequestMessage>(data);
+    deserialize::<AuthResponseMessage>(data);
+});
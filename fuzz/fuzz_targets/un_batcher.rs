@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mirror_rust::mirror::core::batching::un_batcher::UnBatcher;
+
+fuzz_target!(|data: &[u8]| {
+    let mut un_batcher = UnBatcher::new();
+    if !un_batcher.add_batch_with_array_segment(data) {
+        return;
+    }
+    // 读取到结束或者出错为止，不能 panic
+    while let Ok(Some(_)) = un_batcher.get_next_message() {}
+});
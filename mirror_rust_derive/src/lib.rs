@@ -0,0 +1,78 @@
+mod network_behaviour;
+mod network_message;
+mod remote_call;
+mod sync_var;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, ImplItemFn, ItemImpl};
+
+// #[derive(NetworkMessage)]
+// #[network_message(name = "Mirror.ChatMessage")]  C# 完整类型名，用于计算消息 id
+// 整数字段默认压缩，与 Mirror weaver 一致
+// 字段属性: #[network_message(fixed)] 不压缩的整数, #[network_message(skip)] 不参与序列化
+#[proc_macro_derive(NetworkMessage, attributes(network_message))]
+pub fn derive_network_message(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    network_message::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// #[derive(SyncVars)] 放在组件结构体上，字段加 #[sync_var] 声明 SyncVar，脏位按声明顺序分配
+// #[sync_var(hook = "on_health_changed")]  值变化时调用 self.on_health_changed(old, new)
+// #[sync_var(fixed)]  不压缩的整数
+// 生成 set_network_<字段名>() 和 serialize_sync_var_fields / deserialize_sync_var_fields
+#[proc_macro_derive(SyncVars, attributes(sync_var))]
+pub fn derive_sync_vars(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    sync_var::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// #[network_behaviour] 放在 impl NetworkBehaviourTrait for X 上
+// 补全转发到内部 NetworkBehaviour 的 getter/setter、get_once、as_any_mut，已手写的方法保留
+// 没有手写 serialize_sync_vars / deserialize_sync_vars 时使用 #[derive(SyncVars)] 生成的方法
+// 并生成 X::register_network_behaviour_factory()
+// #[network_behaviour(field = "base")]  内部 NetworkBehaviour 字段名，默认 network_behaviour
+// #[network_behaviour(component = "Mirror.X")]  组件类名，默认使用 X::COMPONENT_TAG
+#[proc_macro_attribute]
+pub fn network_behaviour(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut parsed_args = network_behaviour::Args::default();
+    let parser = syn::meta::parser(|meta| parsed_args.parse(meta));
+    parse_macro_input!(args with parser);
+    let input = parse_macro_input!(input as ItemImpl);
+    network_behaviour::expand(parsed_args, input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// #[command(name = "System.Void Mirror.NetworkRoomPlayer::CmdChangeReadyState(System.Boolean)")]
+// 放在组件的 &mut self 方法上，生成 invoke_user_code_<方法名> 委托和 register_<方法名>_delegate()
+// 参数属性: #[fixed] 不压缩的整数, #[sender] 发送命令的连接 id (u64)
+// #[command(name = "...", requires_authority = false)]  不要求客户端拥有对象
+#[proc_macro_attribute]
+pub fn command(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut parsed_args = remote_call::Args::default();
+    let parser = syn::meta::parser(|meta| parsed_args.parse_command(meta));
+    parse_macro_input!(args with parser);
+    let input = parse_macro_input!(input as ImplItemFn);
+    remote_call::expand_command(parsed_args, input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+// #[client_rpc(name = "System.Void Mirror.NetworkAnimator::RpcOnAnimationTriggerClientMessage(System.Int32)")]
+// 放在方法体为空的组件方法上，生成序列化参数并调用 send_rpc_internal 的方法体
+// #[client_rpc(name = "...", channel = "unreliable", include_owner = false)]
+// 参数属性: #[fixed] 不压缩的整数
+#[proc_macro_attribute]
+pub fn client_rpc(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut parsed_args = remote_call::Args::default();
+    let parser = syn::meta::parser(|meta| parsed_args.parse_client_rpc(meta));
+    parse_macro_input!(args with parser);
+    let input = parse_macro_input!(input as ImplItemFn);
+    remote_call::expand_client_rpc(parsed_args, input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
@@ -0,0 +1,305 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
+use syn::meta::ParseNestedMeta;
+use syn::{parse_quote, Ident, ImplItem, ImplItemFn, ItemImpl, LitStr};
+
+// #[network_behaviour(...)] 的参数
+#[derive(Default)]
+pub struct Args {
+    // 内部 NetworkBehaviour 字段路径，默认 network_behaviour
+    field: Option<Vec<Ident>>,
+    // 组件类名，默认使用 Self::COMPONENT_TAG
+    component: Option<LitStr>,
+}
+
+impl Args {
+    pub fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("field") {
+            // 支持嵌套字段，如 "network_transform_base.network_behaviour"
+            let field = meta.value()?.parse::<LitStr>()?;
+            let mut path = Vec::new();
+            for part in field.value().split('.') {
+                path.push(
+                    syn::parse_str::<Ident>(part).map_err(|_| {
+                        syn::Error::new_spanned(&field, "expected `field = \"a.b\"`")
+                    })?,
+                );
+            }
+            self.field = Some(path);
+            Ok(())
+        } else if meta.path.is_ident("component") {
+            self.component = Some(meta.value()?.parse::<LitStr>()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `field = \"...\"` or `component = \"Namespace.TypeName\"`"))
+        }
+    }
+}
+
+// 转发到内部 NetworkBehaviour 的样板方法
+fn boilerplate(field: &TokenStream) -> Vec<ImplItemFn> {
+    let core = quote!(::mirror_rust::mirror::core);
+    vec![
+        parse_quote! {
+            fn get_once() -> &'static ::std::sync::Once
+            where
+                Self: Sized,
+            {
+                static ONCE: ::std::sync::Once = ::std::sync::Once::new();
+                &ONCE
+            }
+        },
+        parse_quote! {
+            fn sync_interval(&self) -> f64 {
+                self.#field.sync_interval
+            }
+        },
+        parse_quote! {
+            fn set_sync_interval(&mut self, value: f64) {
+                self.#field.sync_interval = value;
+            }
+        },
+        parse_quote! {
+            fn last_sync_time(&self) -> f64 {
+                self.#field.last_sync_time
+            }
+        },
+        parse_quote! {
+            fn set_last_sync_time(&mut self, value: f64) {
+                self.#field.last_sync_time = value;
+            }
+        },
+        parse_quote! {
+            fn sync_direction(&mut self) -> &#core::network_behaviour::SyncDirection {
+                &self.#field.sync_direction
+            }
+        },
+        parse_quote! {
+            fn set_sync_direction(&mut self, value: #core::network_behaviour::SyncDirection) {
+                self.#field.sync_direction = value;
+            }
+        },
+        parse_quote! {
+            fn sync_mode(&mut self) -> &#core::network_behaviour::SyncMode {
+                &self.#field.sync_mode
+            }
+        },
+        parse_quote! {
+            fn set_sync_mode(&mut self, value: #core::network_behaviour::SyncMode) {
+                self.#field.sync_mode = value;
+            }
+        },
+        parse_quote! {
+            fn index(&self) -> u8 {
+                self.#field.index
+            }
+        },
+        parse_quote! {
+            fn set_index(&mut self, value: u8) {
+                self.#field.index = value;
+            }
+        },
+        parse_quote! {
+            fn sub_class(&self) -> ::std::string::String {
+                self.#field.sub_class.clone()
+            }
+        },
+        parse_quote! {
+            fn set_sub_class(&mut self, value: ::std::string::String) {
+                self.#field.sub_class = value;
+            }
+        },
+        parse_quote! {
+            fn sync_var_dirty_bits(&self) -> u64 {
+                self.#field.sync_var_dirty_bits
+            }
+        },
+        parse_quote! {
+            fn __set_sync_var_dirty_bits(&mut self, value: u64) {
+                self.#field.sync_var_dirty_bits = value;
+            }
+        },
+        parse_quote! {
+            fn sync_object_dirty_bits(&self) -> u64 {
+                self.#field.sync_object_dirty_bits()
+            }
+        },
+        parse_quote! {
+            fn __set_sync_object_dirty_bits(&mut self, value: u64) {
+                self.#field.sync_object_dirty_bits = value;
+            }
+        },
+        parse_quote! {
+            fn net_id(&self) -> u32 {
+                self.#field.net_id
+            }
+        },
+        parse_quote! {
+            fn set_net_id(&mut self, value: u32) {
+                self.#field.net_id = value;
+            }
+        },
+        parse_quote! {
+            fn connection_to_client(&self) -> u64 {
+                self.#field.connection_to_client
+            }
+        },
+        parse_quote! {
+            fn set_connection_to_client(&mut self, value: u64) {
+                self.#field.connection_to_client = value;
+            }
+        },
+        parse_quote! {
+            fn observers(&self) -> &::std::vec::Vec<u64> {
+                &self.#field.observers
+            }
+        },
+        parse_quote! {
+            fn add_observer(&mut self, conn_id: u64) {
+                self.#field.observers.push(conn_id);
+            }
+        },
+        parse_quote! {
+            fn remove_observer(&mut self, value: u64) {
+                self.#field.observers.retain(|&x| x != value);
+            }
+        },
+        parse_quote! {
+            fn game_object(&self) -> &#core::network_behaviour::GameObject {
+                &self.#field.game_object
+            }
+        },
+        parse_quote! {
+            fn set_game_object(&mut self, value: #core::network_behaviour::GameObject) {
+                self.#field.game_object = value;
+            }
+        },
+        parse_quote! {
+            fn sync_objects(
+                &mut self,
+            ) -> &mut ::std::vec::Vec<::std::boxed::Box<dyn #core::sync_object::SyncObject>> {
+                &mut self.#field.sync_objects
+            }
+        },
+        parse_quote! {
+            fn set_sync_objects(
+                &mut self,
+                value: ::std::vec::Vec<::std::boxed::Box<dyn #core::sync_object::SyncObject>>,
+            ) {
+                self.#field.sync_objects = value;
+            }
+        },
+        parse_quote! {
+            fn add_sync_object(
+                &mut self,
+                value: ::std::boxed::Box<dyn #core::sync_object::SyncObject>,
+            ) {
+                self.#field.sync_objects.push(value);
+            }
+        },
+        parse_quote! {
+            fn sync_var_hook_guard(&self) -> u64 {
+                self.#field.sync_var_hook_guard
+            }
+        },
+        parse_quote! {
+            fn __set_sync_var_hook_guard(&mut self, value: u64) {
+                self.#field.sync_var_hook_guard = value;
+            }
+        },
+        parse_quote! {
+            fn is_dirty(&self) -> bool {
+                self.#field.is_dirty()
+            }
+        },
+        parse_quote! {
+            fn as_any_mut(&mut self) -> &mut dyn ::std::any::Any {
+                self
+            }
+        },
+        // 由 #[derive(SyncVars)] 生成
+        parse_quote! {
+            fn serialize_sync_vars(
+                &mut self,
+                writer: &mut #core::network_writer::NetworkWriter,
+                initial_state: bool,
+            ) {
+                self.serialize_sync_var_fields(writer, initial_state);
+            }
+        },
+        parse_quote! {
+            fn deserialize_sync_vars(
+                &mut self,
+                reader: &mut #core::network_reader::NetworkReader,
+                initial_state: bool,
+            ) -> bool {
+                self.deserialize_sync_var_fields(reader, initial_state)
+            }
+        },
+    ]
+}
+
+pub fn expand(args: Args, mut item: ItemImpl) -> syn::Result<TokenStream> {
+    match &item.trait_ {
+        Some((_, path, _))
+            if path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "NetworkBehaviourTrait") => {}
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &item.self_ty,
+                "#[network_behaviour] must be placed on `impl NetworkBehaviourTrait for ...`",
+            ))
+        }
+    }
+
+    // 已经手写的方法不再生成
+    let defined: HashSet<String> = item
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Fn(func) => Some(func.sig.ident.to_string()),
+            _ => None,
+        })
+        .collect();
+    let field = match args.field {
+        Some(path) => quote!(#(#path).*),
+        None => quote!(network_behaviour),
+    };
+    for func in boilerplate(&field) {
+        if !defined.contains(&func.sig.ident.to_string()) {
+            item.items.push(ImplItem::Fn(func));
+        }
+    }
+
+    let core = quote!(::mirror_rust::mirror::core);
+    let component = match args.component {
+        Some(component) => quote!(#component),
+        None => quote!(Self::COMPONENT_TAG),
+    };
+    let self_ty = &item.self_ty;
+    let (impl_generics, _, where_clause) = item.generics.split_for_impl();
+    Ok(quote! {
+        #item
+
+        impl #impl_generics #self_ty #where_clause {
+            // 注册到 NetworkBehaviourFactory，可以传给 NetworkLoop::add_network_behaviour_factory
+            pub fn register_network_behaviour_factory() {
+                #core::network_behaviour::NetworkBehaviourFactory::add_network_behaviour_factory(
+                    ::std::string::ToString::to_string(#component),
+                    |game_object: #core::network_behaviour::GameObject,
+                     component: &#core::backend_data::NetworkBehaviourComponent| {
+                        ::std::boxed::Box::new(
+                            <Self as #core::network_behaviour::NetworkBehaviourTrait>::new(
+                                game_object,
+                                component,
+                            ),
+                        )
+                    },
+                );
+            }
+        }
+    })
+}
@@ -0,0 +1,146 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Field, Fields, LitStr};
+
+// 字段的序列化方式
+enum FieldKind {
+    Field,
+    Fixed,
+    Skip,
+}
+
+fn field_kind(field: &Field) -> syn::Result<FieldKind> {
+    let mut kind = FieldKind::Field;
+    for attr in field.attrs.iter() {
+        if !attr.path().is_ident("network_message") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("fixed") {
+                kind = FieldKind::Fixed;
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                kind = FieldKind::Skip;
+                Ok(())
+            } else {
+                Err(meta.error("expected `fixed` or `skip`"))
+            }
+        })?;
+    }
+    Ok(kind)
+}
+
+// C# 完整类型名，没有指定时使用结构体名（全局命名空间）
+fn full_name(input: &DeriveInput) -> syn::Result<LitStr> {
+    let mut name = None;
+    for attr in input.attrs.iter() {
+        if !attr.path().is_ident("network_message") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                name = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `name = \"Namespace.TypeName\"`"))
+            }
+        })?;
+    }
+    Ok(name.unwrap_or_else(|| LitStr::new(&input.ident.to_string(), input.ident.span())))
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "NetworkMessage can only be derived for structs",
+            ))
+        }
+    };
+    let full_name = full_name(&input)?;
+
+    let core = quote!(::mirror_rust::mirror::core);
+    let mut writes = Vec::new();
+    let mut reads = Vec::new();
+    let mut bindings = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let ty = &field.ty;
+        let binding = format_ident!("field_{}", index);
+        let member = match &field.ident {
+            Some(ident) => quote!(#ident),
+            None => {
+                let index = syn::Index::from(index);
+                quote!(#index)
+            }
+        };
+        match field_kind(field)? {
+            FieldKind::Field => {
+                writes.push(quote! {
+                    #core::network_field::NetworkField::write_field(&self.#member, writer);
+                });
+                reads.push(quote! {
+                    let #binding = <#ty as #core::network_field::NetworkField>::read_field(reader)?;
+                });
+            }
+            FieldKind::Fixed => {
+                writes.push(quote! {
+                    #core::network_field::NetworkFixedField::write_fixed_field(&self.#member, writer);
+                });
+                reads.push(quote! {
+                    let #binding =
+                        <#ty as #core::network_field::NetworkFixedField>::read_fixed_field(reader)?;
+                });
+            }
+            FieldKind::Skip => {
+                reads.push(quote! {
+                    let #binding = <#ty as ::core::default::Default>::default();
+                });
+            }
+        }
+        bindings.push(match &field.ident {
+            Some(ident) => quote!(#ident: #binding),
+            None => quote!(#binding),
+        });
+    }
+    let construct = match fields {
+        Fields::Named(_) => quote!(Self { #(#bindings),* }),
+        Fields::Unnamed(_) => quote!(Self(#(#bindings),*)),
+        Fields::Unit => quote!(Self),
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #core::messages::NetworkMessageTrait for #ident #ty_generics #where_clause {
+            fn deserialize(
+                reader: &mut #core::network_reader::NetworkReader,
+            ) -> ::core::result::Result<Self, #core::network_reader::ReadError> {
+                #(#reads)*
+                ::core::result::Result::Ok(#construct)
+            }
+
+            fn serialize(&mut self, writer: &mut #core::network_writer::NetworkWriter) {
+                #core::network_writer::NetworkWriterTrait::write_ushort(
+                    writer,
+                    #core::tools::stable_hash::StableHash::get_stable_hash_code16(
+                        <Self as #core::messages::NetworkMessageTrait>::get_full_name(),
+                    ),
+                );
+                #(#writes)*
+            }
+
+            fn get_full_name() -> &'static str
+            where
+                Self: Sized,
+            {
+                #full_name
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn ::core::any::Any {
+                self
+            }
+        }
+    })
+}
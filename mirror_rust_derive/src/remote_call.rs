@@ -0,0 +1,273 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::meta::ParseNestedMeta;
+use syn::{FnArg, ImplItemFn, LitBool, LitStr, Pat, PatType};
+
+// #[command(...)] / #[client_rpc(...)] 的参数
+pub struct Args {
+    // C# 方法完整签名，如 "System.Void Mirror.NetworkRoomPlayer::CmdChangeReadyState(System.Boolean)"
+    name: Option<LitStr>,
+    requires_authority: bool,
+    channel: Option<LitStr>,
+    include_owner: bool,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            name: None,
+            // 与 Mirror 的 [Command(requiresAuthority = true)]、[ClientRpc(includeOwner = true)] 默认值一致
+            requires_authority: true,
+            channel: None,
+            include_owner: true,
+        }
+    }
+}
+
+impl Args {
+    pub fn parse_command(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse::<LitStr>()?);
+            Ok(())
+        } else if meta.path.is_ident("requires_authority") {
+            self.requires_authority = meta.value()?.parse::<LitBool>()?.value;
+            Ok(())
+        } else {
+            Err(meta.error("expected `name = \"...\"` or `requires_authority = bool`"))
+        }
+    }
+
+    pub fn parse_client_rpc(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("name") {
+            self.name = Some(meta.value()?.parse::<LitStr>()?);
+            Ok(())
+        } else if meta.path.is_ident("channel") {
+            self.channel = Some(meta.value()?.parse::<LitStr>()?);
+            Ok(())
+        } else if meta.path.is_ident("include_owner") {
+            self.include_owner = meta.value()?.parse::<LitBool>()?.value;
+            Ok(())
+        } else {
+            Err(meta.error(
+                "expected `name = \"...\"`, `channel = \"reliable\"` or `include_owner = bool`",
+            ))
+        }
+    }
+
+    fn name(&self, func: &ImplItemFn) -> syn::Result<&LitStr> {
+        self.name.as_ref().ok_or_else(|| {
+            syn::Error::new_spanned(
+                &func.sig.ident,
+                "missing `name = \"System.Void Namespace.Type::Method(...)\"`",
+            )
+        })
+    }
+}
+
+// 参数的序列化方式
+enum ParamKind {
+    Field,
+    Fixed,
+    // NetworkConnectionToClient sender，传入连接 id，不参与序列化
+    Sender,
+}
+
+struct Param {
+    pat: Pat,
+    ty: syn::Type,
+    kind: ParamKind,
+}
+
+// 取出参数并去掉参数上的 #[fixed] / #[sender]
+fn take_params(func: &mut ImplItemFn, allow_sender: bool) -> syn::Result<Vec<Param>> {
+    let mut params = Vec::new();
+    for input in func.sig.inputs.iter_mut() {
+        let PatType { attrs, pat, ty, .. } = match input {
+            FnArg::Receiver(_) => continue,
+            FnArg::Typed(pat_type) => pat_type,
+        };
+        let mut kind = ParamKind::Field;
+        let mut error = None;
+        attrs.retain(|attr| {
+            if attr.path().is_ident("fixed") {
+                kind = ParamKind::Fixed;
+                false
+            } else if attr.path().is_ident("sender") {
+                if !allow_sender {
+                    error = Some(syn::Error::new_spanned(
+                        attr,
+                        "#[sender] is only supported on commands",
+                    ));
+                }
+                kind = ParamKind::Sender;
+                false
+            } else {
+                true
+            }
+        });
+        if let Some(error) = error {
+            return Err(error);
+        }
+        params.push(Param {
+            pat: (**pat).clone(),
+            ty: (**ty).clone(),
+            kind,
+        });
+    }
+    Ok(params)
+}
+
+pub fn expand_command(args: Args, mut func: ImplItemFn) -> syn::Result<TokenStream> {
+    let name = args.name(&func)?.clone();
+    let params = take_params(&mut func, true)?;
+    if !matches!(func.sig.inputs.first(), Some(FnArg::Receiver(_))) {
+        return Err(syn::Error::new_spanned(
+            &func.sig,
+            "#[command] methods must take `&mut self`",
+        ));
+    }
+
+    let core = quote!(::mirror_rust::mirror::core);
+    // 没有 #[sender] 参数时不使用连接 id
+    let conn_id = match params
+        .iter()
+        .any(|param| matches!(param.kind, ParamKind::Sender))
+    {
+        true => format_ident!("conn_id"),
+        false => format_ident!("_conn_id"),
+    };
+    let mut reads = Vec::new();
+    let mut bindings = Vec::new();
+    for (index, param) in params.iter().enumerate() {
+        let ty = &param.ty;
+        let binding = format_ident!("arg_{}", index);
+        let read = match param.kind {
+            ParamKind::Field => {
+                quote!(<#ty as #core::network_field::NetworkField>::read_field(reader))
+            }
+            ParamKind::Fixed => {
+                quote!(<#ty as #core::network_field::NetworkFixedField>::read_fixed_field(reader))
+            }
+            ParamKind::Sender => {
+                reads.push(quote!(let #binding: #ty = #conn_id;));
+                bindings.push(binding);
+                continue;
+            }
+        };
+        reads.push(quote! {
+            let #binding = match #read {
+                ::core::result::Result::Ok(value) => value,
+                ::core::result::Result::Err(err) => {
+                    ::mirror_rust::log_error!(
+                        "Command {} failed to read parameters: {}",
+                        #name,
+                        err
+                    );
+                    return;
+                }
+            };
+        });
+        bindings.push(binding);
+    }
+
+    let method = &func.sig.ident;
+    let invoker = format_ident!("invoke_user_code_{}", method);
+    let register = format_ident!("register_{}_delegate", method);
+    let requires_authority = args.requires_authority;
+    Ok(quote! {
+        #func
+
+        fn #invoker(
+            #conn_id: u64,
+            net_id: u32,
+            component_index: u8,
+            _func_hash: u16,
+            reader: &mut #core::network_reader::NetworkReader,
+        ) {
+            if !#core::network_server::NetworkServerStatic::active() {
+                ::mirror_rust::log_error!("Command {} called on client.", #name);
+                return;
+            }
+            #(#reads)*
+            #core::network_behaviour::NetworkBehaviour::invoke_component::<Self, _>(
+                net_id,
+                component_index,
+                |component| component.#method(#(#bindings),*),
+            );
+        }
+
+        pub fn #register() -> u16 {
+            #core::remote_calls::RemoteProcedureCalls::register_command_delegate::<Self>(
+                #name,
+                Self::#invoker,
+                #requires_authority,
+            )
+        }
+    })
+}
+
+pub fn expand_client_rpc(args: Args, mut func: ImplItemFn) -> syn::Result<TokenStream> {
+    let name = args.name(&func)?.clone();
+    let params = take_params(&mut func, false)?;
+    if !matches!(func.sig.inputs.first(), Some(FnArg::Receiver(_))) {
+        return Err(syn::Error::new_spanned(
+            &func.sig,
+            "#[client_rpc] methods must take `&self` or `&mut self`",
+        ));
+    }
+    // 方法体在客户端执行，服务器只负责发送
+    if !func.block.stmts.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &func.block,
+            "#[client_rpc] body runs on the client and must be empty",
+        ));
+    }
+
+    let core = quote!(::mirror_rust::mirror::core);
+    let mut writes = Vec::new();
+    for param in params.iter() {
+        let ident = match &param.pat {
+            Pat::Ident(pat) => &pat.ident,
+            pat => {
+                return Err(syn::Error::new_spanned(
+                    pat,
+                    "#[client_rpc] parameters must be plain identifiers",
+                ))
+            }
+        };
+        writes.push(match param.kind {
+            ParamKind::Fixed => {
+                quote!(#core::network_field::NetworkFixedField::write_fixed_field(&#ident, writer);)
+            }
+            _ => quote!(#core::network_field::NetworkField::write_field(&#ident, writer);),
+        });
+    }
+    let channel = match &args.channel {
+        None => quote!(#core::transport::TransportChannel::Reliable),
+        Some(channel) => match channel.value().as_str() {
+            "reliable" => quote!(#core::transport::TransportChannel::Reliable),
+            "unreliable" => quote!(#core::transport::TransportChannel::Unreliable),
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    channel,
+                    "expected `\"reliable\"` or `\"unreliable\"`",
+                ))
+            }
+        },
+    };
+    let include_owner = args.include_owner;
+    func.block = syn::parse_quote!({
+        #core::network_writer_pool::NetworkWriterPool::get_return(|writer| {
+            #(#writes)*
+            #core::network_behaviour::NetworkBehaviourTrait::send_rpc_internal(
+                &*self,
+                #name,
+                #core::tools::stable_hash::StableHash::get_stable_hash_code(#name),
+                writer,
+                #channel,
+                #include_owner,
+            );
+        });
+    });
+    Ok(quote!(#func))
+}
@@ -0,0 +1,220 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Field, Fields, Ident, LitStr};
+
+// #[sync_var(...)] 字段
+struct SyncVarField {
+    ident: Ident,
+    ty: syn::Type,
+    fixed: bool,
+    hook: Option<Ident>,
+}
+
+fn sync_var_field(field: &Field) -> syn::Result<Option<SyncVarField>> {
+    let mut sync_var = None;
+    for attr in field.attrs.iter() {
+        if !attr.path().is_ident("sync_var") {
+            continue;
+        }
+        let ident = field
+            .ident
+            .clone()
+            .ok_or_else(|| syn::Error::new_spanned(attr, "#[sync_var] requires a named field"))?;
+        let mut fixed = false;
+        let mut hook = None;
+        // 允许不带参数的 #[sync_var]
+        if !matches!(attr.meta, syn::Meta::Path(_)) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("fixed") {
+                    fixed = true;
+                    Ok(())
+                } else if meta.path.is_ident("hook") {
+                    hook = Some(meta.value()?.parse::<LitStr>()?.parse::<Ident>()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `hook = \"on_changed\"` or `fixed`"))
+                }
+            })?;
+        }
+        sync_var = Some(SyncVarField {
+            ident,
+            ty: field.ty.clone(),
+            fixed,
+            hook,
+        });
+    }
+    Ok(sync_var)
+}
+
+pub fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "SyncVars can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "SyncVars can only be derived for structs",
+            ))
+        }
+    };
+    let mut sync_vars = Vec::new();
+    for field in fields.iter() {
+        if let Some(sync_var) = sync_var_field(field)? {
+            sync_vars.push(sync_var);
+        }
+    }
+    if sync_vars.len() > 64 {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "a NetworkBehaviour can have at most 64 SyncVars",
+        ));
+    }
+
+    let core = quote!(::mirror_rust::mirror::core);
+    let behaviour = quote!(#core::network_behaviour::NetworkBehaviourTrait);
+    let mut setters = Vec::new();
+    let mut writes_all = Vec::new();
+    let mut writes_dirty = Vec::new();
+    let mut reads = Vec::new();
+    // 脏位按声明顺序分配，与 Mirror weaver 一致
+    for (index, sync_var) in sync_vars.iter().enumerate() {
+        let ident = &sync_var.ident;
+        let ty = &sync_var.ty;
+        let dirty_bit = quote!((1u64 << #index));
+        let setter = format_ident!("set_network_{}", ident);
+
+        let write = match sync_var.fixed {
+            true => {
+                quote!(#core::network_field::NetworkFixedField::write_fixed_field(&self.#ident, writer);)
+            }
+            false => quote!(#core::network_field::NetworkField::write_field(&self.#ident, writer);),
+        };
+        let read = match sync_var.fixed {
+            true => {
+                quote!(<#ty as #core::network_field::NetworkFixedField>::read_fixed_field(reader))
+            }
+            false => quote!(<#ty as #core::network_field::NetworkField>::read_field(reader)),
+        };
+        writes_all.push(write.clone());
+        writes_dirty.push(quote! {
+            if <Self as #behaviour>::sync_var_dirty_bits(self) & #dirty_bit != 0 {
+                #write
+            }
+        });
+
+        // 在 hook guard 保护下调用 hook，hook 中再次设置同一个 SyncVar 不会递归调用
+        let hook = sync_var.hook.as_ref().map(|hook| {
+            quote! {
+                if !<Self as #behaviour>::get_sync_var_hook_guard(self, #dirty_bit) {
+                    <Self as #behaviour>::set_sync_var_hook_guard(self, #dirty_bit, true);
+                    let new_value = ::core::clone::Clone::clone(&self.#ident);
+                    self.#hook(old_value, new_value);
+                    <Self as #behaviour>::set_sync_var_hook_guard(self, #dirty_bit, false);
+                }
+            }
+        });
+        let old_value = match sync_var.hook {
+            Some(_) => quote!(old_value),
+            None => quote!(_old_value),
+        };
+        // GeneratedSyncVarSetter: 值变化时设置脏位并调用 hook
+        setters.push(quote! {
+            pub fn #setter(&mut self, value: #ty) {
+                if #core::network_behaviour::NetworkBehaviour::sync_var_equal(&self.#ident, &value) {
+                    return;
+                }
+                let #old_value = ::core::mem::replace(&mut self.#ident, value);
+                <Self as #behaviour>::set_sync_var_dirty_bits(self, #dirty_bit);
+                #hook
+            }
+        });
+
+        // GeneratedSyncVarDeserialize: 值变化时调用 hook
+        reads.push(quote! {
+            if dirty_bits & #dirty_bit != 0 {
+                let value = match #read {
+                    ::core::result::Result::Ok(value) => value,
+                    ::core::result::Result::Err(err) => {
+                        ::mirror_rust::log_error!(
+                            "Failed to deserialize SyncVar {}: {}",
+                            stringify!(#ident),
+                            err
+                        );
+                        return false;
+                    }
+                };
+                if !#core::network_behaviour::NetworkBehaviour::sync_var_equal(&self.#ident, &value) {
+                    let #old_value = ::core::mem::replace(&mut self.#ident, value);
+                    #hook
+                }
+            }
+        });
+    }
+
+    let serialize_body = match sync_vars.is_empty() {
+        // 没有 SyncVar 时 Mirror 不生成 SerializeSyncVars
+        true => quote!(let _ = (writer, initial_state);),
+        false => quote! {
+            if initial_state {
+                #(#writes_all)*
+            } else {
+                #core::network_writer::NetworkWriterTrait::compress_var_ulong(
+                    writer,
+                    <Self as #behaviour>::sync_var_dirty_bits(self),
+                );
+                #(#writes_dirty)*
+            }
+        },
+    };
+    let deserialize_body = match sync_vars.is_empty() {
+        true => quote! {
+            let _ = (reader, initial_state);
+            true
+        },
+        false => quote! {
+            let dirty_bits = match initial_state {
+                true => u64::MAX,
+                false => match <u64 as #core::network_field::NetworkField>::read_field(reader) {
+                    ::core::result::Result::Ok(dirty_bits) => dirty_bits,
+                    ::core::result::Result::Err(err) => {
+                        ::mirror_rust::log_error!("Failed to deserialize SyncVar dirty bits: {}", err);
+                        return false;
+                    }
+                },
+            };
+            #(#reads)*
+            true
+        },
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #(#setters)*
+
+            pub fn serialize_sync_var_fields(
+                &mut self,
+                writer: &mut #core::network_writer::NetworkWriter,
+                initial_state: bool,
+            ) {
+                #serialize_body
+            }
+
+            pub fn deserialize_sync_var_fields(
+                &mut self,
+                reader: &mut #core::network_reader::NetworkReader,
+                initial_state: bool,
+            ) -> bool {
+                #deserialize_body
+            }
+        }
+    })
+}
@@ -1,5 +1,10 @@
+// derive 宏生成的代码通过 ::mirror_rust 引用本 crate
+extern crate self as mirror_rust;
+
 pub mod mirror;
 
+pub use mirror_rust_derive::{client_rpc, command, network_behaviour, NetworkMessage, SyncVars};
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -2,7 +2,7 @@ use crate::log_error;
 use crate::mirror::authenticators::network_authenticator::NetworkAuthenticatorTrait;
 use crate::mirror::core::messages::NetworkMessageTrait;
 use crate::mirror::core::network_connection::NetworkConnectionTrait;
-use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait};
+use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTryTrait, ReadError};
 use crate::mirror::core::network_server::{NetworkServer, NetworkServerStatic};
 use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
 use crate::mirror::core::transport::TransportChannel;
@@ -32,7 +32,10 @@ impl NetworkAuthenticatorTrait for BasicAuthenticator {
             // 转为BasicAuthenticator
             let basic_authenticator = authenticator.downcast_mut::<Self>().unwrap();
             // 反序列化 auth请求消息
-            let message = AuthRequestMessage::deserialize(reader);
+            let message = match NetworkServer::read_message::<AuthRequestMessage>(connection_id, reader) {
+                Some(message) => message,
+                None => return,
+            };
             // 检查用户名和密码
             match message.username == basic_authenticator.username
                 && message.password == basic_authenticator.password
@@ -112,11 +115,11 @@ pub struct AuthRequestMessage {
     pub password: String,
 }
 impl NetworkMessageTrait for AuthRequestMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        Self {
-            username: reader.read_string(),
-            password: reader.read_string(),
-        }
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        Ok(Self {
+            username: reader.try_read_string()?,
+            password: reader.try_read_string()?,
+        })
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -150,11 +153,11 @@ impl AuthResponseMessage {
 }
 
 impl NetworkMessageTrait for AuthResponseMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        Self {
-            code: reader.read_byte(),
-            message: reader.read_string(),
-        }
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        Ok(Self {
+            code: reader.try_read_byte()?,
+            message: reader.try_read_string()?,
+        })
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -4,4 +4,4 @@ pub mod room;
 pub mod network_transform;
 pub mod network_rigidbody;
 pub mod network_room_player;
-pub mod network_room_manager;
\ No newline at end of file
+pub mod network_room_manager;
@@ -1,21 +1,15 @@
 use crate::log_error;
 use crate::mirror::core::backend_data::NetworkBehaviourComponent;
-use crate::mirror::core::network_behaviour::{
-    GameObject, NetworkBehaviour, NetworkBehaviourTrait, SyncDirection, SyncMode,
-};
+use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviour, NetworkBehaviourTrait};
 use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait};
 use crate::mirror::core::network_reader_pool::NetworkReaderPool;
 use crate::mirror::core::network_server::{NetworkServerStatic, NETWORK_BEHAVIOURS};
 use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
-use crate::mirror::core::network_writer_pool::NetworkWriterPool;
 use crate::mirror::core::remote_calls::RemoteProcedureCalls;
-use crate::mirror::core::sync_object::SyncObject;
-use crate::mirror::core::transport::TransportChannel;
+use crate::{client_rpc, network_behaviour};
 use dashmap::try_result::TryResult;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
-use std::any::Any;
-use std::sync::Once;
 
 #[derive(Debug)]
 pub struct NetworkAnimator {
@@ -37,12 +31,11 @@ impl NetworkAnimator {
         let parameter_count = self.animator.parameters.len() as u8;
         writer.write_byte(parameter_count);
 
-        let dirty_bits: u64;
-        if force_all {
-            dirty_bits = u64::MAX;
+        let dirty_bits = if force_all {
+            u64::MAX
         } else {
-            dirty_bits = self.next_dirty_bits();
-        }
+            self.next_dirty_bits()
+        };
         writer.write_ulong(dirty_bits);
 
         for i in 0..parameter_count as usize {
@@ -376,6 +369,9 @@ impl NetworkAnimator {
     }
 
     // 1 RpcOnAnimationClientMessage(int stateHash, float normalizedTime, int layerId, float weight, byte[] parameters)
+    #[client_rpc(
+        name = "System.Void Mirror.NetworkAnimator::RpcOnAnimationClientMessage(System.Int32,System.Single,System.Int32,System.Single,System.Byte[])"
+    )]
     fn rpc_on_animation_client_message(
         &mut self,
         state_hash: i32,
@@ -384,41 +380,28 @@ impl NetworkAnimator {
         weight: f32,
         parameters: Vec<u8>,
     ) {
-        NetworkWriterPool::get_return(|writer| {
-            writer.compress_var_int(state_hash);
-            writer.write_float(normalized_time);
-            writer.compress_var_int(layer_id);
-            writer.write_float(weight);
-            writer.write_bytes_and_size(parameters);
-            self.send_rpc_internal("System.Void Mirror.NetworkAnimator::RpcOnAnimationClientMessage(System.Int32,System.Single,System.Int32,System.Single,System.Byte[])", -392669502, writer, TransportChannel::Reliable, true);
-        });
     }
 
     // 2 RpcOnAnimationParametersClientMessage(byte[] parameters)
-    fn rpc_on_animation_parameters_client_message(&mut self, parameters: Vec<u8>) {
-        NetworkWriterPool::get_return(|writer| {
-            writer.write_bytes_and_size(parameters);
-            self.send_rpc_internal("System.Void Mirror.NetworkAnimator::RpcOnAnimationParametersClientMessage(System.Byte[])", -2095336766, writer, TransportChannel::Reliable, true);
-        });
-    }
+    #[client_rpc(
+        name = "System.Void Mirror.NetworkAnimator::RpcOnAnimationParametersClientMessage(System.Byte[])"
+    )]
+    fn rpc_on_animation_parameters_client_message(&mut self, parameters: Vec<u8>) {}
 
     // 3 RpcOnAnimationTriggerClientMessage(int stateHash)
-    fn rpc_on_animation_trigger_client_message(&mut self, state_hash: i32) {
-        NetworkWriterPool::get_return(|writer| {
-            writer.compress_var_int(state_hash);
-            self.send_rpc_internal("System.Void Mirror.NetworkAnimator::RpcOnAnimationTriggerClientMessage(System.Int32)", 1759094990, writer, TransportChannel::Reliable, true);
-        });
-    }
+    #[client_rpc(
+        name = "System.Void Mirror.NetworkAnimator::RpcOnAnimationTriggerClientMessage(System.Int32)"
+    )]
+    fn rpc_on_animation_trigger_client_message(&mut self, state_hash: i32) {}
 
     // 4 RpcOnAnimationResetTriggerClientMessage(int stateHash)
-    fn rpc_on_animation_reset_trigger_client_message(&mut self, state_hash: i32) {
-        NetworkWriterPool::get_return(|writer| {
-            writer.compress_var_int(state_hash);
-            self.send_rpc_internal("System.Void Mirror.NetworkAnimator::RpcOnAnimationResetTriggerClientMessage(System.Int32)", 1545278305, writer, TransportChannel::Reliable, true);
-        });
-    }
+    #[client_rpc(
+        name = "System.Void Mirror.NetworkAnimator::RpcOnAnimationResetTriggerClientMessage(System.Int32)"
+    )]
+    fn rpc_on_animation_reset_trigger_client_message(&mut self, state_hash: i32) {}
 }
 
+#[network_behaviour]
 impl NetworkBehaviourTrait for NetworkAnimator {
     fn new(game_object: GameObject, network_behaviour_component: &NetworkBehaviourComponent) -> Self
     where
@@ -431,7 +414,7 @@ impl NetworkBehaviourTrait for NetworkAnimator {
             .animator
             .parameters
             .len();
-        let animator = Self {
+        Self {
             network_behaviour: NetworkBehaviour::new(
                 game_object,
                 network_behaviour_component.network_behaviour_setting,
@@ -454,8 +437,7 @@ impl NetworkBehaviourTrait for NetworkAnimator {
             last_int_parameters: Vec::with_capacity(last_parameters_count),
             last_float_parameters: Vec::with_capacity(last_parameters_count),
             last_bool_parameters: Vec::with_capacity(last_parameters_count),
-        };
-        animator
+        }
     }
 
     fn register_delegate()
@@ -498,137 +480,6 @@ impl NetworkBehaviourTrait for NetworkAnimator {
         );
     }
 
-    fn get_once() -> &'static Once
-    where
-        Self: Sized,
-    {
-        static ONCE: Once = Once::new();
-        &ONCE
-    }
-    fn sync_interval(&self) -> f64 {
-        self.network_behaviour.sync_interval
-    }
-
-    fn set_sync_interval(&mut self, value: f64) {
-        self.network_behaviour.sync_interval = value
-    }
-
-    fn last_sync_time(&self) -> f64 {
-        self.network_behaviour.last_sync_time
-    }
-
-    fn set_last_sync_time(&mut self, value: f64) {
-        self.network_behaviour.last_sync_time = value
-    }
-
-    fn sync_direction(&mut self) -> &SyncDirection {
-        &self.network_behaviour.sync_direction
-    }
-
-    fn set_sync_direction(&mut self, value: SyncDirection) {
-        self.network_behaviour.sync_direction = value
-    }
-
-    fn sync_mode(&mut self) -> &SyncMode {
-        &self.network_behaviour.sync_mode
-    }
-
-    fn set_sync_mode(&mut self, value: SyncMode) {
-        self.network_behaviour.sync_mode = value
-    }
-
-    fn index(&self) -> u8 {
-        self.network_behaviour.index
-    }
-
-    fn set_index(&mut self, value: u8) {
-        self.network_behaviour.index = value
-    }
-
-    fn sub_class(&self) -> String {
-        self.network_behaviour.sub_class.clone()
-    }
-
-    fn set_sub_class(&mut self, value: String) {
-        self.network_behaviour.sub_class = value
-    }
-
-    fn sync_var_dirty_bits(&self) -> u64 {
-        self.network_behaviour.sync_var_dirty_bits
-    }
-
-    fn __set_sync_var_dirty_bits(&mut self, value: u64) {
-        self.network_behaviour.sync_var_dirty_bits = value
-    }
-
-    fn sync_object_dirty_bits(&self) -> u64 {
-        self.network_behaviour.sync_object_dirty_bits
-    }
-
-    fn __set_sync_object_dirty_bits(&mut self, value: u64) {
-        self.network_behaviour.sync_object_dirty_bits = value
-    }
-
-    fn net_id(&self) -> u32 {
-        self.network_behaviour.net_id
-    }
-
-    fn set_net_id(&mut self, value: u32) {
-        self.network_behaviour.net_id = value
-    }
-
-    fn connection_to_client(&self) -> u64 {
-        self.network_behaviour.connection_to_client
-    }
-
-    fn set_connection_to_client(&mut self, value: u64) {
-        self.network_behaviour.connection_to_client = value
-    }
-
-    fn observers(&self) -> &Vec<u64> {
-        &self.network_behaviour.observers
-    }
-
-    fn add_observer(&mut self, conn_id: u64) {
-        self.network_behaviour.observers.push(conn_id)
-    }
-
-    fn remove_observer(&mut self, value: u64) {
-        self.network_behaviour.observers.retain(|&x| x != value);
-    }
-
-    fn game_object(&self) -> &GameObject {
-        &self.network_behaviour.game_object
-    }
-
-    fn set_game_object(&mut self, value: GameObject) {
-        self.network_behaviour.game_object = value
-    }
-
-    fn sync_objects(&mut self) -> &mut Vec<Box<dyn SyncObject>> {
-        &mut self.network_behaviour.sync_objects
-    }
-
-    fn set_sync_objects(&mut self, value: Vec<Box<dyn SyncObject>>) {
-        self.network_behaviour.sync_objects = value
-    }
-
-    fn add_sync_object(&mut self, value: Box<dyn SyncObject>) {
-        self.network_behaviour.sync_objects.push(value);
-    }
-
-    fn sync_var_hook_guard(&self) -> u64 {
-        self.network_behaviour.sync_var_hook_guard
-    }
-
-    fn __set_sync_var_hook_guard(&mut self, value: u64) {
-        self.network_behaviour.sync_var_hook_guard = value
-    }
-
-    fn is_dirty(&self) -> bool {
-        self.network_behaviour.is_dirty()
-    }
-
     fn on_serialize(&mut self, writer: &mut NetworkWriter, initial_state: bool) {
         // 默认实现 start
         self.serialize_sync_objects(writer, initial_state);
@@ -649,10 +500,6 @@ impl NetworkBehaviourTrait for NetworkAnimator {
         }
     }
 
-    fn as_any_mut(&mut self) -> &mut dyn Any {
-        self
-    }
-
     fn serialize_sync_vars(&mut self, writer: &mut NetworkWriter, initial_state: bool) {
         if initial_state {
             writer.write_float(self.animator_speed);
@@ -1,29 +1,32 @@
 use crate::log_error;
 use crate::mirror::core::backend_data::{
-    BackendDataStatic, NetworkBehaviourComponent, SyncVarData,
-};
-use crate::mirror::core::network_behaviour::{
-    GameObject, NetworkBehaviour, NetworkBehaviourTrait, SyncDirection, SyncMode,
+    BackendData, BackendDataStatic, MethodType, NetworkBehaviourComponent, SyncVarData,
 };
+use crate::mirror::core::csharp_types::CSharpTypes;
+use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviour, NetworkBehaviourTrait};
 use crate::mirror::core::network_loop::NetworkLoop;
-use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait};
+use crate::mirror::core::network_reader::NetworkReader;
 use crate::mirror::core::network_server::{NetworkServerStatic, NETWORK_BEHAVIOURS};
 use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
 use crate::mirror::core::network_writer_pool::NetworkWriterPool;
 use crate::mirror::core::remote_calls::RemoteProcedureCalls;
-use crate::mirror::core::sync_object::SyncObject;
+use crate::mirror::core::sync_object::{SyncObject, SyncObjectFactory, UnsupportedSyncObject};
 use crate::mirror::core::tools::stable_hash::StableHash;
 use crate::mirror::core::transport::TransportChannel;
+use crate::network_behaviour;
 use dashmap::try_result::TryResult;
 use dashmap::DashMap;
-use std::any::Any;
+use std::collections::HashMap;
 use std::fmt::Debug;
-use std::sync::Once;
 
 #[derive(Debug)]
 pub struct NetworkCommonBehaviour {
     pub network_behaviour: NetworkBehaviour,
     pub sync_vars: DashMap<u8, SyncVarData>,
+    // SyncObject 的 fullname，与 sync_objects 顺序一致
+    pub sync_object_names: Vec<String>,
+    // 拥有者专属 SyncVar 的初始值，观察者的初始状态写入初始值
+    pub owner_only_initial_values: HashMap<u8, Vec<u8>>,
 }
 
 impl NetworkCommonBehaviour {
@@ -60,52 +63,30 @@ impl NetworkCommonBehaviour {
         }
         None
     }
-    fn get_sync_var_value_vec(&self, r#type: &str, reader: &mut NetworkReader) -> Vec<u8> {
-        // 初始化数据
-        let mut value = Vec::new();
-        match r#type {
-            // 非定长类型
-            "System.String" => {
-                let string = reader.read_string();
-                NetworkWriterPool::get_return(|writer| {
-                    writer.write_string(string);
-                    value = writer.to_bytes();
-                });
-            }
-            // 常规类型
-
-            // 压缩类型
-            // TODO fix
-            "System.Int32" | "System.UInt32" | "System.Long" | "System.ULong" => {
-                value = reader.decompress_var()
-            }
-
-            // 4 字节
-            "System.Float" => {
-                value = reader.read_bytes(4);
-            }
-            // 8 字节
-            "System.Double" => {
-                value = reader.read_bytes(8);
-            }
-            // 12 字节
-            "UnityEngine.Vector3" => {
-                value = reader.read_bytes(12);
+    // 更新同步变量，参数不是同步变量时只跳过它的值
+    fn update_sync_var(
+        &mut self,
+        backend_data: &BackendData,
+        full_name: &str,
+        r#type: &str,
+        reader: &mut NetworkReader,
+    ) -> bool {
+        match CSharpTypes::read_value_bytes(backend_data, r#type, reader) {
+            Ok(value) => {
+                if let Some(index) = self.__get_sync_var_index(full_name) {
+                    self.__update_sync_var(index, value);
+                }
+                true
             }
-            // 16 字节
-            "UnityEngine.Color" => {
-                value = reader.read_bytes(16);
+            Err(err) => {
+                log_error!(
+                    "Failed to read sync var {} of type {}: {}",
+                    full_name,
+                    r#type,
+                    err
+                );
+                false
             }
-            // 未知类型
-            _ => {}
-        };
-        value
-    }
-    // 更新同步变量
-    fn update_sync_var(&mut self, full_name: &str, r#type: &str, reader: &mut NetworkReader) {
-        if let Some(index) = self.__get_sync_var_index(full_name) {
-            let value = self.get_sync_var_value_vec(r#type, reader);
-            self.__update_sync_var(index, value);
         }
     }
     // 通用更新同步变量
@@ -153,28 +134,49 @@ impl NetworkCommonBehaviour {
         func_hash: u16,
         _conn_id: u64,
     ) {
+        let backend_data = BackendDataStatic::get_backend_data();
         // 获取方法数据
-        if let Some(method_data) =
-            BackendDataStatic::get_backend_data().get_method_data_by_hash_code(func_hash)
-        {
+        if let Some(method_data) = backend_data.get_method_data_by_hash_code(func_hash) {
             // 更新同步变量
             for (index, parameter) in method_data.parameters.iter().enumerate() {
                 let r#type = parameter.value.as_str();
                 let full_name = method_data.var_list[index].value.as_str();
-                self.update_sync_var(full_name, r#type, reader);
+                // 读取失败时后续参数和转发的数据都不可信
+                if !self.update_sync_var(&backend_data, full_name, r#type, reader) {
+                    return;
+                }
             }
 
             // 发送RPCs
             NetworkWriterPool::get_return(|writer| {
                 writer.write_array_segment_all(reader.to_array_segment());
                 for rpc in method_data.rpc_list.iter() {
-                    self.send_rpc_internal(
-                        rpc.as_str(),
-                        rpc.get_stable_hash_code(),
-                        writer,
-                        TransportChannel::Reliable,
-                        true,
-                    );
+                    match backend_data.get_method_data_by_method_name(rpc) {
+                        // TargetRpc 发送给拥有者，拥有者不是观察者时不发送
+                        Some(rpc_method_data)
+                            if matches!(rpc_method_data.r#type, MethodType::TargetRpc) =>
+                        {
+                            if !self.observers().contains(&self.connection_to_client()) {
+                                continue;
+                            }
+                            self.send_target_rpc_internal(
+                                None,
+                                rpc.as_str(),
+                                rpc.get_stable_hash_code(),
+                                writer,
+                                TransportChannel::Reliable,
+                            );
+                        }
+                        _ => {
+                            self.send_rpc_internal(
+                                rpc.as_str(),
+                                rpc.get_stable_hash_code(),
+                                writer,
+                                TransportChannel::Reliable,
+                                true,
+                            );
+                        }
+                    }
                 }
             });
         } else {
@@ -182,6 +184,60 @@ impl NetworkCommonBehaviour {
         }
     }
 
+    // 拥有者专属 SyncVar 的脏位
+    fn __owner_only_mask(&self) -> u64 {
+        self.owner_only_initial_values
+            .keys()
+            .fold(0, |mask, index| mask | (1 << index))
+    }
+    // 只写入 sync_var_mask 中的 SyncVar，初始状态下其余 SyncVar 写入初始值
+    fn __serialize_sync_vars(
+        &self,
+        writer: &mut NetworkWriter,
+        initial_state: bool,
+        sync_var_mask: u64,
+    ) {
+        match initial_state {
+            // 初始状态
+            true => {
+                for i in 0..self.sync_vars.len() as u8 {
+                    match self.owner_only_initial_values.get(&i) {
+                        Some(initial_value) if sync_var_mask & (1 << i) == 0 => {
+                            writer.write_array_segment_all(initial_value.as_slice());
+                        }
+                        _ => {
+                            if let Some(sync_var) = self.sync_vars.get(&i) {
+                                writer.write_array_segment_all(sync_var.value.as_slice());
+                            }
+                        }
+                    }
+                }
+            }
+            // 非初始状态
+            false => {
+                let dirty_bits = self.sync_var_dirty_bits() & sync_var_mask;
+                writer.compress_var_ulong(dirty_bits);
+                for i in 0..self.sync_vars.len() as u8 {
+                    if dirty_bits & (1 << i) != 0 {
+                        if let Some(sync_var) = self.sync_vars.get(&i) {
+                            writer.write_array_segment_all(sync_var.value.as_slice());
+                        }
+                    }
+                }
+            }
+        }
+    }
+    fn __get_sync_object_index(&self, full_name: &str) -> Option<usize> {
+        self.sync_object_names
+            .iter()
+            .position(|name| name == full_name)
+    }
+    // 按 fullname 获取 SyncObject，如 sync_object_mut_by_name::<SyncList<i32>>("...")
+    pub fn sync_object_mut_by_name<T: SyncObject>(&mut self, full_name: &str) -> Option<&mut T> {
+        let index = self.__get_sync_object_index(full_name)?;
+        self.sync_object_mut::<T>(index)
+    }
+
     // 通用更新
     pub fn user_code_cmd_common_update_func(
         &mut self,
@@ -194,30 +250,56 @@ impl NetworkCommonBehaviour {
     }
 }
 
+#[network_behaviour]
 impl NetworkBehaviourTrait for NetworkCommonBehaviour {
     fn new(game_object: GameObject, network_behaviour_component: &NetworkBehaviourComponent) -> Self
     where
         Self: Sized,
     {
+        let backend_data = BackendDataStatic::get_backend_data();
         let sync_vars = DashMap::new();
-        for (i, sync_var) in BackendDataStatic::get_backend_data()
+        let mut owner_only_initial_values = HashMap::new();
+        for (i, sync_var) in backend_data
             .get_sync_var_data_s_by_sub_class(network_behaviour_component.sub_class.as_ref())
             .iter()
             .enumerate()
         {
+            if sync_var.owner_only {
+                owner_only_initial_values.insert(i as u8, sync_var.value.clone());
+            }
             sync_vars.insert(i as u8, (*sync_var).clone());
         }
         Self::call_register_delegate();
+        let mut network_behaviour = NetworkBehaviour::new(
+            game_object,
+            network_behaviour_component.network_behaviour_setting,
+            network_behaviour_component.index,
+            network_behaviour_component.sub_class.clone(),
+        );
+        // SyncObject 按声明顺序分配脏位，不支持的类型用占位跳过，不影响后续顺序
+        let mut sync_object_names = Vec::new();
+        for sync_object_data in backend_data
+            .get_sync_object_data_s_by_sub_class(network_behaviour_component.sub_class.as_ref())
+            .iter()
+        {
+            let sync_object = SyncObjectFactory::create_sync_object(sync_object_data)
+                .unwrap_or_else(|| {
+                    log_error!(
+                        "Unsupported SyncObject {}: {} {:?}",
+                        sync_object_data.full_name,
+                        sync_object_data.r#type,
+                        sync_object_data.generic_arguments
+                    );
+                    Box::new(UnsupportedSyncObject)
+                });
+            network_behaviour.sync_objects.push(sync_object);
+            sync_object_names.push(sync_object_data.full_name.clone());
+        }
         Self {
-            network_behaviour: NetworkBehaviour::new(
-                game_object,
-                network_behaviour_component
-                    .network_behaviour_setting
-                    .clone(),
-                network_behaviour_component.index,
-                network_behaviour_component.sub_class.clone(),
-            ),
+            network_behaviour,
             sync_vars,
+            sync_object_names,
+            owner_only_initial_values,
         }
     }
 
@@ -243,168 +325,172 @@ impl NetworkBehaviourTrait for NetworkCommonBehaviour {
         }
     }
 
-    fn get_once() -> &'static Once
-    where
-        Self: Sized,
-    {
-        static ONCE: Once = Once::new();
-        &ONCE
-    }
-
-    fn sync_interval(&self) -> f64 {
-        self.network_behaviour.sync_interval
-    }
-
-    fn set_sync_interval(&mut self, value: f64) {
-        self.network_behaviour.sync_interval = value
+    fn has_owner_only_sync_vars(&self) -> bool {
+        !self.owner_only_initial_values.is_empty()
     }
 
-    fn last_sync_time(&self) -> f64 {
-        self.network_behaviour.last_sync_time
+    fn is_dirty_for_observers(&self) -> bool {
+        self.network_behaviour
+            .is_dirty_with_sync_var_mask(!self.__owner_only_mask())
     }
 
-    fn set_last_sync_time(&mut self, value: f64) {
-        self.network_behaviour.last_sync_time = value
-    }
-
-    fn sync_direction(&mut self) -> &SyncDirection {
-        &self.network_behaviour.sync_direction
-    }
-
-    fn set_sync_direction(&mut self, value: SyncDirection) {
-        self.network_behaviour.sync_direction = value
-    }
-
-    fn sync_mode(&mut self) -> &SyncMode {
-        &self.network_behaviour.sync_mode
-    }
-
-    fn set_sync_mode(&mut self, value: SyncMode) {
-        self.network_behaviour.sync_mode = value
-    }
-
-    fn index(&self) -> u8 {
-        self.network_behaviour.index
-    }
-
-    fn set_index(&mut self, value: u8) {
-        self.network_behaviour.index = value
-    }
-
-    fn sub_class(&self) -> String {
-        self.network_behaviour.sub_class.clone()
-    }
-
-    fn set_sub_class(&mut self, value: String) {
-        self.network_behaviour.sub_class = value
-    }
-
-    fn sync_var_dirty_bits(&self) -> u64 {
-        self.network_behaviour.sync_var_dirty_bits
-    }
-
-    fn __set_sync_var_dirty_bits(&mut self, value: u64) {
-        self.network_behaviour.sync_var_dirty_bits = value
-    }
-
-    fn sync_object_dirty_bits(&self) -> u64 {
-        self.network_behaviour.sync_object_dirty_bits
-    }
-
-    fn __set_sync_object_dirty_bits(&mut self, value: u64) {
-        self.network_behaviour.sync_object_dirty_bits = value
-    }
-
-    fn net_id(&self) -> u32 {
-        self.network_behaviour.net_id
-    }
-
-    fn set_net_id(&mut self, value: u32) {
-        self.network_behaviour.net_id = value
-    }
-
-    fn connection_to_client(&self) -> u64 {
-        self.network_behaviour.connection_to_client
-    }
-
-    fn set_connection_to_client(&mut self, value: u64) {
-        self.network_behaviour.connection_to_client = value
-    }
-
-    fn observers(&self) -> &Vec<u64> {
-        &self.network_behaviour.observers
-    }
-
-    fn add_observer(&mut self, conn_id: u64) {
-        self.network_behaviour.observers.push(conn_id)
-    }
-
-    fn remove_observer(&mut self, value: u64) {
-        self.network_behaviour.observers.retain(|&x| x != value)
-    }
-
-
-    fn game_object(&self) -> &GameObject {
-        &self.network_behaviour.game_object
-    }
-
-    fn set_game_object(&mut self, value: GameObject) {
-        self.network_behaviour.game_object = value
-    }
-
-    fn sync_objects(&mut self) -> &mut Vec<Box<dyn SyncObject>> {
-        &mut self.network_behaviour.sync_objects
-    }
-
-    fn set_sync_objects(&mut self, value: Vec<Box<dyn SyncObject>>) {
-        self.network_behaviour.sync_objects = value
+    fn serialize_sync_vars(&mut self, writer: &mut NetworkWriter, initial_state: bool) {
+        self.__serialize_sync_vars(writer, initial_state, u64::MAX);
     }
 
-    fn add_sync_object(&mut self, value: Box<dyn SyncObject>) {
-        self.network_behaviour.sync_objects.push(value)
+    fn serialize_sync_vars_for_observers(
+        &mut self,
+        writer: &mut NetworkWriter,
+        initial_state: bool,
+    ) {
+        self.__serialize_sync_vars(writer, initial_state, !self.__owner_only_mask());
     }
 
-    fn sync_var_hook_guard(&self) -> u64 {
-        self.network_behaviour.sync_var_hook_guard
+    fn deserialize_sync_vars(&mut self, _reader: &mut NetworkReader, _initial_state: bool) -> bool {
+        true
     }
+}
 
-    fn __set_sync_var_hook_guard(&mut self, value: u64) {
-        self.network_behaviour.sync_var_hook_guard = value
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirror::core::backend_data::{MethodData, NetworkBehaviourSetting, SyncObjectData};
+    use crate::mirror::core::network_connection::NetworkConnectionTrait;
+    use crate::mirror::core::network_connection_to_client::NetworkConnectionToClient;
+    use crate::mirror::core::network_server::tests::{
+        lock_server, network_identity_data, TestTransport,
+    };
+    use crate::mirror::core::sync_list::SyncList;
+
+    fn method_data(name: &str, r#type: MethodType, rpc_list: Vec<String>) -> MethodData {
+        MethodData {
+            hash_code: name.get_fn_stable_hash_code(),
+            sub_class: "Test".to_string(),
+            name: name.to_string(),
+            requires_authority: false,
+            r#type,
+            parameters: Vec::new(),
+            rpc_list,
+            var_list: Vec::new(),
+            rate_limit: None,
+            rate_limit_burst: None,
+        }
     }
 
-    fn is_dirty(&self) -> bool {
-        self.network_behaviour.is_dirty()
+    fn rpcs_size(conn_id: u64) -> usize {
+        NetworkServerStatic::network_connections()
+            .get(&conn_id)
+            .map(|conn| conn.reliable_rpcs_batch.get_position())
+            .unwrap_or_default()
     }
 
-    fn as_any_mut(&mut self) -> &mut dyn Any {
-        self
+    fn sync_object_data(name: &str, r#type: &str, generic_arguments: &[&str]) -> SyncObjectData {
+        SyncObjectData {
+            full_name: format!("Test.Synced::{}", name),
+            sub_class: "Test.Synced".to_string(),
+            name: name.to_string(),
+            r#type: r#type.to_string(),
+            generic_arguments: generic_arguments.iter().map(|s| s.to_string()).collect(),
+        }
     }
 
-    fn serialize_sync_vars(&mut self, writer: &mut NetworkWriter, initial_state: bool) {
-        match initial_state {
-            // 初始状态
-            true => {
-                for i in 0..self.sync_vars.len() as u8 {
-                    if let Some(sync_var) = self.sync_vars.get(&i) {
-                        writer.write_array_segment_all(sync_var.value.as_slice());
-                    }
-                }
-            }
-            // 非初始状态
-            false => {
-                writer.compress_var_ulong(self.sync_var_dirty_bits());
-                for i in 0..self.sync_vars.len() as u8 {
-                    if self.sync_var_dirty_bits() & (1 << i) != 0 {
-                        if let Some(sync_var) = self.sync_vars.get(&i) {
-                            writer.write_array_segment_all(sync_var.value.as_slice());
-                        }
-                    }
-                }
-            }
+    #[test]
+    fn test_skip_unsupported_sync_object() {
+        let _lock = lock_server();
+        let backend_data = BackendDataStatic::get_backend_data();
+        let mut test_backend_data = backend_data.clone();
+        test_backend_data.sync_objects.extend([
+            sync_object_data("a", "Mirror.SyncList`1", &["System.Int32"]),
+            sync_object_data("b", "Mirror.SyncList`1", &["Test.Unknown"]),
+            sync_object_data("c", "Mirror.SyncList`1", &["System.Int32"]),
+        ]);
+        assert!(BackendDataStatic::store(&test_backend_data));
+
+        let identity_data = network_identity_data(1, &["Test.Synced"]);
+        let mut behaviour = NetworkCommonBehaviour::new(
+            GameObject::default(),
+            &identity_data.network_behaviour_components[0].value,
+        );
+        assert!(BackendDataStatic::store(&backend_data));
+
+        // 只跳过不支持的 b，c 仍使用第 2 个脏位
+        assert_eq!(behaviour.sync_objects().len(), 3);
+        assert!(behaviour
+            .sync_object_mut_by_name::<SyncList<i32>>("Test.Synced::b")
+            .is_none());
+        behaviour.clear_all_dirty_bits();
+        behaviour.add_observer(1);
+        behaviour
+            .sync_object_mut_by_name::<SyncList<i32>>("Test.Synced::c")
+            .unwrap()
+            .add(1);
+        assert_eq!(behaviour.sync_object_dirty_bits(), 1 << 2);
+
+        // 占位按空集合同步
+        let mut writer = NetworkWriter::new();
+        behaviour.serialize_sync_objects(&mut writer, true);
+        assert_eq!(writer.to_array_segment(), vec![0, 0, 0, 0, 1, 2, 1]);
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        assert!(behaviour.deserialize_sync_objects(&mut reader, true));
+    }
+
+    #[test]
+    fn test_forward_target_rpc_to_owner() {
+        let _lock = lock_server();
+        TestTransport::install();
+        let cmd = "System.Void Test::CmdFire()";
+        let target_rpc = "System.Void Test::TargetFire()";
+        let backend_data = BackendDataStatic::get_backend_data();
+        let mut test_backend_data = backend_data.clone();
+        test_backend_data.methods.push(method_data(
+            cmd,
+            MethodType::Command,
+            vec![target_rpc.to_string()],
+        ));
+        test_backend_data
+            .methods
+            .push(method_data(target_rpc, MethodType::TargetRpc, Vec::new()));
+        assert!(BackendDataStatic::store(&test_backend_data));
+        NetworkServerStatic::set_active(true);
+        for conn_id in [1, 2] {
+            NetworkServerStatic::network_connections()
+                .insert(conn_id, NetworkConnectionToClient::new(conn_id));
         }
-    }
 
-    fn deserialize_sync_vars(&mut self, _reader: &mut NetworkReader, _initial_state: bool) -> bool {
-        true
+        let mut behaviour = NetworkCommonBehaviour {
+            network_behaviour: NetworkBehaviour::new(
+                GameObject::default(),
+                NetworkBehaviourSetting::default(),
+                0,
+                "".to_string(),
+            ),
+            sync_vars: DashMap::new(),
+            sync_object_names: Vec::new(),
+            owner_only_initial_values: HashMap::new(),
+        };
+        behaviour.set_connection_to_client(1);
+        behaviour.add_observer(1);
+        behaviour.add_observer(2);
+
+        // 连接 2 调用 Command，TargetRpc 发送给拥有者
+        let func_hash = cmd.get_fn_stable_hash_code();
+        let mut reader = NetworkReader::new_with_array_segment(&[]);
+        behaviour.user_code_cmd_common_update_sync_var(&mut reader, func_hash, 2);
+        assert!(rpcs_size(1) > 0);
+        assert_eq!(rpcs_size(2), 0);
+
+        // 拥有者不是观察者时不发送
+        if let Some(mut conn) = NetworkServerStatic::network_connections().get_mut(&1) {
+            conn.reliable_rpcs_batch.reset();
+        }
+        behaviour.remove_observer(1);
+        let mut reader = NetworkReader::new_with_array_segment(&[]);
+        behaviour.user_code_cmd_common_update_sync_var(&mut reader, func_hash, 2);
+        assert_eq!(rpcs_size(1), 0);
+        assert_eq!(rpcs_size(2), 0);
+
+        NetworkServerStatic::network_connections().clear();
+        NetworkServerStatic::set_active(false);
+        assert!(BackendDataStatic::store(&backend_data));
     }
 }
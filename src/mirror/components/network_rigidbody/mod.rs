@@ -1,2 +1,5 @@
 pub mod network_rigidbody_unreliable;
-pub mod network_rigidbody_reliable;
\ No newline at end of file
+pub mod network_rigidbody_reliable;
+pub mod network_rigidbody_base;
+#[cfg(feature = "rapier")]
+pub mod physics_world;
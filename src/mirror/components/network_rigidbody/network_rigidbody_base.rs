@@ -0,0 +1,229 @@
+#[cfg(feature = "rapier")]
+use crate::mirror::components::network_rigidbody::physics_world::PhysicsWorld;
+use crate::mirror::core::backend_data::NetworkRigidbodySetting;
+use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait};
+use crate::mirror::core::network_time::NetworkTime;
+use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
+use nalgebra::{Quaternion, UnitQuaternion, Vector3};
+
+// NetworkRigidbody 的刚体状态，挂在 NetworkTransformBase 上
+#[derive(Debug)]
+pub struct NetworkRigidbodyBase {
+    pub velocity: Vector3<f32>,
+    pub angular_velocity: Vector3<f32>,
+    pub sleeping: bool,
+    pub mass: f32,
+    pub use_gravity: bool,
+    pub is_kinematic: bool,
+    pub drag: f32,
+    pub angular_drag: f32,
+    pub sync_velocity: bool,
+    pub velocity_sensitivity: f32,
+    last_serialized_velocity: Vector3<f32>,
+    last_serialized_angular_velocity: Vector3<f32>,
+    last_serialized_sleeping: bool,
+    // 没有物理模拟时用于估算速度
+    last_position: Option<Vector3<f32>>,
+    last_rotation: Quaternion<f32>,
+    last_update_time: f64,
+}
+
+impl NetworkRigidbodyBase {
+    pub fn new(setting: NetworkRigidbodySetting) -> Self {
+        Self {
+            velocity: Vector3::zeros(),
+            angular_velocity: Vector3::zeros(),
+            sleeping: true,
+            mass: setting.mass,
+            use_gravity: setting.use_gravity,
+            is_kinematic: setting.is_kinematic,
+            drag: setting.drag,
+            angular_drag: setting.angular_drag,
+            sync_velocity: setting.sync_velocity,
+            velocity_sensitivity: setting.velocity_sensitivity,
+            last_serialized_velocity: Vector3::zeros(),
+            last_serialized_angular_velocity: Vector3::zeros(),
+            last_serialized_sleeping: true,
+            last_position: None,
+            last_rotation: Quaternion::identity(),
+            last_update_time: 0.0,
+        }
+    }
+
+    // 热重载时只应用不影响传输格式的设置
+    pub fn apply_setting(&mut self, setting: NetworkRigidbodySetting) {
+        self.mass = setting.mass;
+        self.use_gravity = setting.use_gravity;
+        self.is_kinematic = setting.is_kinematic;
+        self.drag = setting.drag;
+        self.angular_drag = setting.angular_drag;
+        self.velocity_sensitivity = setting.velocity_sensitivity;
+    }
+
+    pub fn changed(&self) -> bool {
+        self.sync_velocity
+            && ((self.velocity - self.last_serialized_velocity).norm() > self.velocity_sensitivity
+                || (self.angular_velocity - self.last_serialized_angular_velocity).norm()
+                    > self.velocity_sensitivity
+                || self.sleeping != self.last_serialized_sleeping)
+    }
+
+    // 写在 NetworkTransform 数据之后
+    pub fn serialize(&mut self, writer: &mut NetworkWriter, initial_state: bool) {
+        if !self.sync_velocity {
+            return;
+        }
+        writer.write_vector3(self.velocity);
+        writer.write_vector3(self.angular_velocity);
+        writer.write_bool(self.sleeping);
+        if !initial_state {
+            self.last_serialized_velocity = self.velocity;
+            self.last_serialized_angular_velocity = self.angular_velocity;
+            self.last_serialized_sleeping = self.sleeping;
+        }
+    }
+
+    pub fn deserialize(&mut self, reader: &mut NetworkReader) {
+        if !self.sync_velocity {
+            return;
+        }
+        self.velocity = reader.read_vector3();
+        self.angular_velocity = reader.read_vector3();
+        self.sleeping = reader.read_bool();
+    }
+
+    // 每帧调用，物理模拟驱动时返回需要应用到 Transform 的位置和旋转
+    #[allow(unused_variables)]
+    pub fn step(
+        &mut self,
+        net_id: u32,
+        server_authority: bool,
+        position: Vector3<f32>,
+        rotation: Quaternion<f32>,
+    ) -> Option<(Vector3<f32>, Quaternion<f32>)> {
+        #[cfg(feature = "rapier")]
+        if PhysicsWorld::enabled() {
+            return PhysicsWorld::sync_rigidbody(
+                self,
+                net_id,
+                server_authority,
+                position,
+                rotation,
+            );
+        }
+        self.estimate(position, rotation);
+        None
+    }
+
+    // 根据位置和旋转的变化估算速度
+    pub fn estimate(&mut self, position: Vector3<f32>, rotation: Quaternion<f32>) {
+        let now = NetworkTime::local_time();
+        if let Some(last_position) = self.last_position {
+            let dt = (now - self.last_update_time) as f32;
+            if dt > f32::EPSILON {
+                self.velocity = (position - last_position) / dt;
+                let delta = UnitQuaternion::from_quaternion(rotation)
+                    * UnitQuaternion::from_quaternion(self.last_rotation).inverse();
+                self.angular_velocity = delta.scaled_axis() / dt;
+            }
+        }
+        self.sleeping = self.velocity.norm() <= self.velocity_sensitivity
+            && self.angular_velocity.norm() <= self.velocity_sensitivity;
+        self.last_position = Some(position);
+        self.last_rotation = rotation;
+        self.last_update_time = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sync_velocity_rigidbody() -> NetworkRigidbodyBase {
+        NetworkRigidbodyBase::new(NetworkRigidbodySetting {
+            sync_velocity: true,
+            ..NetworkRigidbodySetting::default()
+        })
+    }
+
+    #[test]
+    fn test_serialize_deserialize() {
+        let mut rigidbody = sync_velocity_rigidbody();
+        rigidbody.velocity = Vector3::new(1.0, 2.0, 3.0);
+        rigidbody.angular_velocity = Vector3::new(-1.0, 0.5, 0.0);
+        rigidbody.sleeping = false;
+
+        let mut writer = NetworkWriter::new();
+        rigidbody.serialize(&mut writer, false);
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        let mut other = sync_velocity_rigidbody();
+        other.deserialize(&mut reader);
+        assert_eq!(other.velocity, rigidbody.velocity);
+        assert_eq!(other.angular_velocity, rigidbody.angular_velocity);
+        assert!(!other.sleeping);
+        assert_eq!(reader.remaining(), 0);
+
+        // 不同步速度时不读写任何数据
+        let mut rigidbody = NetworkRigidbodyBase::new(NetworkRigidbodySetting::default());
+        rigidbody.velocity = Vector3::new(1.0, 0.0, 0.0);
+        let mut writer = NetworkWriter::new();
+        rigidbody.serialize(&mut writer, true);
+        assert_eq!(writer.get_position(), 0);
+        let mut reader = NetworkReader::new_with_array_segment(&[1, 2, 3]);
+        rigidbody.deserialize(&mut reader);
+        assert_eq!(reader.get_position(), 0);
+        assert_eq!(rigidbody.velocity, Vector3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_changed() {
+        let mut rigidbody = sync_velocity_rigidbody();
+        assert!(!rigidbody.changed());
+
+        // 变化不超过 velocity_sensitivity 时不需要同步
+        rigidbody.velocity = Vector3::new(0.05, 0.0, 0.0);
+        assert!(!rigidbody.changed());
+        rigidbody.velocity = Vector3::new(0.2, 0.0, 0.0);
+        assert!(rigidbody.changed());
+
+        // 初始状态不更新上次同步的值
+        let mut writer = NetworkWriter::new();
+        rigidbody.serialize(&mut writer, true);
+        assert!(rigidbody.changed());
+        rigidbody.serialize(&mut writer, false);
+        assert!(!rigidbody.changed());
+
+        rigidbody.angular_velocity = Vector3::new(0.0, 0.2, 0.0);
+        assert!(rigidbody.changed());
+        rigidbody.serialize(&mut writer, false);
+        rigidbody.sleeping = !rigidbody.sleeping;
+        assert!(rigidbody.changed());
+
+        rigidbody.sync_velocity = false;
+        assert!(!rigidbody.changed());
+    }
+
+    #[test]
+    fn test_estimate() {
+        let mut rigidbody = sync_velocity_rigidbody();
+        // 第一次只记录位置
+        rigidbody.estimate(Vector3::zeros(), Quaternion::identity());
+        assert_eq!(rigidbody.velocity, Vector3::zeros());
+        assert!(rigidbody.sleeping);
+
+        // 假设 0.5 秒后移动了 1 米并绕 y 轴转了 0.5 弧度
+        rigidbody.last_update_time -= 0.5;
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.5).into_inner();
+        rigidbody.estimate(Vector3::new(1.0, 0.0, 0.0), rotation);
+        assert!((rigidbody.velocity - Vector3::new(2.0, 0.0, 0.0)).norm() < 1.0e-2);
+        assert!((rigidbody.angular_velocity - Vector3::new(0.0, 1.0, 0.0)).norm() < 1.0e-2);
+        assert!(!rigidbody.sleeping);
+
+        // 停下来后进入休眠
+        rigidbody.last_update_time -= 0.5;
+        rigidbody.estimate(Vector3::new(1.0, 0.0, 0.0), rotation);
+        assert!(rigidbody.velocity.norm() < 1.0e-4);
+        assert!(rigidbody.angular_velocity.norm() < 1.0e-4);
+        assert!(rigidbody.sleeping);
+    }
+}
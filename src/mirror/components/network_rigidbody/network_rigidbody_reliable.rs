@@ -1,6 +1,25 @@
+use crate::mirror::components::network_rigidbody::network_rigidbody_base::NetworkRigidbodyBase;
+use crate::mirror::components::network_transform::network_transform_base::NetworkTransformBaseTrait;
+use crate::mirror::components::network_transform::network_transform_reliable::NetworkTransformReliable;
+use crate::mirror::core::backend_data::NetworkBehaviourComponent;
+use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviourTrait};
+
 #[derive(Debug)]
 pub struct NetworkRigidbodyReliable;
 
 impl NetworkRigidbodyReliable {
     pub const COMPONENT_TAG: &'static str = "Mirror.NetworkRigidbodyReliable";
+
+    // Mirror 中 NetworkRigidbodyReliable 继承 NetworkTransformReliable，这里创建带刚体状态的 NetworkTransformReliable
+    pub fn create_network_transform(
+        game_object: GameObject,
+        network_behaviour_component: &NetworkBehaviourComponent,
+    ) -> NetworkTransformReliable {
+        let mut network_transform =
+            NetworkTransformReliable::new(game_object, network_behaviour_component);
+        *network_transform.rigidbody() = Some(NetworkRigidbodyBase::new(
+            network_behaviour_component.network_rigidbody_setting,
+        ));
+        network_transform
+    }
 }
@@ -1,6 +1,25 @@
+use crate::mirror::components::network_rigidbody::network_rigidbody_base::NetworkRigidbodyBase;
+use crate::mirror::components::network_transform::network_transform_base::NetworkTransformBaseTrait;
+use crate::mirror::components::network_transform::network_transform_unreliable::NetworkTransformUnreliable;
+use crate::mirror::core::backend_data::NetworkBehaviourComponent;
+use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviourTrait};
+
 #[derive(Debug)]
 pub struct NetworkRigidbodyUnreliable;
 
 impl NetworkRigidbodyUnreliable {
     pub const COMPONENT_TAG: &'static str = "Mirror.NetworkRigidbodyUnreliable";
+
+    // Mirror 中 NetworkRigidbodyUnreliable 继承 NetworkTransformUnreliable，这里创建带刚体状态的 NetworkTransformUnreliable
+    pub fn create_network_transform(
+        game_object: GameObject,
+        network_behaviour_component: &NetworkBehaviourComponent,
+    ) -> NetworkTransformUnreliable {
+        let mut network_transform =
+            NetworkTransformUnreliable::new(game_object, network_behaviour_component);
+        *network_transform.rigidbody() = Some(NetworkRigidbodyBase::new(
+            network_behaviour_component.network_rigidbody_setting,
+        ));
+        network_transform
+    }
 }
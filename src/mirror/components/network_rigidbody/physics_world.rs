@@ -0,0 +1,359 @@
+use crate::mirror::components::network_rigidbody::network_rigidbody_base::NetworkRigidbodyBase;
+use crate::mirror::core::backend_data::{BackendDataStatic, ColliderData, ColliderShape};
+use crate::mirror::core::network_server::NetworkServerStatic;
+use crate::{log_error, log_warn};
+use atomic::Atomic;
+use dashmap::try_result::TryResult;
+use lazy_static::lazy_static;
+use nalgebra::{Isometry3, Quaternion, Translation3, UnitQuaternion, Vector3};
+use rapier3d::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+
+lazy_static! {
+    static ref PHYSICS_ENABLED: Atomic<bool> = Atomic::new(true);
+    static ref PHYSICS_GRAVITY: Atomic<f32> = Atomic::new(-9.81);
+    static ref PHYSICS_WORLD: Mutex<PhysicsWorld> = Mutex::new(PhysicsWorld::new());
+}
+
+// 服务器物理模拟，每个 identity 最多一个刚体
+pub struct PhysicsWorld {
+    pub integration_parameters: IntegrationParameters,
+    pub physics_pipeline: PhysicsPipeline,
+    pub island_manager: IslandManager,
+    pub broad_phase: DefaultBroadPhase,
+    pub narrow_phase: NarrowPhase,
+    pub rigid_body_set: RigidBodySet,
+    pub collider_set: ColliderSet,
+    pub impulse_joint_set: ImpulseJointSet,
+    pub multibody_joint_set: MultibodyJointSet,
+    pub ccd_solver: CCDSolver,
+    pub query_pipeline: QueryPipeline,
+    // net_id -> 刚体
+    pub bodies: HashMap<u32, RigidBodyHandle>,
+}
+
+impl PhysicsWorld {
+    fn new() -> Self {
+        Self {
+            integration_parameters: IntegrationParameters::default(),
+            physics_pipeline: PhysicsPipeline::new(),
+            island_manager: IslandManager::new(),
+            broad_phase: DefaultBroadPhase::new(),
+            narrow_phase: NarrowPhase::new(),
+            rigid_body_set: RigidBodySet::new(),
+            collider_set: ColliderSet::new(),
+            impulse_joint_set: ImpulseJointSet::new(),
+            multibody_joint_set: MultibodyJointSet::new(),
+            ccd_solver: CCDSolver::new(),
+            query_pipeline: QueryPipeline::new(),
+            bodies: HashMap::new(),
+        }
+    }
+
+    pub fn enabled() -> bool {
+        PHYSICS_ENABLED.load(Ordering::Relaxed)
+    }
+    pub fn set_enabled(value: bool) {
+        PHYSICS_ENABLED.store(value, Ordering::Relaxed);
+    }
+    pub fn gravity() -> f32 {
+        PHYSICS_GRAVITY.load(Ordering::Relaxed)
+    }
+    pub fn set_gravity(value: f32) {
+        PHYSICS_GRAVITY.store(value, Ordering::Relaxed);
+    }
+
+    pub fn with<T, F: FnOnce(&mut PhysicsWorld) -> T>(func: F) -> Option<T> {
+        match PHYSICS_WORLD.lock() {
+            Ok(mut physics_world) => Some(func(&mut physics_world)),
+            Err(e) => {
+                log_error!(format!("PhysicsWorld.with() error: {}", e));
+                None
+            }
+        }
+    }
+
+    pub fn reset() {
+        Self::with(|physics_world| {
+            *physics_world = PhysicsWorld::new();
+        });
+    }
+
+    // 每个 tick 模拟一次，在 NetworkBehaviour update 之前
+    pub fn step(dt: f32) {
+        if !Self::enabled() {
+            return;
+        }
+        Self::with(|physics_world| {
+            // 移除已经销毁的对象
+            let removed: Vec<u32> = physics_world
+                .bodies
+                .keys()
+                .filter(|net_id| {
+                    !NetworkServerStatic::spawned_network_identities().contains_key(net_id)
+                })
+                .copied()
+                .collect();
+            for net_id in removed {
+                physics_world.remove_body(net_id);
+            }
+
+            physics_world.integration_parameters.dt = dt;
+            let gravity = vector![0.0, Self::gravity(), 0.0];
+            physics_world.physics_pipeline.step(
+                &gravity,
+                &physics_world.integration_parameters,
+                &mut physics_world.island_manager,
+                &mut physics_world.broad_phase,
+                &mut physics_world.narrow_phase,
+                &mut physics_world.rigid_body_set,
+                &mut physics_world.collider_set,
+                &mut physics_world.impulse_joint_set,
+                &mut physics_world.multibody_joint_set,
+                &mut physics_world.ccd_solver,
+                Some(&mut physics_world.query_pipeline),
+                &(),
+                &(),
+            );
+        });
+    }
+
+    pub fn remove_body(&mut self, net_id: u32) {
+        if let Some(handle) = self.bodies.remove(&net_id) {
+            self.rigid_body_set.remove(
+                handle,
+                &mut self.island_manager,
+                &mut self.collider_set,
+                &mut self.impulse_joint_set,
+                &mut self.multibody_joint_set,
+                true,
+            );
+        }
+    }
+
+    // 服务器权威的刚体由物理模拟驱动，客户端权威的刚体作为运动学刚体跟随 Transform
+    pub fn sync_rigidbody(
+        rigidbody: &mut NetworkRigidbodyBase,
+        net_id: u32,
+        server_authority: bool,
+        position: Vector3<f32>,
+        rotation: Quaternion<f32>,
+    ) -> Option<(Vector3<f32>, Quaternion<f32>)> {
+        let kinematic = rigidbody.is_kinematic || !server_authority;
+        let isometry = Isometry3::from_parts(
+            Translation3::from(position),
+            UnitQuaternion::from_quaternion(rotation),
+        );
+        Self::with(|physics_world| {
+            let handle = match physics_world.bodies.get(&net_id) {
+                Some(handle) => *handle,
+                None => physics_world.create_body(net_id, rigidbody, isometry)?,
+            };
+            let body = physics_world.rigid_body_set.get_mut(handle)?;
+            let body_type = match kinematic {
+                true => RigidBodyType::KinematicPositionBased,
+                false => RigidBodyType::Dynamic,
+            };
+            if body.body_type() != body_type {
+                body.set_body_type(body_type, true);
+            }
+            body.set_linear_damping(rigidbody.drag);
+            body.set_angular_damping(rigidbody.angular_drag);
+            body.set_gravity_scale(if rigidbody.use_gravity { 1.0 } else { 0.0 }, false);
+            if kinematic {
+                body.set_next_kinematic_position(isometry);
+                None
+            } else {
+                rigidbody.velocity = *body.linvel();
+                rigidbody.angular_velocity = *body.angvel();
+                rigidbody.sleeping = body.is_sleeping();
+                let position = body.position();
+                Some((position.translation.vector, position.rotation.into_inner()))
+            }
+        })
+        .flatten()
+        .or_else(|| {
+            // 运动学刚体或者创建失败时估算速度
+            rigidbody.estimate(position, rotation);
+            None
+        })
+    }
+
+    fn create_body(
+        &mut self,
+        net_id: u32,
+        rigidbody: &NetworkRigidbodyBase,
+        isometry: Isometry3<f32>,
+    ) -> Option<RigidBodyHandle> {
+        let (asset_id, scene_id) =
+            match NetworkServerStatic::spawned_network_identities().try_get(&net_id) {
+                TryResult::Present(identity) => (identity.asset_id, identity.scene_id),
+                TryResult::Absent => return None,
+                TryResult::Locked => {
+                    log_warn!(format!(
+                        "PhysicsWorld.create_body: netId {} is locked",
+                        net_id
+                    ));
+                    return None;
+                }
+            };
+        let colliders = BackendDataStatic::get_backend_data()
+            .get_colliders_by_asset_id_or_scene_id(asset_id, scene_id);
+        if colliders.is_empty() {
+            log_warn!(format!(
+                "PhysicsWorld.create_body: netId {} has no colliders",
+                net_id
+            ));
+        }
+        let body = RigidBodyBuilder::dynamic()
+            .position(isometry)
+            .linear_damping(rigidbody.drag)
+            .angular_damping(rigidbody.angular_drag)
+            .gravity_scale(if rigidbody.use_gravity { 1.0 } else { 0.0 })
+            .build();
+        let handle = self.rigid_body_set.insert(body);
+        let collider_mass = rigidbody.mass / colliders.len().max(1) as f32;
+        for collider_data in colliders.iter() {
+            let collider = Self::build_collider(collider_data)
+                .mass(collider_mass)
+                .build();
+            self.collider_set
+                .insert_with_parent(collider, handle, &mut self.rigid_body_set);
+        }
+        self.bodies.insert(net_id, handle);
+        Some(handle)
+    }
+
+    fn build_collider(collider_data: &ColliderData) -> ColliderBuilder {
+        let builder = match collider_data.shape {
+            ColliderShape::Sphere => ColliderBuilder::ball(collider_data.radius),
+            ColliderShape::Box => {
+                let half_extents = collider_data.size.to_vector3() / 2.0;
+                ColliderBuilder::cuboid(half_extents.x, half_extents.y, half_extents.z)
+            }
+            ColliderShape::Capsule => ColliderBuilder::capsule_y(
+                (collider_data.height / 2.0 - collider_data.radius).max(0.0),
+                collider_data.radius,
+            ),
+        };
+        builder
+            .translation(collider_data.center.to_vector3())
+            .sensor(collider_data.is_trigger)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirror::core::backend_data::{
+        NetworkIdentityData, NetworkRigidbodySetting, Vector3Data,
+    };
+    use crate::mirror::core::network_identity::NetworkIdentity;
+    use crate::mirror::core::network_server::tests::lock_server;
+
+    const ASSET_ID: u32 = 9001;
+
+    fn spawn_identity() -> u32 {
+        let mut identity = NetworkIdentity::new_with_asset_id(ASSET_ID);
+        let net_id = NetworkIdentity::get_static_next_network_id();
+        identity.set_net_id(net_id);
+        NetworkServerStatic::spawned_network_identities().insert(net_id, identity);
+        net_id
+    }
+
+    fn rigidbody() -> NetworkRigidbodyBase {
+        NetworkRigidbodyBase::new(NetworkRigidbodySetting {
+            sync_velocity: true,
+            ..NetworkRigidbodySetting::default()
+        })
+    }
+
+    #[test]
+    fn test_sync_rigidbody() {
+        let _lock = lock_server();
+        PhysicsWorld::reset();
+        let backend_data = BackendDataStatic::get_backend_data();
+        let mut test_backend_data = backend_data.clone();
+        test_backend_data
+            .network_identities
+            .push(NetworkIdentityData {
+                asset_id: ASSET_ID,
+                scene_id: "".to_string(),
+                network_behaviour_components: Vec::new(),
+                hitboxes: Vec::new(),
+                colliders: vec![ColliderData {
+                    shape: ColliderShape::Sphere,
+                    center: Vector3Data::default(),
+                    size: Vector3Data::default(),
+                    radius: 0.5,
+                    height: 0.0,
+                    is_trigger: false,
+                }],
+            });
+        assert!(BackendDataStatic::store(&test_backend_data));
+
+        // 服务器权威的刚体受重力下落
+        let net_id = spawn_identity();
+        let mut dynamic = rigidbody();
+        let start = Vector3::new(0.0, 10.0, 0.0);
+        let (position, _) =
+            PhysicsWorld::sync_rigidbody(&mut dynamic, net_id, true, start, Quaternion::identity())
+                .unwrap();
+        assert_eq!(position, start);
+        for _ in 0..10 {
+            PhysicsWorld::step(0.05);
+        }
+        let (position, _) =
+            PhysicsWorld::sync_rigidbody(&mut dynamic, net_id, true, start, Quaternion::identity())
+                .unwrap();
+        assert!(position.y < start.y);
+        assert!(dynamic.velocity.y < 0.0);
+        assert!(!dynamic.sleeping);
+
+        // 客户端权威的刚体跟随 Transform，不返回位置
+        let client_net_id = spawn_identity();
+        let mut kinematic = rigidbody();
+        assert!(PhysicsWorld::sync_rigidbody(
+            &mut kinematic,
+            client_net_id,
+            false,
+            start,
+            Quaternion::identity()
+        )
+        .is_none());
+        PhysicsWorld::step(0.05);
+        let body_type = PhysicsWorld::with(|physics_world| {
+            let handle = physics_world.bodies[&client_net_id];
+            physics_world.rigid_body_set[handle].body_type()
+        });
+        assert_eq!(body_type, Some(RigidBodyType::KinematicPositionBased));
+
+        // 销毁的对象在下一次 step 中移除
+        NetworkServerStatic::spawned_network_identities().remove(&net_id);
+        PhysicsWorld::step(0.05);
+        let bodies = PhysicsWorld::with(|physics_world| {
+            (
+                physics_world.bodies.contains_key(&net_id),
+                physics_world.bodies.contains_key(&client_net_id),
+            )
+        });
+        assert_eq!(bodies, Some((false, true)));
+
+        // 找不到对象时不创建刚体，估算速度
+        let mut missing = rigidbody();
+        assert!(PhysicsWorld::sync_rigidbody(
+            &mut missing,
+            net_id,
+            true,
+            start,
+            Quaternion::identity()
+        )
+        .is_none());
+
+        NetworkServerStatic::spawned_network_identities().remove(&client_net_id);
+        PhysicsWorld::reset();
+        assert!(BackendDataStatic::store(&backend_data));
+    }
+}
@@ -11,9 +11,8 @@ use crate::mirror::core::network_manager::{
     NetworkManager, NetworkManagerMode, NetworkManagerStatic, NetworkManagerTrait,
 };
 use crate::mirror::core::network_reader::NetworkReader;
-use crate::mirror::core::network_server::{
-    EventHandlerType, NetworkServer, NetworkServerStatic, ReplacePlayerOptions,
-};
+use crate::mirror::core::network_server::{NetworkServer, NetworkServerStatic, ReplacePlayerOptions};
+use crate::mirror::core::server_events::{EventHandlerType, ServerEvents};
 use crate::mirror::core::transport::{TransportChannel, TransportError};
 use crate::{log_debug, log_error, log_warn};
 use dashmap::try_result::TryResult;
@@ -38,26 +37,25 @@ pub struct NetworkRoomManager {
 impl NetworkRoomManager {
     // zhuce
     fn register_server_messages() {
-        // 添加连接事件
-        NetworkServerStatic::connected_event().insert(
-            EventHandlerType::OnConnectedEvent,
-            Box::new(Self::on_server_connect_internal),
-        );
-        // 添加断开连接事件
-        NetworkServerStatic::connected_event().insert(
-            EventHandlerType::OnDisconnectedEvent,
-            Box::new(Self::on_server_disconnect),
-        );
-        // 添加错误事件
-        NetworkServerStatic::connected_event().insert(
-            EventHandlerType::OnErrorEvent,
-            Box::new(Self::on_server_error),
-        );
-        // 添加异常事件
-        NetworkServerStatic::connected_event().insert(
-            EventHandlerType::OnTransportExceptionEvent,
-            Box::new(Self::on_server_transport_exception),
-        );
+        NetworkManagerStatic::set_server_event_subscriptions(vec![
+            // 添加连接事件
+            ServerEvents::subscribe_connection(
+                EventHandlerType::OnConnectedEvent,
+                Self::on_server_connect_internal,
+            ),
+            // 添加断开连接事件
+            ServerEvents::subscribe_connection(
+                EventHandlerType::OnDisconnectedEvent,
+                Self::on_server_disconnect,
+            ),
+            // 添加错误事件
+            ServerEvents::subscribe_connection(EventHandlerType::OnErrorEvent, Self::on_server_error),
+            // 添加异常事件
+            ServerEvents::subscribe_connection(
+                EventHandlerType::OnTransportExceptionEvent,
+                Self::on_server_transport_exception,
+            ),
+        ]);
 
         // 添加 AddPlayerMessage 消息处理
         NetworkServer::register_handler::<AddPlayerMessage>(
@@ -87,7 +85,7 @@ impl NetworkRoomManager {
         // 获取 场景名称
         let network_scene_name = NetworkManagerStatic::network_scene_name();
         // 如果 场景名称不为空 且 场景名称不等于 NetworkManager 的 offline_scene
-        if network_scene_name != "" && network_scene_name != offline_scene {
+        if !network_scene_name.is_empty() && network_scene_name != offline_scene {
             // 创建 SceneMessage 消息
             let mut scene_message = SceneMessage::new(
                 network_scene_name.to_string(),
@@ -139,7 +137,7 @@ pub trait NetworkRoomManagerTrait: NetworkManagerTrait {
             return;
         }
 
-        let game_player = Self::on_room_server_create_game_player(conn_id, &room_player);
+        let game_player = Self::on_room_server_create_game_player(conn_id, room_player);
 
         let player = match game_player {
             None => {
@@ -152,7 +150,7 @@ pub trait NetworkRoomManagerTrait: NetworkManagerTrait {
 
         NetworkServer::replace_player_for_connection(
             conn_id,
-            &player,
+            player,
             ReplacePlayerOptions::KeepAuthority,
         );
     }
@@ -223,10 +221,8 @@ impl NetworkManagerTrait for NetworkRoomManager {
         // always >= 0
         self.min_players = self.min_players.max(0);
 
-        if !self.room_player_prefab.is_null() {
-            if !self.room_player_prefab.is_has_component() {
-                log_error!("NetworkRoomManager - RoomPlayer Prefab must have a NetworkIdentity.");
-            }
+        if !self.room_player_prefab.is_null() && !self.room_player_prefab.is_has_component() {
+            log_error!("NetworkRoomManager - RoomPlayer Prefab must have a NetworkIdentity.");
         }
     }
 
@@ -282,7 +278,7 @@ impl NetworkManagerTrait for NetworkRoomManager {
         let mut index = 0;
         let mut id = 0;
         for (i, net_id) in self.room_slots.iter().enumerate() {
-            match NetworkServerStatic::spawned_network_identities().try_get(&net_id) {
+            match NetworkServerStatic::spawned_network_identities().try_get(net_id) {
                 TryResult::Present(identity) => {
                     if !identity.get_component::<NetworkRoomPlayer, _>(|player| {
                         player.index = i as i32;
@@ -345,7 +341,7 @@ impl NetworkManagerTrait for NetworkRoomManager {
     {
         // 获取 BackendData
         let backend_data = BackendDataStatic::get_backend_data();
-        if backend_data.network_manager_settings.len() == 0 {
+        if backend_data.network_manager_settings.is_empty() {
             panic!("No NetworkRoomManager settings found in the BackendData. Please add a NetworkRoomManager setting.");
         }
 
@@ -1,72 +1,29 @@
 use crate::log_error;
 use crate::mirror::core::backend_data::NetworkBehaviourComponent;
-use crate::mirror::core::network_behaviour::{
-    GameObject, NetworkBehaviour, NetworkBehaviourTrait, SyncDirection, SyncMode,
-};
+use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviour, NetworkBehaviourTrait};
 use crate::mirror::core::network_manager::NetworkManagerStatic;
-use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait};
-use crate::mirror::core::network_server::{NetworkServerStatic, NETWORK_BEHAVIOURS};
-use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
-use crate::mirror::core::remote_calls::RemoteProcedureCalls;
-use crate::mirror::core::sync_object::SyncObject;
-use dashmap::try_result::TryResult;
-use std::any::Any;
-use std::sync::Once;
+use crate::mirror::core::network_server::NetworkServerStatic;
+use crate::{command, network_behaviour, SyncVars};
 
-#[derive(Debug)]
+#[derive(Debug, SyncVars)]
 pub struct NetworkRoomPlayer {
     pub network_behaviour: NetworkBehaviour,
+    #[sync_var]
     pub ready_to_begin: bool,
+    #[sync_var]
     pub index: i32,
 }
 
 impl NetworkRoomPlayer {
     pub const COMPONENT_TAG: &'static str = "Mirror.NetworkRoomPlayer";
-    fn invoke_user_code_cmd_change_ready_state_boolean(
-        _conn_id: u64,
-        net_id: u32,
-        component_index: u8,
-        _func_hash: u16,
-        reader: &mut NetworkReader,
-    ) {
-        if !NetworkServerStatic::active() {
-            log_error!("Command CmdClientToServerSync called on client.");
-            return;
-        }
-        // 获取 NetworkBehaviour
-        match NETWORK_BEHAVIOURS.try_get_mut(&format!("{}_{}", net_id, component_index)) {
-            TryResult::Present(mut component) => {
-                component
-                    .as_any_mut()
-                    .downcast_mut::<Self>()
-                    .unwrap()
-                    .user_code_cmd_change_ready_state_boolean(reader.read_bool());
-                NetworkBehaviour::late_invoke(net_id, component.game_object().clone());
-            }
-            TryResult::Absent => {
-                log_error!(
-                    "NetworkBehaviour not found by net_id: {}, component_index: {}",
-                    net_id,
-                    component_index
-                );
-            }
-            TryResult::Locked => {
-                log_error!(
-                    "NetworkBehaviour locked by net_id: {}, component_index: {}",
-                    net_id,
-                    component_index
-                );
-            }
-        }
-    }
-
-    fn user_code_cmd_change_ready_state_boolean(&mut self, value: bool) {
-        self.ready_to_begin = value;
-        self.set_sync_var_dirty_bits(1 << 0);
+    #[command(name = "System.Void Mirror.NetworkRoomPlayer::CmdChangeReadyState(System.Boolean)")]
+    fn cmd_change_ready_state(&mut self, value: bool) {
+        self.set_network_ready_to_begin(value);
         NetworkManagerStatic::network_manager_singleton().ready_status_changed(self);
     }
 }
 
+#[network_behaviour]
 impl NetworkBehaviourTrait for NetworkRoomPlayer {
     fn new(game_object: GameObject, network_behaviour_component: &NetworkBehaviourComponent) -> Self
     where
@@ -90,147 +47,7 @@ impl NetworkBehaviourTrait for NetworkRoomPlayer {
         Self: Sized,
     {
         // RemoteProcedureCalls.RegisterCommand(typeof (NetworkRoomPlayer), "System.Void Mirror.NetworkRoomPlayer::CmdChangeReadyState(System.Boolean)", new RemoteCallDelegate(NetworkRoomPlayer.InvokeUserCode_CmdChangeReadyState__Boolean), true);
-        RemoteProcedureCalls::register_command_delegate::<Self>(
-            "System.Void Mirror.NetworkRoomPlayer::CmdChangeReadyState(System.Boolean)",
-            Self::invoke_user_code_cmd_change_ready_state_boolean,
-            true,
-        );
-    }
-
-    fn get_once() -> &'static Once
-    where
-        Self: Sized,
-    {
-        static ONCE: Once = Once::new();
-        &ONCE
-    }
-
-    fn sync_interval(&self) -> f64 {
-        self.network_behaviour.sync_interval
-    }
-
-    fn set_sync_interval(&mut self, value: f64) {
-        self.network_behaviour.sync_interval = value;
-    }
-
-    fn last_sync_time(&self) -> f64 {
-        self.network_behaviour.last_sync_time
-    }
-
-    fn set_last_sync_time(&mut self, value: f64) {
-        self.network_behaviour.last_sync_time = value;
-    }
-
-    fn sync_direction(&mut self) -> &SyncDirection {
-        &self.network_behaviour.sync_direction
-    }
-
-    fn set_sync_direction(&mut self, value: SyncDirection) {
-        self.network_behaviour.sync_direction = value;
-    }
-
-    fn sync_mode(&mut self) -> &SyncMode {
-        &self.network_behaviour.sync_mode
-    }
-
-    fn set_sync_mode(&mut self, value: SyncMode) {
-        self.network_behaviour.sync_mode = value;
-    }
-
-    fn index(&self) -> u8 {
-        self.network_behaviour.index
-    }
-
-    fn set_index(&mut self, value: u8) {
-        self.network_behaviour.index = value;
-    }
-
-    fn sub_class(&self) -> String {
-        self.network_behaviour.sub_class.clone()
-    }
-
-    fn set_sub_class(&mut self, value: String) {
-        self.network_behaviour.sub_class = value;
-    }
-
-    fn sync_var_dirty_bits(&self) -> u64 {
-        self.network_behaviour.sync_var_dirty_bits
-    }
-
-    fn __set_sync_var_dirty_bits(&mut self, value: u64) {
-        self.network_behaviour.sync_var_dirty_bits = value;
-    }
-
-    fn sync_object_dirty_bits(&self) -> u64 {
-        self.network_behaviour.sync_object_dirty_bits
-    }
-
-    fn __set_sync_object_dirty_bits(&mut self, value: u64) {
-        self.network_behaviour.sync_object_dirty_bits = value;
-    }
-
-    fn net_id(&self) -> u32 {
-        self.network_behaviour.net_id
-    }
-
-    fn set_net_id(&mut self, value: u32) {
-        self.network_behaviour.net_id = value;
-    }
-
-    fn connection_to_client(&self) -> u64 {
-        self.network_behaviour.connection_to_client
-    }
-
-    fn set_connection_to_client(&mut self, value: u64) {
-        self.network_behaviour.connection_to_client = value;
-    }
-
-    fn observers(&self) -> &Vec<u64> {
-        &self.network_behaviour.observers
-    }
-
-    fn add_observer(&mut self, conn_id: u64) {
-        self.network_behaviour.observers.push(conn_id);
-    }
-
-    fn remove_observer(&mut self, value: u64) {
-        self.network_behaviour.observers.retain(|&x| x != value);
-    }
-
-    fn game_object(&self) -> &GameObject {
-        &self.network_behaviour.game_object
-    }
-
-    fn set_game_object(&mut self, value: GameObject) {
-        self.network_behaviour.game_object = value
-    }
-
-    fn sync_objects(&mut self) -> &mut Vec<Box<dyn SyncObject>> {
-        &mut self.network_behaviour.sync_objects
-    }
-
-    fn set_sync_objects(&mut self, value: Vec<Box<dyn SyncObject>>) {
-        self.network_behaviour.sync_objects = value
-    }
-
-    fn add_sync_object(&mut self, value: Box<dyn SyncObject>) {
-        self.network_behaviour.sync_objects.push(value);
-    }
-
-    fn sync_var_hook_guard(&self) -> u64 {
-        self.network_behaviour.sync_var_hook_guard
-    }
-
-    fn __set_sync_var_hook_guard(&mut self, value: u64) {
-        self.network_behaviour.sync_var_hook_guard = value
-    }
-
-    fn is_dirty(&self) -> bool {
-        self.network_behaviour.is_dirty()
-    }
-
-    fn as_any_mut(&mut self) -> &mut dyn Any {
-        self
+        Self::register_cmd_change_ready_state_delegate();
     }
 
     // 在第一次 update 之前仅调用一次
@@ -241,8 +58,6 @@ impl NetworkBehaviourTrait for NetworkRoomPlayer {
         }
 
         let network_manager = NetworkManagerStatic::network_manager_singleton();
-        if network_manager.dont_destroy_on_load() {}
-
         network_manager.room_slots().push(self.net_id());
 
         if NetworkServerStatic::active() {
@@ -250,8 +65,7 @@ impl NetworkBehaviourTrait for NetworkRoomPlayer {
             let (index, net_id) = network_manager.recalculate_room_player_indices();
             match net_id == self.net_id() {
                 true => {
-                    self.index = index;
-                    self.set_sync_var_dirty_bits(1 << 1);
+                    self.set_network_index(index);
                 }
                 false => {
                     log_error!("Please fix the code, this should not happen.");
@@ -261,23 +75,155 @@ impl NetworkBehaviourTrait for NetworkRoomPlayer {
         // 设置为 false，表示已经运行过 start 方法
         self.network_behaviour.run_start = false;
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirror::core::backend_data::NetworkBehaviourSetting;
+    use crate::mirror::core::network_reader::NetworkReader;
+    use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
 
-    fn serialize_sync_vars(&mut self, writer: &mut NetworkWriter, initial_state: bool) {
+    fn room_player() -> NetworkRoomPlayer {
+        let mut player = NetworkRoomPlayer {
+            network_behaviour: NetworkBehaviour::new(
+                GameObject::default(),
+                NetworkBehaviourSetting::default(),
+                0,
+                "".to_string(),
+            ),
+            ready_to_begin: false,
+            index: 0,
+        };
+        player.clear_all_dirty_bits();
+        player
+    }
+
+    // 移除前手写的 SerializeSyncVars
+    fn legacy_serialize_sync_vars(
+        player: &NetworkRoomPlayer,
+        writer: &mut NetworkWriter,
+        initial_state: bool,
+    ) {
         if initial_state {
-            writer.write_bool(self.ready_to_begin);
-            writer.compress_var_int(self.index);
+            writer.write_bool(player.ready_to_begin);
+            writer.compress_var_int(player.index);
         } else {
-            writer.compress_var_ulong(self.sync_var_dirty_bits());
-            if self.sync_var_dirty_bits() & (1 << 0) != 0 {
-                writer.write_bool(self.ready_to_begin);
+            writer.compress_var_ulong(player.sync_var_dirty_bits());
+            if player.sync_var_dirty_bits() & (1 << 0) != 0 {
+                writer.write_bool(player.ready_to_begin);
             }
-            if self.sync_var_dirty_bits() & (1 << 1) != 0 {
-                writer.compress_var_int(self.index);
+            if player.sync_var_dirty_bits() & (1 << 1) != 0 {
+                writer.compress_var_int(player.index);
             }
         }
     }
 
-    fn deserialize_sync_vars(&mut self, _reader: &mut NetworkReader, _initial_state: bool) -> bool {
-        true
+    fn assert_same_bytes(player: &mut NetworkRoomPlayer, initial_state: bool) {
+        let mut expected = NetworkWriter::new();
+        legacy_serialize_sync_vars(player, &mut expected, initial_state);
+        let mut writer = NetworkWriter::new();
+        player.serialize_sync_vars(&mut writer, initial_state);
+        assert_eq!(writer.to_bytes(), expected.to_bytes());
+    }
+
+    #[test]
+    fn test_sync_vars_match_legacy_serialization() {
+        let mut player = room_player();
+        player.set_network_ready_to_begin(true);
+        player.set_network_index(300);
+        assert_eq!(player.sync_var_dirty_bits(), 0b11);
+        assert_same_bytes(&mut player, true);
+        assert_same_bytes(&mut player, false);
+
+        // 只有 index 变化时脏位只包含 index
+        player.clear_all_dirty_bits();
+        player.set_network_index(-5);
+        assert_eq!(player.sync_var_dirty_bits(), 0b10);
+        assert_same_bytes(&mut player, false);
+
+        // 设置相同的值不会设置脏位
+        player.clear_all_dirty_bits();
+        player.set_network_ready_to_begin(true);
+        assert_eq!(player.sync_var_dirty_bits(), 0);
+        assert_same_bytes(&mut player, false);
+
+        // 反序列化得到相同的字段
+        let mut writer = NetworkWriter::new();
+        player.serialize_sync_vars(&mut writer, true);
+        let mut copy = room_player();
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        assert!(copy.deserialize_sync_vars(&mut reader, true));
+        assert!(copy.ready_to_begin);
+        assert_eq!(copy.index, -5);
+    }
+
+    #[derive(Debug, SyncVars)]
+    struct HookedPlayer {
+        network_behaviour: NetworkBehaviour,
+        #[sync_var(hook = "on_index_changed")]
+        index: i32,
+        hook_calls: Vec<(i32, i32)>,
+    }
+
+    impl HookedPlayer {
+        fn on_index_changed(&mut self, old_value: i32, new_value: i32) {
+            self.hook_calls.push((old_value, new_value));
+            // hook 中再次设置同一个 SyncVar 不会再次调用 hook
+            self.set_network_index(new_value + 1);
+        }
+    }
+
+    #[network_behaviour(component = "Mirror.HookedPlayer")]
+    impl NetworkBehaviourTrait for HookedPlayer {
+        fn new(game_object: GameObject, component: &NetworkBehaviourComponent) -> Self
+        where
+            Self: Sized,
+        {
+            Self {
+                network_behaviour: NetworkBehaviour::new(
+                    game_object,
+                    component.network_behaviour_setting,
+                    component.index,
+                    component.sub_class.clone(),
+                ),
+                index: 0,
+                hook_calls: Vec::new(),
+            }
+        }
+
+        fn register_delegate()
+        where
+            Self: Sized,
+        {
+        }
+    }
+
+    #[test]
+    fn test_sync_var_hook_guard() {
+        let mut player = HookedPlayer {
+            network_behaviour: NetworkBehaviour::new(
+                GameObject::default(),
+                NetworkBehaviourSetting::default(),
+                0,
+                "".to_string(),
+            ),
+            index: 0,
+            hook_calls: Vec::new(),
+        };
+        player.clear_all_dirty_bits();
+        player.set_network_index(1);
+        assert_eq!(player.hook_calls, vec![(0, 1)]);
+        assert_eq!(player.index, 2);
+        assert_eq!(player.sync_var_dirty_bits(), 1);
+        assert!(!player.get_sync_var_hook_guard(1));
+
+        // 反序列化时值变化才调用 hook
+        let mut writer = NetworkWriter::new();
+        writer.compress_var_ulong(1);
+        writer.compress_var_int(2);
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        assert!(player.deserialize_sync_vars(&mut reader, false));
+        assert_eq!(player.hook_calls.len(), 1);
     }
 }
@@ -1,3 +1,4 @@
+use crate::mirror::components::network_rigidbody::network_rigidbody_base::NetworkRigidbodyBase;
 use crate::mirror::components::network_transform::transform_snapshot::TransformSnapshot;
 use crate::mirror::core::backend_data::{NetworkBehaviourSetting, NetworkTransformBaseSetting};
 use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviour};
@@ -44,6 +45,8 @@ pub struct NetworkTransformBase {
     pub interpolate_scale: bool,
     pub send_interval_multiplier: u32,
     pub timeline_offset: bool,
+    // NetworkRigidbody* 组件的刚体状态
+    pub rigidbody: Option<NetworkRigidbodyBase>,
 }
 
 impl NetworkTransformBase {
@@ -66,6 +69,7 @@ impl NetworkTransformBase {
             coordinate_space: CoordinateSpace::from_u8(network_transform_base_setting.coordinate_space),
             send_interval_multiplier: network_transform_base_setting.send_interval_multiplier,
             timeline_offset: network_transform_base_setting.timeline_offset,
+            rigidbody: None,
         };
         base.time_stamp_adjustment = NetworkServerStatic::send_interval() as f64 * (base.send_interval_multiplier as f64 - 1.0);
         if base.timeline_offset {
@@ -76,6 +80,20 @@ impl NetworkTransformBase {
     pub fn reset_state(&mut self) {
         self.server_snapshots.clear();
     }
+    // 热重载时只应用不影响传输格式的设置
+    pub fn apply_setting(&mut self, network_transform_base_setting: NetworkTransformBaseSetting) {
+        self.only_sync_on_change = network_transform_base_setting.only_sync_on_change;
+        self.interpolate_position = network_transform_base_setting.interpolate_position;
+        self.interpolate_rotation = network_transform_base_setting.interpolate_rotation;
+        self.interpolate_scale = network_transform_base_setting.interpolate_scale;
+        self.send_interval_multiplier = network_transform_base_setting.send_interval_multiplier;
+        self.timeline_offset = network_transform_base_setting.timeline_offset;
+        self.time_stamp_adjustment = NetworkServerStatic::send_interval() as f64 * (self.send_interval_multiplier as f64 - 1.0);
+        self.offset = match self.timeline_offset {
+            true => NetworkServerStatic::send_interval() as f64 * self.send_interval_multiplier as f64,
+            false => 0.0,
+        };
+    }
 }
 
 pub trait NetworkTransformBaseTrait {
@@ -132,6 +150,29 @@ pub trait NetworkTransformBaseTrait {
         self.set_game_object(game_object);
     }
     // Construct()
+    fn rigidbody(&mut self) -> &mut Option<NetworkRigidbodyBase>;
+    fn rigidbody_changed(&mut self) -> bool {
+        match self.rigidbody() {
+            Some(rigidbody) => rigidbody.changed(),
+            None => false,
+        }
+    }
+    // 更新刚体状态，物理模拟驱动时把结果应用到 Transform
+    fn update_rigidbody(&mut self, net_id: u32, server_authority: bool) {
+        let mut rigidbody = match self.rigidbody().take() {
+            Some(rigidbody) => rigidbody,
+            None => return,
+        };
+        let position = self.get_position();
+        let rotation = self.get_rotation();
+        if let Some((position, rotation)) =
+            rigidbody.step(net_id, server_authority, position, rotation)
+        {
+            self.set_position(position);
+            self.set_rotation(rotation);
+        }
+        *self.rigidbody() = Some(rigidbody);
+    }
     fn construct(&self) -> TransformSnapshot {
         TransformSnapshot {
             position: self.get_position(),
@@ -236,8 +277,10 @@ impl Transform {
             local_scale,
         }
     }
+}
 
-    pub fn default() -> Self {
+impl Default for Transform {
+    fn default() -> Self {
         Self::new(Vector3::new(0.0, 0.5, 0.0),
                   Quaternion::new(1.0, 0.0, 0.0, 0.0),
                   Vector3::new(1.0, 1.0, 1.0),
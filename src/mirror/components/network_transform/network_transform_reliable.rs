@@ -1,10 +1,11 @@
 use crate::log_error;
+use crate::mirror::components::network_rigidbody::network_rigidbody_base::NetworkRigidbodyBase;
 use crate::mirror::components::network_transform::network_transform_base::{
     CoordinateSpace, NetworkTransformBase, NetworkTransformBaseTrait,
 };
 use crate::mirror::components::network_transform::transform_snapshot::TransformSnapshot;
 use crate::mirror::core::backend_data::NetworkBehaviourComponent;
-use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviour, NetworkBehaviourTrait, SyncDirection, SyncMode};
+use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviour, NetworkBehaviourTrait, SyncDirection};
 use crate::mirror::core::network_connection::NetworkConnectionTrait;
 use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait};
 use crate::mirror::core::network_server::{NetworkServerStatic, NETWORK_BEHAVIOURS};
@@ -13,19 +14,17 @@ use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
 use crate::mirror::core::network_writer_pool::NetworkWriterPool;
 use crate::mirror::core::remote_calls::RemoteProcedureCalls;
 use crate::mirror::core::snapshot_interpolation::snapshot_interpolation::SnapshotInterpolation;
-use crate::mirror::core::sync_object::SyncObject;
 use crate::mirror::core::tools::accurateinterval::AccurateInterval;
 use crate::mirror::core::tools::compress::{Compress, CompressTrait};
 use crate::mirror::core::tools::delta_compression::DeltaCompression;
 use crate::mirror::core::transport::TransportChannel;
+use crate::network_behaviour;
 use dashmap::try_result::TryResult;
 use nalgebra::{Quaternion, UnitQuaternion, Vector3};
 use ordered_float::OrderedFloat;
-use std::any::Any;
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::mem::take;
-use std::sync::Once;
 
 #[derive(Debug)]
 pub struct NetworkTransformReliable {
@@ -55,7 +54,7 @@ impl NetworkTransformReliable {
         if self.sync_direction() == &SyncDirection::ClientToServer
             && self.connection_to_client() != 0
         {
-            if self.network_transform_base.server_snapshots.len() == 0 {
+            if self.network_transform_base.server_snapshots.is_empty() {
                 return;
             }
 
@@ -65,7 +64,7 @@ impl NetworkTransformReliable {
                         &mut self.network_transform_base.server_snapshots,
                         conn.remote_timeline,
                     );
-                    let computed = TransformSnapshot::transform_snapshot(from, to, t);
+                    let computed = TransformSnapshot::interpolate(from, to, t);
                     self.apply(computed, to);
                 }
                 TryResult::Absent => {
@@ -210,6 +209,7 @@ impl NetworkTransformReliable {
             >= buffer_time * tolerance_multiplier
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn rewrite_history(
         snapshots: &mut BTreeMap<OrderedFloat<f64>, TransformSnapshot>,
         remote_timestamp: f64,
@@ -385,6 +385,7 @@ impl NetworkTransformReliable {
     }
 }
 
+#[network_behaviour(field = "network_transform_base.network_behaviour")]
 impl NetworkBehaviourTrait for NetworkTransformReliable {
     fn new(game_object: GameObject, network_behaviour_component: &NetworkBehaviourComponent) -> Self
     where
@@ -441,167 +442,6 @@ impl NetworkBehaviourTrait for NetworkTransformReliable {
         );
     }
 
-    fn get_once() -> &'static Once
-    where
-        Self: Sized,
-    {
-        static ONCE: Once = Once::new();
-        &ONCE
-    }
-
-    fn sync_interval(&self) -> f64 {
-        self.network_transform_base.network_behaviour.sync_interval
-    }
-
-    fn set_sync_interval(&mut self, value: f64) {
-        self.network_transform_base.network_behaviour.sync_interval = value
-    }
-
-    fn last_sync_time(&self) -> f64 {
-        self.network_transform_base.network_behaviour.last_sync_time
-    }
-
-    fn set_last_sync_time(&mut self, value: f64) {
-        self.network_transform_base.network_behaviour.last_sync_time = value
-    }
-
-    fn sync_direction(&mut self) -> &SyncDirection {
-        &self.network_transform_base.network_behaviour.sync_direction
-    }
-
-    fn set_sync_direction(&mut self, value: SyncDirection) {
-        self.network_transform_base.network_behaviour.sync_direction = value
-    }
-
-    fn sync_mode(&mut self) -> &SyncMode {
-        &self.network_transform_base.network_behaviour.sync_mode
-    }
-
-    fn set_sync_mode(&mut self, value: SyncMode) {
-        self.network_transform_base.network_behaviour.sync_mode = value
-    }
-
-    fn index(&self) -> u8 {
-        self.network_transform_base.network_behaviour.index
-    }
-
-    fn set_index(&mut self, value: u8) {
-        self.network_transform_base.network_behaviour.index = value
-    }
-
-    fn sub_class(&self) -> String {
-        self.network_transform_base
-            .network_behaviour
-            .sub_class
-            .clone()
-    }
-
-    fn set_sub_class(&mut self, value: String) {
-        self.network_transform_base.network_behaviour.sub_class = value
-    }
-
-    fn sync_var_dirty_bits(&self) -> u64 {
-        self.network_transform_base
-            .network_behaviour
-            .sync_var_dirty_bits
-    }
-
-    fn __set_sync_var_dirty_bits(&mut self, value: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .sync_var_dirty_bits = value
-    }
-
-    fn sync_object_dirty_bits(&self) -> u64 {
-        self.network_transform_base
-            .network_behaviour
-            .sync_object_dirty_bits
-    }
-
-    fn __set_sync_object_dirty_bits(&mut self, value: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .sync_object_dirty_bits = value
-    }
-
-    fn net_id(&self) -> u32 {
-        self.network_transform_base.network_behaviour.net_id
-    }
-
-    fn set_net_id(&mut self, value: u32) {
-        self.network_transform_base.network_behaviour.net_id = value
-    }
-
-    fn connection_to_client(&self) -> u64 {
-        self.network_transform_base
-            .network_behaviour
-            .connection_to_client
-    }
-
-    fn set_connection_to_client(&mut self, value: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .connection_to_client = value
-    }
-
-    fn observers(&self) -> &Vec<u64> {
-        &self.network_transform_base.network_behaviour.observers
-    }
-
-    fn add_observer(&mut self, conn_id: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .observers
-            .push(conn_id);
-    }
-
-    fn remove_observer(&mut self, value: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .observers
-            .retain(|&x| x != value);
-    }
-
-
-    fn game_object(&self) -> &GameObject {
-        &self.network_transform_base.network_behaviour.game_object
-    }
-
-    fn set_game_object(&mut self, value: GameObject) {
-        self.network_transform_base.network_behaviour.game_object = value
-    }
-
-    fn sync_objects(&mut self) -> &mut Vec<Box<dyn SyncObject>> {
-        &mut self.network_transform_base.network_behaviour.sync_objects
-    }
-
-    fn set_sync_objects(&mut self, value: Vec<Box<dyn SyncObject>>) {
-        self.network_transform_base.network_behaviour.sync_objects = value
-    }
-
-    fn add_sync_object(&mut self, value: Box<dyn SyncObject>) {
-        self.network_transform_base
-            .network_behaviour
-            .sync_objects
-            .push(value);
-    }
-
-    fn sync_var_hook_guard(&self) -> u64 {
-        self.network_transform_base
-            .network_behaviour
-            .sync_var_hook_guard
-    }
-
-    fn __set_sync_var_hook_guard(&mut self, value: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .sync_var_hook_guard = value
-    }
-
-    fn is_dirty(&self) -> bool {
-        self.network_transform_base.network_behaviour.is_dirty()
-    }
-
     // OnSerialize()
     fn on_serialize(&mut self, writer: &mut NetworkWriter, initial_state: bool) {
         let mut snapshot = self.construct();
@@ -668,6 +508,9 @@ impl NetworkBehaviourTrait for NetworkTransformReliable {
             // set 'last'
             self.last_snapshot = snapshot;
         }
+        if let Some(rigidbody) = &mut self.network_transform_base.rigidbody {
+            rigidbody.serialize(writer, initial_state);
+        }
     }
     // OnDeserialize()
     fn on_deserialize(&mut self, reader: &mut NetworkReader, initial_state: bool) -> bool {
@@ -714,6 +557,10 @@ impl NetworkBehaviourTrait for NetworkTransformReliable {
             }
         }
 
+        if let Some(rigidbody) = &mut self.network_transform_base.rigidbody {
+            rigidbody.deserialize(reader);
+        }
+
         self.on_client_to_server_sync(position, rotation, scale);
 
         if self.sync_position() {
@@ -726,22 +573,37 @@ impl NetworkBehaviourTrait for NetworkTransformReliable {
         }
         true
     }
-    fn as_any_mut(&mut self) -> &mut dyn Any {
-        self
-    }
+
     fn update(&mut self) {
         self.update_server();
+        let server_authority = *self.sync_direction() == SyncDirection::ServerToClient
+            || self.connection_to_client() == 0;
+        self.update_rigidbody(self.net_id(), server_authority);
     }
 
     fn late_update(&mut self) {
         if self.send_interval_counter == self.network_transform_base.send_interval_multiplier
-            && (!self.network_transform_base.only_sync_on_change || self.changed(self.construct()))
+            && (!self.network_transform_base.only_sync_on_change
+            || self.changed(self.construct())
+            || self.rigidbody_changed())
         {
             self.set_dirty()
         }
         self.u_check_last_send_time();
     }
 
+    fn on_backend_data_reload(&mut self, network_behaviour_component: &NetworkBehaviourComponent) {
+        self.network_transform_base
+            .apply_setting(network_behaviour_component.network_transform_base_setting);
+        let setting = network_behaviour_component.network_transform_reliable_setting;
+        self.only_sync_on_change_correction_multiplier =
+            setting.only_sync_on_change_correction_multiplier;
+        self.rotation_sensitivity = setting.rotation_sensitivity;
+        if let Some(rigidbody) = &mut self.network_transform_base.rigidbody {
+            rigidbody.apply_setting(network_behaviour_component.network_rigidbody_setting);
+        }
+    }
+
     fn serialize_sync_vars(&mut self, _writer: &mut NetworkWriter, _initial_state: bool) {}
 
     fn deserialize_sync_vars(&mut self, _reader: &mut NetworkReader, _initial_state: bool) -> bool {
@@ -790,6 +652,10 @@ impl NetworkTransformBaseTrait for NetworkTransformReliable {
         self.network_transform_base.sync_scale
     }
 
+    fn rigidbody(&mut self) -> &mut Option<NetworkRigidbodyBase> {
+        &mut self.network_transform_base.rigidbody
+    }
+
     fn reset_state(&mut self) {
         self.network_transform_base.reset_state();
         self.last_deserialized_position = Default::default();
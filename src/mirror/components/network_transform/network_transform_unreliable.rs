@@ -1,4 +1,5 @@
 use crate::log_error;
+use crate::mirror::components::network_rigidbody::network_rigidbody_base::NetworkRigidbodyBase;
 use crate::mirror::components::network_transform::network_transform_base::{
     CoordinateSpace, NetworkTransformBase, NetworkTransformBaseTrait,
 };
@@ -6,26 +7,24 @@ use crate::mirror::components::network_transform::transform_snapshot::TransformS
 use crate::mirror::components::network_transform::transform_sync_data::{Changed, SyncData};
 use crate::mirror::core::backend_data::NetworkBehaviourComponent;
 use crate::mirror::core::messages::NetworkMessageTrait;
-use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviour, NetworkBehaviourTrait, SyncDirection, SyncMode};
+use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviour, NetworkBehaviourTrait, SyncDirection};
 use crate::mirror::core::network_connection::NetworkConnectionTrait;
 use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait};
-use crate::mirror::core::network_server::{NetworkServerStatic, NETWORK_BEHAVIOURS};
+use crate::mirror::core::network_server::{NetworkServer, NetworkServerStatic, NETWORK_BEHAVIOURS};
 use crate::mirror::core::network_time::NetworkTime;
 use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
 use crate::mirror::core::network_writer_pool::NetworkWriterPool;
 use crate::mirror::core::remote_calls::RemoteProcedureCalls;
 use crate::mirror::core::snapshot_interpolation::snapshot_interpolation::SnapshotInterpolation;
-use crate::mirror::core::sync_object::SyncObject;
 use crate::mirror::core::tools::accurateinterval::AccurateInterval;
 use crate::mirror::core::tools::compress::CompressTrait;
 use crate::mirror::core::transport::TransportChannel;
+use crate::network_behaviour;
 use dashmap::try_result::TryResult;
 use nalgebra::{Quaternion, UnitQuaternion, Vector3};
 use ordered_float::OrderedFloat;
-use std::any::Any;
 use std::collections::BTreeMap;
 use std::mem::take;
-use std::sync::Once;
 
 #[derive(Debug)]
 pub struct NetworkTransformUnreliable {
@@ -51,7 +50,7 @@ impl NetworkTransformUnreliable {
         if *self.sync_direction() == SyncDirection::ClientToServer
             && self.connection_to_client() != 0
         {
-            if self.network_transform_base.server_snapshots.len() == 0 {
+            if self.network_transform_base.server_snapshots.is_empty() {
                 return;
             }
 
@@ -61,7 +60,7 @@ impl NetworkTransformUnreliable {
                         &mut self.network_transform_base.server_snapshots,
                         conn.remote_timeline,
                     );
-                    let computed = TransformSnapshot::transform_snapshot(from, to, t);
+                    let computed = TransformSnapshot::interpolate(from, to, t);
                     self.apply(computed, to);
                 }
                 TryResult::Absent => {
@@ -185,12 +184,11 @@ impl NetworkTransformUnreliable {
             }
         }
 
-        if self.sync_scale() {
-            if (self.last_snapshot.scale - snapshot.scale).magnitude_squared()
+        if self.sync_scale()
+            && (self.last_snapshot.scale - snapshot.scale).magnitude_squared()
                 > self.scale_sensitivity * self.scale_sensitivity
-            {
-                changed |= Changed::Scale.to_u8();
-            }
+        {
+            changed |= Changed::Scale.to_u8();
         }
         changed
     }
@@ -347,28 +345,19 @@ impl NetworkTransformUnreliable {
         rotation: Option<u32>,
         scale: Option<Vector3<f32>>,
     ) {
-        let quaternion: Option<Quaternion<f32>>;
-        if rotation.is_none() {
-            if self.network_transform_base.server_snapshots.len() > 0 {
-                let (_, last_snapshot) = self
-                    .network_transform_base
-                    .server_snapshots
-                    .iter()
-                    .last()
-                    .unwrap();
-                quaternion = Some(last_snapshot.rotation);
-            } else {
-                quaternion = Some(self.get_rotation());
-            }
-        } else {
-            quaternion = Some(Quaternion::decompress(rotation.unwrap()));
-        }
+        let quaternion = match rotation {
+            None => match self.network_transform_base.server_snapshots.iter().last() {
+                Some((_, last_snapshot)) => Some(last_snapshot.rotation),
+                None => Some(self.get_rotation()),
+            },
+            Some(rotation) => Some(Quaternion::decompress(rotation)),
+        };
         self.on_client_to_server_sync_nullable_1_nullable_1_nullable_1(position, quaternion, scale);
     }
 
     // &mut Box<dyn NetworkBehaviourTrait>, &mut NetworkReader, u64
     fn invoke_user_code_cmd_client_to_server_sync_sync_data(
-        _conn_id: u64,
+        conn_id: u64,
         net_id: u32,
         component_index: u8,
         _func_hash: u16,
@@ -378,7 +367,10 @@ impl NetworkTransformUnreliable {
             log_error!("Command CmdClientToServerSync called on client.");
             return;
         }
-        let sync_data = SyncData::deserialize(reader);
+        let sync_data = match NetworkServer::read_message::<SyncData>(conn_id, reader) {
+            Some(sync_data) => sync_data,
+            None => return,
+        };
 
         // 获取 NetworkBehaviour
         match NETWORK_BEHAVIOURS.try_get_mut(&format!("{}_{}", net_id, component_index)) {
@@ -554,7 +546,7 @@ impl NetworkTransformUnreliable {
             }
         } else {
             // x
-            if sync_data.changed_data_byte & Changed::PosX.to_u8() <= 0 {
+            if sync_data.changed_data_byte & Changed::PosX.to_u8() == 0 {
                 if let Some((_, last_snapshot)) = snapshots.iter().last() {
                     sync_data.position.x = last_snapshot.position.x;
                 } else {
@@ -562,7 +554,7 @@ impl NetworkTransformUnreliable {
                 }
             }
             // y
-            if sync_data.changed_data_byte & Changed::PosY.to_u8() <= 0 {
+            if sync_data.changed_data_byte & Changed::PosY.to_u8() == 0 {
                 if let Some((_, last_snapshot)) = snapshots.iter().last() {
                     sync_data.position.y = last_snapshot.position.y;
                 } else {
@@ -570,7 +562,7 @@ impl NetworkTransformUnreliable {
                 }
             }
             // z
-            if sync_data.changed_data_byte & Changed::PosZ.to_u8() <= 0 {
+            if sync_data.changed_data_byte & Changed::PosZ.to_u8() == 0 {
                 if let Some((_, last_snapshot)) = snapshots.iter().last() {
                     sync_data.position.z = last_snapshot.position.z;
                 } else {
@@ -580,7 +572,7 @@ impl NetworkTransformUnreliable {
 
             if sync_data.changed_data_byte & Changed::CompressRot.to_u8() == 0 {
                 // Rot x
-                if sync_data.changed_data_byte & Changed::RotX.to_u8() <= 0 {
+                if sync_data.changed_data_byte & Changed::RotX.to_u8() == 0 {
                     if let Some((_, last_snapshot)) = snapshots.iter().last() {
                         let euler_angles =
                             UnitQuaternion::from_quaternion(last_snapshot.rotation).euler_angles();
@@ -592,7 +584,7 @@ impl NetworkTransformUnreliable {
                     }
                 }
                 // Rot y
-                if sync_data.changed_data_byte & Changed::RotY.to_u8() <= 0 {
+                if sync_data.changed_data_byte & Changed::RotY.to_u8() == 0 {
                     if let Some((_, last_snapshot)) = snapshots.iter().last() {
                         let euler_angles =
                             UnitQuaternion::from_quaternion(last_snapshot.rotation).euler_angles();
@@ -604,7 +596,7 @@ impl NetworkTransformUnreliable {
                     }
                 }
                 // Rot z
-                if sync_data.changed_data_byte & Changed::RotZ.to_u8() <= 0 {
+                if sync_data.changed_data_byte & Changed::RotZ.to_u8() == 0 {
                     if let Some((_, last_snapshot)) = snapshots.iter().last() {
                         let euler_angles =
                             UnitQuaternion::from_quaternion(last_snapshot.rotation).euler_angles();
@@ -616,7 +608,7 @@ impl NetworkTransformUnreliable {
                     }
                 }
             } else {
-                if sync_data.changed_data_byte & Changed::CompressRot.to_u8() <= 0 {
+                if sync_data.changed_data_byte & Changed::CompressRot.to_u8() == 0 {
                     if let Some((_, last_snapshot)) = snapshots.iter().last() {
                         sync_data.quat_rotation = last_snapshot.rotation;
                     } else {
@@ -624,7 +616,7 @@ impl NetworkTransformUnreliable {
                     }
                 }
             }
-            if sync_data.changed_data_byte & Changed::Scale.to_u8() <= 0 {
+            if sync_data.changed_data_byte & Changed::Scale.to_u8() == 0 {
                 if let Some((_, last_snapshot)) = snapshots.iter().last() {
                     sync_data.scale = last_snapshot.scale;
                 } else {
@@ -824,6 +816,7 @@ impl NetworkTransformUnreliable {
     }
 }
 
+#[network_behaviour(field = "network_transform_base.network_behaviour")]
 impl NetworkBehaviourTrait for NetworkTransformUnreliable {
     fn new(
         game_object: GameObject,
@@ -899,166 +892,6 @@ impl NetworkBehaviourTrait for NetworkTransformUnreliable {
         );
     }
 
-    fn get_once() -> &'static Once
-    where
-        Self: Sized,
-    {
-        static ONCE: Once = Once::new();
-        &ONCE
-    }
-
-    fn sync_interval(&self) -> f64 {
-        self.network_transform_base.network_behaviour.sync_interval
-    }
-
-    fn set_sync_interval(&mut self, value: f64) {
-        self.network_transform_base.network_behaviour.sync_interval = value
-    }
-
-    fn last_sync_time(&self) -> f64 {
-        self.network_transform_base.network_behaviour.last_sync_time
-    }
-
-    fn set_last_sync_time(&mut self, value: f64) {
-        self.network_transform_base.network_behaviour.last_sync_time = value
-    }
-
-    fn sync_direction(&mut self) -> &SyncDirection {
-        &self.network_transform_base.network_behaviour.sync_direction
-    }
-
-    fn set_sync_direction(&mut self, value: SyncDirection) {
-        self.network_transform_base.network_behaviour.sync_direction = value
-    }
-
-    fn sync_mode(&mut self) -> &SyncMode {
-        &self.network_transform_base.network_behaviour.sync_mode
-    }
-
-    fn set_sync_mode(&mut self, value: SyncMode) {
-        self.network_transform_base.network_behaviour.sync_mode = value
-    }
-
-    fn index(&self) -> u8 {
-        self.network_transform_base.network_behaviour.index
-    }
-
-    fn set_index(&mut self, value: u8) {
-        self.network_transform_base.network_behaviour.index = value
-    }
-
-    fn sub_class(&self) -> String {
-        self.network_transform_base
-            .network_behaviour
-            .sub_class
-            .clone()
-    }
-
-    fn set_sub_class(&mut self, value: String) {
-        self.network_transform_base.network_behaviour.sub_class = value
-    }
-
-    fn sync_var_dirty_bits(&self) -> u64 {
-        self.network_transform_base
-            .network_behaviour
-            .sync_var_dirty_bits
-    }
-
-    fn __set_sync_var_dirty_bits(&mut self, value: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .sync_var_dirty_bits = value
-    }
-
-    fn sync_object_dirty_bits(&self) -> u64 {
-        self.network_transform_base
-            .network_behaviour
-            .sync_object_dirty_bits
-    }
-
-    fn __set_sync_object_dirty_bits(&mut self, value: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .sync_object_dirty_bits = value
-    }
-
-    fn net_id(&self) -> u32 {
-        self.network_transform_base.network_behaviour.net_id
-    }
-
-    fn set_net_id(&mut self, value: u32) {
-        self.network_transform_base.network_behaviour.net_id = value
-    }
-
-    fn connection_to_client(&self) -> u64 {
-        self.network_transform_base
-            .network_behaviour
-            .connection_to_client
-    }
-
-    fn set_connection_to_client(&mut self, value: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .connection_to_client = value
-    }
-
-    fn observers(&self) -> &Vec<u64> {
-        &self.network_transform_base.network_behaviour.observers
-    }
-
-    fn add_observer(&mut self, conn_id: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .observers
-            .push(conn_id);
-    }
-
-    fn remove_observer(&mut self, value: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .observers
-            .retain(|&x| x != value);
-    }
-
-
-    fn game_object(&self) -> &GameObject {
-        &self.network_transform_base.network_behaviour.game_object
-    }
-
-    fn set_game_object(&mut self, value: GameObject) {
-        self.network_transform_base.network_behaviour.game_object = value
-    }
-
-    fn sync_objects(&mut self) -> &mut Vec<Box<dyn SyncObject>> {
-        &mut self.network_transform_base.network_behaviour.sync_objects
-    }
-
-    fn set_sync_objects(&mut self, value: Vec<Box<dyn SyncObject>>) {
-        self.network_transform_base.network_behaviour.sync_objects = value
-    }
-
-    fn add_sync_object(&mut self, value: Box<dyn SyncObject>) {
-        self.network_transform_base
-            .network_behaviour
-            .sync_objects
-            .push(value);
-    }
-
-    fn sync_var_hook_guard(&self) -> u64 {
-        self.network_transform_base
-            .network_behaviour
-            .sync_var_hook_guard
-    }
-
-    fn __set_sync_var_hook_guard(&mut self, value: u64) {
-        self.network_transform_base
-            .network_behaviour
-            .sync_var_hook_guard = value
-    }
-
-    fn is_dirty(&self) -> bool {
-        self.network_transform_base.network_behaviour.is_dirty()
-    }
     fn on_serialize(&mut self, writer: &mut NetworkWriter, initial_state: bool) {
         if initial_state {
             if self.network_transform_base.sync_position {
@@ -1071,17 +904,37 @@ impl NetworkBehaviourTrait for NetworkTransformUnreliable {
                 writer.write_vector3(self.get_scale());
             }
         }
-    }
-    fn as_any_mut(&mut self) -> &mut dyn Any {
-        self
+        // 位置通过不可靠 rpc 同步，刚体状态通过状态同步
+        if let Some(rigidbody) = &mut self.network_transform_base.rigidbody {
+            rigidbody.serialize(writer, initial_state);
+        }
     }
 
     fn update(&mut self) {
         self.update_server_interpolation();
+        let server_authority = *self.sync_direction() == SyncDirection::ServerToClient
+            || self.connection_to_client() == 0;
+        self.update_rigidbody(self.net_id(), server_authority);
     }
 
     fn late_update(&mut self) {
         self.update_server_broadcast();
+        if self.rigidbody_changed() {
+            self.set_dirty();
+        }
+    }
+
+    fn on_backend_data_reload(&mut self, network_behaviour_component: &NetworkBehaviourComponent) {
+        self.network_transform_base
+            .apply_setting(network_behaviour_component.network_transform_base_setting);
+        let setting = network_behaviour_component.network_transform_unreliable_setting;
+        self.buffer_reset_multiplier = setting.buffer_reset_multiplier;
+        self.position_sensitivity = setting.position_sensitivity;
+        self.rotation_sensitivity = setting.rotation_sensitivity;
+        self.scale_sensitivity = setting.scale_sensitivity;
+        if let Some(rigidbody) = &mut self.network_transform_base.rigidbody {
+            rigidbody.apply_setting(network_behaviour_component.network_rigidbody_setting);
+        }
     }
 
     fn serialize_sync_vars(&mut self, _writer: &mut NetworkWriter, _initial_state: bool) {}
@@ -1132,6 +985,10 @@ impl NetworkTransformBaseTrait for NetworkTransformUnreliable {
         self.network_transform_base.sync_scale
     }
 
+    fn rigidbody(&mut self) -> &mut Option<NetworkRigidbodyBase> {
+        &mut self.network_transform_base.rigidbody
+    }
+
     fn reset_state(&mut self) {
         self.network_transform_base.reset_state();
     }
@@ -23,7 +23,16 @@ impl TransformSnapshot {
         }
     }
 
-    pub fn default() -> Self {
+    pub fn interpolate(from: TransformSnapshot, to: TransformSnapshot, t: f64) -> TransformSnapshot {
+        let position = Vector3::lerp(&from.position, &to.position, t as f32);
+        let rotation = Quaternion::lerp(&from.rotation, &to.rotation, t as f32);
+        let scale = Vector3::lerp(&from.scale, &to.scale, t as f32);
+        TransformSnapshot::new(0.0, 0.0, position, rotation, scale)
+    }
+}
+
+impl Default for TransformSnapshot {
+    fn default() -> Self {
         Self {
             remote_time: 0.0,
             local_time: 0.0,
@@ -32,13 +41,6 @@ impl TransformSnapshot {
             scale: Vector3::new(1.0, 1.0, 1.0),
         }
     }
-
-    pub fn transform_snapshot(from: TransformSnapshot, to: TransformSnapshot, t: f64) -> TransformSnapshot {
-        let position = Vector3::lerp(&from.position, &to.position, t as f32);
-        let rotation = Quaternion::lerp(&from.rotation, &to.rotation, t as f32);
-        let scale = Vector3::lerp(&from.scale, &to.scale, t as f32);
-        TransformSnapshot::new(0.0, 0.0, position, rotation, scale)
-    }
 }
 
 impl Eq for TransformSnapshot {}
@@ -54,7 +56,7 @@ impl Ord for TransformSnapshot {
 }
 impl PartialOrd for TransformSnapshot {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.remote_time.partial_cmp(&other.remote_time)
+        Some(self.cmp(other))
     }
 }
 
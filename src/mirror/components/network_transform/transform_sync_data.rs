@@ -1,5 +1,5 @@
 use crate::mirror::core::messages::NetworkMessageTrait;
-use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait};
+use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTryTrait, ReadError};
 use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
 use crate::mirror::core::tools::compress::CompressTrait;
 use nalgebra::{Quaternion, UnitQuaternion, Vector3};
@@ -35,20 +35,20 @@ impl SyncData {
 }
 
 impl NetworkMessageTrait for SyncData {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
         // 改变的数据
-        let changed = reader.read_byte();
+        let changed = reader.try_read_byte()?;
 
         // 位置
         let mut position = Vector3::new(0.0, 0.0, 0.0);
         if (changed & Changed::PosX.to_u8()) > 0 {
-            position.x = reader.read_float();
+            position.x = reader.try_read_float()?;
         }
         if changed & Changed::PosY.to_u8() > 0 {
-            position.y = reader.read_float();
+            position.y = reader.try_read_float()?;
         }
         if changed & Changed::PosZ.to_u8() > 0 {
-            position.z = reader.read_float();
+            position.z = reader.try_read_float()?;
         }
 
         // 四元数
@@ -58,26 +58,26 @@ impl NetworkMessageTrait for SyncData {
 
         if (changed & Changed::CompressRot.to_u8()) > 0 {
             if (changed & Changed::RotX.to_u8()) > 0 {
-                quaternion = Quaternion::decompress(reader.read_uint());
+                quaternion = Quaternion::decompress(reader.try_read_uint()?);
             }
         } else {
             if changed & Changed::RotX.to_u8() > 0 {
-                vec_rotation.x = reader.read_float();
+                vec_rotation.x = reader.try_read_float()?;
             }
             if changed & Changed::RotY.to_u8() > 0 {
-                vec_rotation.y = reader.read_float();
+                vec_rotation.y = reader.try_read_float()?;
             }
             if changed & Changed::RotZ.to_u8() > 0 {
-                vec_rotation.z = reader.read_float();
+                vec_rotation.z = reader.try_read_float()?;
             }
         }
 
         // 缩放
         let mut scale = Vector3::new(1.0, 1.0, 1.0);
         if changed & Changed::Scale.to_u8() == Changed::Scale.to_u8() {
-            scale.x = reader.read_float();
-            scale.y = reader.read_float();
-            scale.z = reader.read_float();
+            scale.x = reader.try_read_float()?;
+            scale.y = reader.try_read_float()?;
+            scale.z = reader.try_read_float()?;
         }
 
         if changed & Changed::CompressRot.to_u8() > 0 {
@@ -86,13 +86,13 @@ impl NetworkMessageTrait for SyncData {
             quaternion = *UnitQuaternion::from_euler_angles(vec_rotation.x, vec_rotation.y, vec_rotation.z);
         }
 
-        Self {
+        Ok(Self {
             changed_data_byte: changed,
             position,
             quat_rotation: quaternion,
             vec_rotation,
             scale,
-        }
+        })
     }
 
     fn serialize(&mut self, write: &mut NetworkWriter) {
@@ -0,0 +1,116 @@
+use crate::log_error;
+use crate::mirror::core::network_identity::NetworkIdentity;
+use lazy_static::lazy_static;
+use std::sync::RwLock;
+
+lazy_static! {
+    static ref AUTHORITY_POLICY: RwLock<Option<Box<dyn AuthorityPolicy>>> = RwLock::new(None);
+}
+
+// 客户端请求权限时由服务器决定是否同意
+// 回调时 identity 处于锁定状态，不要再通过 spawned_network_identities 访问它
+pub trait AuthorityPolicy: Send + Sync {
+    // 请求获得权限，对象可能已经属于其他连接
+    fn on_request_authority(&self, conn_id: u64, identity: &NetworkIdentity) -> bool;
+    // 请求释放权限
+    fn on_release_authority(&self, conn_id: u64, identity: &NetworkIdentity) -> bool {
+        let _ = (conn_id, identity);
+        true
+    }
+}
+
+// 只允许请求没有拥有者的对象
+pub struct UnownedAuthorityPolicy;
+
+impl AuthorityPolicy for UnownedAuthorityPolicy {
+    fn on_request_authority(&self, _conn_id: u64, identity: &NetworkIdentity) -> bool {
+        identity.connection_to_client() == 0
+    }
+}
+
+pub struct AuthorityPolicyStatic;
+
+impl AuthorityPolicyStatic {
+    pub fn set_authority_policy(policy: Box<dyn AuthorityPolicy>) {
+        match AUTHORITY_POLICY.write() {
+            Ok(mut authority_policy) => *authority_policy = Some(policy),
+            Err(e) => log_error!(format!("AuthorityPolicy.set error: {}", e)),
+        }
+    }
+    pub fn clear_authority_policy() {
+        match AUTHORITY_POLICY.write() {
+            Ok(mut authority_policy) => *authority_policy = None,
+            Err(e) => log_error!(format!("AuthorityPolicy.clear error: {}", e)),
+        }
+    }
+
+    // 没有设置策略时拒绝所有请求
+    pub fn request_authority(conn_id: u64, identity: &NetworkIdentity) -> bool {
+        match AUTHORITY_POLICY.read() {
+            Ok(authority_policy) => match authority_policy.as_ref() {
+                Some(policy) => policy.on_request_authority(conn_id, identity),
+                None => false,
+            },
+            Err(e) => {
+                log_error!(format!("AuthorityPolicy.request error: {}", e));
+                false
+            }
+        }
+    }
+    pub fn release_authority(conn_id: u64, identity: &NetworkIdentity) -> bool {
+        match AUTHORITY_POLICY.read() {
+            Ok(authority_policy) => match authority_policy.as_ref() {
+                Some(policy) => policy.on_release_authority(conn_id, identity),
+                None => false,
+            },
+            Err(e) => {
+                log_error!(format!("AuthorityPolicy.release error: {}", e));
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirror::core::network_server::tests::lock_server;
+
+    // 只允许 conn_id 1 获得和释放权限
+    struct FirstConnectionPolicy;
+
+    impl AuthorityPolicy for FirstConnectionPolicy {
+        fn on_request_authority(&self, conn_id: u64, _identity: &NetworkIdentity) -> bool {
+            conn_id == 1
+        }
+        fn on_release_authority(&self, conn_id: u64, _identity: &NetworkIdentity) -> bool {
+            conn_id == 1
+        }
+    }
+
+    #[test]
+    fn test_authority_policy() {
+        let _lock = lock_server();
+        let mut identity = NetworkIdentity::new_with_asset_id(0);
+
+        // 没有设置策略时拒绝所有请求
+        AuthorityPolicyStatic::clear_authority_policy();
+        assert!(!AuthorityPolicyStatic::request_authority(1, &identity));
+        assert!(!AuthorityPolicyStatic::release_authority(1, &identity));
+
+        AuthorityPolicyStatic::set_authority_policy(Box::new(UnownedAuthorityPolicy));
+        assert!(AuthorityPolicyStatic::request_authority(1, &identity));
+        // 默认允许释放
+        assert!(AuthorityPolicyStatic::release_authority(2, &identity));
+        identity.set_client_owner(2);
+        assert!(!AuthorityPolicyStatic::request_authority(1, &identity));
+
+        AuthorityPolicyStatic::set_authority_policy(Box::new(FirstConnectionPolicy));
+        assert!(AuthorityPolicyStatic::request_authority(1, &identity));
+        assert!(!AuthorityPolicyStatic::request_authority(2, &identity));
+        assert!(!AuthorityPolicyStatic::release_authority(2, &identity));
+
+        AuthorityPolicyStatic::clear_authority_policy();
+        assert!(!AuthorityPolicyStatic::request_authority(1, &identity));
+    }
+}
@@ -3,21 +3,35 @@ use crate::mirror::core::network_behaviour::GameObject;
 use crate::mirror::core::network_identity::NetworkIdentity;
 use crate::mirror::core::network_loop::NetworkLoop;
 use crate::mirror::transports::kcp2k::kcp2k_transport::Kcp2kTransportConfig;
-use crate::{log_error, log_info};
-use config::Config;
+use crate::{log_error, log_info, log_warn};
+use atomic::Atomic;
+use config::{Config, FileFormat};
 use lazy_static::lazy_static;
+use nalgebra::Vector3;
 use notify::event::{DataChange, ModifyKind};
 use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::collections::{HashSet, VecDeque};
 use std::path::Path;
+use std::sync::atomic::Ordering;
 use std::sync::mpsc::channel;
 use std::sync::{OnceLock, RwLock};
 use std::time::Duration;
 
 lazy_static! {
     static ref BACKEND_DATA_FILE: String = "tobackend.json".to_string();
+    // 热重载后是否需要把新设置应用到已生成的对象上
+    static ref BACKEND_DATA_RELOADED: Atomic<bool> = Atomic::new(false);
+}
+
+// 热重载结果
+#[derive(Debug, Clone, Default)]
+pub struct BackendDataReloadReport {
+    // 已在线应用的修改
+    pub applied: Vec<String>,
+    // 需要重启才能生效的修改
+    pub restart_required: Vec<String>,
 }
 
 pub struct BackendDataStatic;
@@ -37,6 +51,9 @@ impl BackendDataStatic {
                         network_room_manager_settings: Vec::new(),
                         scene_ids: Vec::new(),
                         sync_vars: Vec::new(),
+                        sync_objects: Vec::new(),
+                        enums: Vec::new(),
+                        structs: Vec::new(),
                         assets: Vec::new(),
                     };
                     serde_json::to_string_pretty(&backend_data).unwrap()
@@ -75,7 +92,7 @@ impl BackendDataStatic {
                 Path::new(BACKEND_DATA_FILE.as_str()),
                 RecursiveMode::NonRecursive,
             )
-            .unwrap_or_else(|_| {});
+            .unwrap_or(());
 
         // This is a simple loop, but you may want to use more complex logic here,
         // for example to handle I/O.
@@ -88,19 +105,7 @@ impl BackendDataStatic {
                        kind: notify::event::EventKind::Modify(ModifyKind::Data(DataChange::Content)),
                        ..
                    }) => {
-                    match Config::builder()
-                        .add_source(config::File::with_name(BACKEND_DATA_FILE.as_str()))
-                        .build()
-                    {
-                        Ok(backend_data) => {
-                            *Self::tobackend().write().unwrap() = backend_data;
-                            NetworkLoop::set_stop(true);
-                            log_info!(format!("{} has been updated", BACKEND_DATA_FILE.as_str()));
-                        }
-                        Err(e) => {
-                            log_error!(format!("watch error: {:?}", e));
-                        }
-                    }
+                    Self::reload();
                 }
                 Err(e) => {
                     log_error!(format!("watch error: {:?}", e));
@@ -110,6 +115,86 @@ impl BackendDataStatic {
         }
     }
 
+    // 重新读取 tobackend.json，在线应用安全的修改，并报告需要重启的修改
+    pub fn reload() -> Option<BackendDataReloadReport> {
+        let new_backend_data = match Config::builder()
+            .add_source(config::File::with_name(BACKEND_DATA_FILE.as_str()))
+            .build()
+        {
+            Ok(config) => match config.try_deserialize::<BackendData>() {
+                Ok(backend_data) => backend_data,
+                Err(e) => {
+                    log_error!(format!("reload deserialize error: {:?}", e));
+                    return None;
+                }
+            },
+            Err(e) => {
+                log_error!(format!("reload error: {:?}", e));
+                return None;
+            }
+        };
+
+        let (merged, report) = Self::get_backend_data().merge_reload(&new_backend_data);
+
+        if !report.applied.is_empty() {
+            if !Self::store(&merged) {
+                return None;
+            }
+            BACKEND_DATA_RELOADED.store(true, Ordering::Relaxed);
+        }
+
+        for applied in report.applied.iter() {
+            log_info!(format!(
+                "{} hot reload applied: {}",
+                BACKEND_DATA_FILE.as_str(),
+                applied
+            ));
+        }
+        for restart_required in report.restart_required.iter() {
+            log_warn!(format!(
+                "{} change requires restart: {}",
+                BACKEND_DATA_FILE.as_str(),
+                restart_required
+            ));
+        }
+        Some(report)
+    }
+
+    // 替换内存中的 BackendData，不写回文件
+    pub(crate) fn store(backend_data: &BackendData) -> bool {
+        let json = match serde_json::to_string(backend_data) {
+            Ok(json) => json,
+            Err(e) => {
+                log_error!(format!("reload serialize error: {:?}", e));
+                return false;
+            }
+        };
+        match Config::builder()
+            .add_source(config::File::from_str(json.as_str(), FileFormat::Json))
+            .build()
+        {
+            Ok(config) => match Self::tobackend().write() {
+                Ok(mut tobackend) => {
+                    *tobackend = config;
+                    true
+                }
+                Err(e) => {
+                    log_error!(format!("reload write error: {:?}", e));
+                    false
+                }
+            },
+            Err(e) => {
+                log_error!(format!("reload error: {:?}", e));
+                false
+            }
+        }
+    }
+
+    // 取出热重载标记，由 NetworkServer 在主循环中应用到已生成的对象
+    pub fn take_reloaded() -> bool {
+        BACKEND_DATA_RELOADED.swap(false, Ordering::Relaxed)
+    }
+
     pub fn get_backend_data() -> BackendData {
         match Self::tobackend().read().unwrap().clone().try_deserialize() {
             Ok(backend_data) => backend_data,
@@ -171,6 +256,11 @@ pub struct MethodData {
     pub rpc_list: Vec<String>,
     #[serde(rename = "varList")]
     pub var_list: Vec<KeyValue<u8, String>>,
+    // 每个连接每秒允许的调用次数，覆盖默认的 Command 限制
+    #[serde(rename = "rateLimit", default)]
+    pub rate_limit: Option<f32>,
+    #[serde(rename = "rateLimitBurst", default)]
+    pub rate_limit_burst: Option<f32>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -187,6 +277,56 @@ pub struct SyncVarData {
     pub value: Vec<u8>,
     #[serde(rename = "dirtyBit")]
     pub dirty_bit: u32,
+    // 只同步给拥有者，观察者只能看到初始值
+    #[serde(rename = "ownerOnly", default)]
+    pub owner_only: bool,
+}
+
+// SyncList / SyncDictionary / SyncHashSet，按声明顺序排列
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SyncObjectData {
+    #[serde(rename = "fullname")]
+    pub full_name: String,
+    #[serde(rename = "subClass")]
+    pub sub_class: String,
+    #[serde(rename = "name")]
+    pub name: String,
+    // 如 Mirror.SyncList`1
+    #[serde(rename = "type")]
+    pub r#type: String,
+    // 如 ["System.Int32"]
+    #[serde(rename = "genericArguments", default)]
+    pub generic_arguments: Vec<String>,
+}
+
+// 枚举按底层类型序列化
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EnumData {
+    #[serde(rename = "fullname")]
+    pub full_name: String,
+    // 如 System.Int32
+    #[serde(rename = "underlyingType")]
+    pub underlying_type: String,
+}
+
+// 自定义结构体按字段顺序序列化
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StructData {
+    #[serde(rename = "fullname")]
+    pub full_name: String,
+    // class 前面有一个是否为 null 的 bool
+    #[serde(rename = "isClass", default)]
+    pub is_class: bool,
+    #[serde(rename = "fields")]
+    pub fields: Vec<StructFieldData>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StructFieldData {
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "type")]
+    pub r#type: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, Default)]
@@ -194,6 +334,12 @@ pub struct NetworkBehaviourSetting {
     #[serde(rename = "syncDirection")]
     /// need fix
     pub sync_direction: u8,
+    // 0: Observers 1: Owner
+    #[serde(rename = "syncMode", default)]
+    pub sync_mode: u8,
+    // 秒，0 表示每个 tick 都同步
+    #[serde(rename = "syncInterval", default)]
+    pub sync_interval: f64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, Default)]
@@ -266,6 +412,39 @@ pub struct NetworkAnimatorSetting {
     pub previous_speed: f32,
 }
 
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[serde(default)]
+pub struct NetworkRigidbodySetting {
+    #[serde(rename = "mass")]
+    pub mass: f32,
+    #[serde(rename = "useGravity")]
+    pub use_gravity: bool,
+    #[serde(rename = "isKinematic")]
+    pub is_kinematic: bool,
+    #[serde(rename = "drag")]
+    pub drag: f32,
+    #[serde(rename = "angularDrag")]
+    pub angular_drag: f32,
+    // 是否同步速度和休眠状态，需要客户端组件支持
+    #[serde(rename = "syncVelocity")]
+    pub sync_velocity: bool,
+    #[serde(rename = "velocitySensitivity")]
+    pub velocity_sensitivity: f32,
+}
+impl Default for NetworkRigidbodySetting {
+    fn default() -> Self {
+        Self {
+            mass: 1.0,
+            use_gravity: true,
+            is_kinematic: false,
+            drag: 0.0,
+            angular_drag: 0.05,
+            sync_velocity: false,
+            velocity_sensitivity: 0.1,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct NetworkBehaviourComponent {
     #[serde(rename = "componentIndex")]
@@ -282,6 +461,8 @@ pub struct NetworkBehaviourComponent {
     pub network_transform_unreliable_setting: NetworkTransformUnreliableSetting,
     #[serde(rename = "networkAnimatorSetting")]
     pub network_animator_setting: NetworkAnimatorSetting,
+    #[serde(rename = "networkRigidbodySetting", default)]
+    pub network_rigidbody_setting: NetworkRigidbodySetting,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -372,6 +553,64 @@ pub struct NetworkRoomManagerSetting {
     pub network_manager_setting: NetworkManagerSetting,
 }
 
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default, PartialEq)]
+pub struct Vector3Data {
+    #[serde(rename = "x")]
+    pub x: f32,
+    #[serde(rename = "y")]
+    pub y: f32,
+    #[serde(rename = "z")]
+    pub z: f32,
+}
+impl Vector3Data {
+    pub fn to_vector3(&self) -> Vector3<f32> {
+        Vector3::new(self.x, self.y, self.z)
+    }
+}
+
+#[derive(Serialize_repr, Deserialize_repr, Debug, Copy, Clone, PartialEq)]
+#[repr(u8)]
+pub enum ColliderShape {
+    Sphere = 0,
+    Box = 1,
+    Capsule = 2,
+}
+
+// 命中盒，center 为相对物体的偏移
+// Sphere 使用 radius，Box 使用 size，Capsule 使用 radius 和 height（沿 y 轴）
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HitboxData {
+    #[serde(rename = "name")]
+    pub name: String,
+    #[serde(rename = "shape")]
+    pub shape: ColliderShape,
+    #[serde(rename = "center", default)]
+    pub center: Vector3Data,
+    #[serde(rename = "size", default)]
+    pub size: Vector3Data,
+    #[serde(rename = "radius", default)]
+    pub radius: f32,
+    #[serde(rename = "height", default)]
+    pub height: f32,
+}
+
+// 物理碰撞体，字段含义与 HitboxData 相同
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ColliderData {
+    #[serde(rename = "shape")]
+    pub shape: ColliderShape,
+    #[serde(rename = "center", default)]
+    pub center: Vector3Data,
+    #[serde(rename = "size", default)]
+    pub size: Vector3Data,
+    #[serde(rename = "radius", default)]
+    pub radius: f32,
+    #[serde(rename = "height", default)]
+    pub height: f32,
+    #[serde(rename = "isTrigger", default)]
+    pub is_trigger: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct NetworkIdentityData {
     #[serde(rename = "assetId")]
@@ -381,6 +620,10 @@ pub struct NetworkIdentityData {
     /// need fix  dont need use KeyValue
     #[serde(rename = "networkBehaviourComponents")]
     pub network_behaviour_components: Vec<KeyValue<u8, NetworkBehaviourComponent>>,
+    #[serde(rename = "hitboxes", default)]
+    pub hitboxes: Vec<HitboxData>,
+    #[serde(rename = "colliders", default)]
+    pub colliders: Vec<ColliderData>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -399,6 +642,12 @@ pub struct BackendData {
     pub scene_ids: Vec<KeyValue<String, String>>,
     #[serde(rename = "syncVars")]
     pub sync_vars: Vec<SyncVarData>,
+    #[serde(rename = "syncObjects", default)]
+    pub sync_objects: Vec<SyncObjectData>,
+    #[serde(rename = "enums", default)]
+    pub enums: Vec<EnumData>,
+    #[serde(rename = "structs", default)]
+    pub structs: Vec<StructData>,
     #[serde(rename = "assets")]
     pub assets: Vec<KeyValue<u32, String>>,
 }
@@ -410,21 +659,15 @@ impl BackendData {
 
     #[allow(dead_code)]
     pub fn get_method_data_by_hash_code(&self, hash_code: u16) -> Option<&MethodData> {
-        for method_data in self.methods.iter() {
-            if method_data.hash_code == hash_code {
-                return Some(method_data);
-            }
-        }
-        None
+        self.methods
+            .iter()
+            .find(|method_data| method_data.hash_code == hash_code)
     }
     #[allow(dead_code)]
     pub fn get_method_data_by_method_name(&self, method_name: &str) -> Option<&MethodData> {
-        for method_data in self.methods.iter() {
-            if method_data.name == method_name {
-                return Some(method_data);
-            }
-        }
-        None
+        self.methods
+            .iter()
+            .find(|method_data| method_data.name == method_name)
     }
     #[allow(dead_code)]
     pub fn get_rpc_hash_code_s(&self, hash_code: u16) -> Vec<u16> {
@@ -450,12 +693,9 @@ impl BackendData {
         if asset_id == 0 {
             return None;
         }
-        for network_identity_data in self.network_identities.iter() {
-            if network_identity_data.asset_id == asset_id {
-                return Some(network_identity_data);
-            }
-        }
-        None
+        self.network_identities
+            .iter()
+            .find(|network_identity_data| network_identity_data.asset_id == asset_id)
     }
     #[allow(dead_code)]
     pub fn get_network_identity_data_by_scene_id(
@@ -465,12 +705,9 @@ impl BackendData {
         if scene_id == 0 {
             return None;
         }
-        for network_identity_data in self.network_identities.iter() {
-            if network_identity_data.scene_id == scene_id.to_string() {
-                return Some(network_identity_data);
-            }
-        }
-        None
+        self.network_identities
+            .iter()
+            .find(|network_identity_data| network_identity_data.scene_id == scene_id.to_string())
     }
     #[allow(dead_code)]
     pub fn get_network_identity_data_network_behaviour_components_by_asset_id(
@@ -509,6 +746,35 @@ impl BackendData {
         network_behaviour_components
     }
 
+    #[allow(dead_code)]
+    pub fn get_colliders_by_asset_id_or_scene_id(
+        &self,
+        asset_id: u32,
+        scene_id: u64,
+    ) -> Vec<ColliderData> {
+        match self.get_network_identity_data_by_asset_id(asset_id) {
+            Some(network_identity_data) => network_identity_data.colliders.clone(),
+            None => match self.get_network_identity_data_by_scene_id(scene_id) {
+                Some(network_identity_data) => network_identity_data.colliders.clone(),
+                None => Vec::new(),
+            },
+        }
+    }
+    #[allow(dead_code)]
+    pub fn get_hitboxes_by_asset_id_or_scene_id(
+        &self,
+        asset_id: u32,
+        scene_id: u64,
+    ) -> Vec<HitboxData> {
+        match self.get_network_identity_data_by_asset_id(asset_id) {
+            Some(network_identity_data) => network_identity_data.hitboxes.clone(),
+            None => match self.get_network_identity_data_by_scene_id(scene_id) {
+                Some(network_identity_data) => network_identity_data.hitboxes.clone(),
+                None => Vec::new(),
+            },
+        }
+    }
+
     pub fn get_scene_id_by_scene_name(&self, scene_name: &str) -> Option<u64> {
         for scene_id in self.scene_ids.iter() {
             if scene_id.key == scene_name {
@@ -524,6 +790,23 @@ impl BackendData {
         }
         None
     }
+    // sceneId 的高 32 位是场景路径的哈希，场景名不在 sceneIds 中时返回 None
+    pub fn get_scene_hash_by_scene_name(&self, scene_name: &str) -> Option<u32> {
+        self.get_scene_id_by_scene_name(scene_name)
+            .map(|scene_id| (scene_id >> 32) as u32)
+    }
+    // 场景中所有场景对象的名称
+    pub fn get_scene_object_names_by_scene_hash(&self, scene_hash: u32) -> Vec<String> {
+        let mut scene_object_names = Vec::new();
+        for scene_id in self.scene_ids.iter() {
+            if let Ok(id) = scene_id.value.parse::<u64>() {
+                if (id >> 32) as u32 == scene_hash {
+                    scene_object_names.push(scene_id.key.clone());
+                }
+            }
+        }
+        scene_object_names
+    }
     pub fn get_asset_id_by_asset_name(&self, asset_name: &str) -> Option<u32> {
         for asset in self.assets.iter() {
             if asset.value == asset_name {
@@ -537,15 +820,41 @@ impl BackendData {
         let mut sync_var_data_s = Vec::new();
         let mut seen_full_names = HashSet::new();
         for sync_var_data in self.sync_vars.iter() {
-            if sync_var_data.sub_class == sub_class {
-                if seen_full_names.insert(sync_var_data.full_name.clone()) {
-                    sync_var_data_s.push(sync_var_data.clone());
-                }
+            if sync_var_data.sub_class == sub_class
+                && seen_full_names.insert(sync_var_data.full_name.clone())
+            {
+                sync_var_data_s.push(sync_var_data.clone());
             }
         }
         sync_var_data_s
     }
 
+    pub fn get_sync_object_data_s_by_sub_class(&self, sub_class: &str) -> Vec<SyncObjectData> {
+        let mut sync_object_data_s = Vec::new();
+        let mut seen_full_names = HashSet::new();
+        for sync_object_data in self.sync_objects.iter() {
+            if sync_object_data.sub_class == sub_class
+                && seen_full_names.insert(sync_object_data.full_name.clone())
+            {
+                sync_object_data_s.push(sync_object_data.clone());
+            }
+        }
+        sync_object_data_s
+    }
+
+    pub fn get_enum_underlying_type(&self, full_name: &str) -> Option<&str> {
+        self.enums
+            .iter()
+            .find(|enum_data| enum_data.full_name == full_name)
+            .map(|enum_data| enum_data.underlying_type.as_str())
+    }
+
+    pub fn get_struct_data(&self, full_name: &str) -> Option<&StructData> {
+        self.structs
+            .iter()
+            .find(|struct_data| struct_data.full_name == full_name)
+    }
+
     pub fn find_scene_network_identity_all(&self) -> VecDeque<NetworkIdentity> {
         let mut network_identities = VecDeque::new();
         for scene_ids in self.scene_ids.iter() {
@@ -559,6 +868,308 @@ impl BackendData {
         }
         network_identities
     }
+
+    // 比较新旧 BackendData，返回合并后的数据（只包含可以在线应用的修改）和修改报告
+    pub fn merge_reload(&self, new: &BackendData) -> (BackendData, BackendDataReloadReport) {
+        let mut merged = self.clone();
+        let mut report = BackendDataReloadReport::default();
+
+        if !Self::same(&self.kcp2k_config, &new.kcp2k_config) {
+            report
+                .restart_required
+                .push("kcp2k_config changed".to_string());
+        }
+        if !Self::same(&self.network_manager_settings, &new.network_manager_settings) {
+            report
+                .restart_required
+                .push("networkManagerSettings changed".to_string());
+        }
+        if !Self::same(
+            &self.network_room_manager_settings,
+            &new.network_room_manager_settings,
+        ) {
+            report
+                .restart_required
+                .push("networkRoomManagerSettings changed".to_string());
+        }
+
+        // methods
+        for new_method in new.methods.iter() {
+            match self.get_method_data_by_hash_code(new_method.hash_code) {
+                None => {
+                    merged.methods.push(new_method.clone());
+                    report
+                        .applied
+                        .push(format!("method added: {}", new_method.name));
+                }
+                Some(old_method) => {
+                    // 限流设置可以在线修改
+                    let mut rate_limited_method = old_method.clone();
+                    rate_limited_method.rate_limit = new_method.rate_limit;
+                    rate_limited_method.rate_limit_burst = new_method.rate_limit_burst;
+                    if !Self::same(&rate_limited_method, new_method) {
+                        report
+                            .restart_required
+                            .push(format!("method changed: {}", new_method.name));
+                    } else if !Self::same(old_method, new_method) {
+                        if let Some(merged_method) = merged
+                            .methods
+                            .iter_mut()
+                            .find(|v| v.hash_code == new_method.hash_code)
+                        {
+                            merged_method.rate_limit = new_method.rate_limit;
+                            merged_method.rate_limit_burst = new_method.rate_limit_burst;
+                        }
+                        report
+                            .applied
+                            .push(format!("method rate limit changed: {}", new_method.name));
+                    }
+                }
+            }
+        }
+        for old_method in self.methods.iter() {
+            if new.get_method_data_by_hash_code(old_method.hash_code).is_none() {
+                report
+                    .restart_required
+                    .push(format!("method removed: {}", old_method.name));
+            }
+        }
+
+        // sync_vars
+        for new_sync_var in new.sync_vars.iter() {
+            match self
+                .sync_vars
+                .iter()
+                .find(|v| v.full_name == new_sync_var.full_name)
+            {
+                None => {
+                    // 新类的 SyncVar 可以在线添加，已有类添加 SyncVar 会改变序列化布局
+                    if self
+                        .sync_vars
+                        .iter()
+                        .any(|v| v.sub_class == new_sync_var.sub_class)
+                    {
+                        report.restart_required.push(format!(
+                            "sync var added to existing class: {}",
+                            new_sync_var.full_name
+                        ));
+                    } else {
+                        merged.sync_vars.push(new_sync_var.clone());
+                        report
+                            .applied
+                            .push(format!("sync var added: {}", new_sync_var.full_name));
+                    }
+                }
+                Some(old_sync_var) => {
+                    if !Self::same(old_sync_var, new_sync_var) {
+                        report
+                            .restart_required
+                            .push(format!("sync var changed: {}", new_sync_var.full_name));
+                    }
+                }
+            }
+        }
+        for old_sync_var in self.sync_vars.iter() {
+            if !new
+                .sync_vars
+                .iter()
+                .any(|v| v.full_name == old_sync_var.full_name)
+            {
+                report
+                    .restart_required
+                    .push(format!("sync var removed: {}", old_sync_var.full_name));
+            }
+        }
+
+        // sync_objects 决定组件的序列化布局，修改后需要重启
+        if !Self::same(&self.sync_objects, &new.sync_objects) {
+            report
+                .restart_required
+                .push("syncObjects changed".to_string());
+        }
+
+        // enums 只影响参数的读取，可以在线应用
+        if !Self::same(&self.enums, &new.enums) {
+            merged.enums = new.enums.clone();
+            report.applied.push("enums changed".to_string());
+        }
+
+        // structs 同样只影响参数的读取
+        if !Self::same(&self.structs, &new.structs) {
+            merged.structs = new.structs.clone();
+            report.applied.push("structs changed".to_string());
+        }
+
+        // assets
+        for new_asset in new.assets.iter() {
+            match self.assets.iter().find(|v| v.key == new_asset.key) {
+                None => {
+                    merged.assets.push(new_asset.clone());
+                    report
+                        .applied
+                        .push(format!("asset added: {}", new_asset.value));
+                }
+                Some(old_asset) => {
+                    if old_asset.value != new_asset.value {
+                        report
+                            .restart_required
+                            .push(format!("asset changed: {}", new_asset.key));
+                    }
+                }
+            }
+        }
+        for old_asset in self.assets.iter() {
+            if !new.assets.iter().any(|v| v.key == old_asset.key) {
+                report
+                    .restart_required
+                    .push(format!("asset removed: {}", old_asset.value));
+            }
+        }
+
+        // scene_ids
+        for new_scene_id in new.scene_ids.iter() {
+            match self.scene_ids.iter().find(|v| v.key == new_scene_id.key) {
+                None => {
+                    merged.scene_ids.push(new_scene_id.clone());
+                    report
+                        .applied
+                        .push(format!("scene object added: {}", new_scene_id.key));
+                }
+                Some(old_scene_id) => {
+                    if old_scene_id.value != new_scene_id.value {
+                        report
+                            .restart_required
+                            .push(format!("scene object changed: {}", new_scene_id.key));
+                    }
+                }
+            }
+        }
+        for old_scene_id in self.scene_ids.iter() {
+            if !new.scene_ids.iter().any(|v| v.key == old_scene_id.key) {
+                report
+                    .restart_required
+                    .push(format!("scene object removed: {}", old_scene_id.key));
+            }
+        }
+
+        // network_identities
+        for new_identity in new.network_identities.iter() {
+            let name = format!(
+                "assetId={} sceneId={}",
+                new_identity.asset_id, new_identity.scene_id
+            );
+            let old_index = self.network_identities.iter().position(|v| {
+                v.asset_id == new_identity.asset_id && v.scene_id == new_identity.scene_id
+            });
+            let old_index = match old_index {
+                None => {
+                    merged.network_identities.push(new_identity.clone());
+                    report.applied.push(format!("prefab added: {}", name));
+                    continue;
+                }
+                Some(old_index) => old_index,
+            };
+            let old_identity = &self.network_identities[old_index];
+            if Self::same(old_identity, new_identity) {
+                continue;
+            }
+            // 命中盒只在服务器使用，可以在线修改
+            if !Self::same(&old_identity.hitboxes, &new_identity.hitboxes) {
+                merged.network_identities[old_index].hitboxes = new_identity.hitboxes.clone();
+                report.applied.push(format!("prefab hitboxes changed: {}", name));
+            }
+            // 碰撞体只在创建刚体时读取
+            if !Self::same(&old_identity.colliders, &new_identity.colliders) {
+                report
+                    .restart_required
+                    .push(format!("prefab colliders changed: {}", name));
+            }
+            // 组件布局改变
+            if old_identity.network_behaviour_components.len()
+                != new_identity.network_behaviour_components.len()
+                || old_identity
+                .network_behaviour_components
+                .iter()
+                .zip(new_identity.network_behaviour_components.iter())
+                .any(|(o, n)| o.value.index != n.value.index || o.value.sub_class != n.value.sub_class)
+            {
+                report
+                    .restart_required
+                    .push(format!("prefab components changed: {}", name));
+                continue;
+            }
+            for (i, (old_component, new_component)) in old_identity
+                .network_behaviour_components
+                .iter()
+                .zip(new_identity.network_behaviour_components.iter())
+                .enumerate()
+            {
+                let (o, n) = (&old_component.value, &new_component.value);
+                if Self::same(o, n) {
+                    continue;
+                }
+                let component_name = format!("{} component {} ({})", name, n.index, n.sub_class);
+                // 影响传输格式的设置需要重启
+                let (ob, nb) = (
+                    &o.network_transform_base_setting,
+                    &n.network_transform_base_setting,
+                );
+                // syncInterval 可以在线修改，syncMode 改变后观察者缺少组件的初始状态
+                let (os, ns) = (&o.network_behaviour_setting, &n.network_behaviour_setting);
+                if os.sync_direction != ns.sync_direction
+                    || os.sync_mode != ns.sync_mode
+                    || !Self::same(&o.network_animator_setting, &n.network_animator_setting)
+                    || ob.sync_position != nb.sync_position
+                    || ob.sync_rotation != nb.sync_rotation
+                    || ob.sync_scale != nb.sync_scale
+                    || ob.compress_rotation != nb.compress_rotation
+                    || ob.coordinate_space != nb.coordinate_space
+                    || o.network_transform_reliable_setting.position_precision
+                    != n.network_transform_reliable_setting.position_precision
+                    || o.network_transform_reliable_setting.scale_precision
+                    != n.network_transform_reliable_setting.scale_precision
+                    || o.network_rigidbody_setting.sync_velocity
+                    != n.network_rigidbody_setting.sync_velocity
+                {
+                    report
+                        .restart_required
+                        .push(format!("{} settings changed", component_name));
+                    continue;
+                }
+                let merged_component =
+                    &mut merged.network_identities[old_index].network_behaviour_components[i].value;
+                merged_component.network_behaviour_setting.sync_interval = ns.sync_interval;
+                merged_component.network_transform_base_setting = *nb;
+                merged_component.network_transform_reliable_setting =
+                    n.network_transform_reliable_setting;
+                merged_component.network_transform_unreliable_setting =
+                    n.network_transform_unreliable_setting;
+                merged_component.network_rigidbody_setting = n.network_rigidbody_setting;
+                report
+                    .applied
+                    .push(format!("{} settings changed", component_name));
+            }
+        }
+        for old_identity in self.network_identities.iter() {
+            if !new.network_identities.iter().any(|v| {
+                v.asset_id == old_identity.asset_id && v.scene_id == old_identity.scene_id
+            }) {
+                report.restart_required.push(format!(
+                    "prefab removed: assetId={} sceneId={}",
+                    old_identity.asset_id, old_identity.scene_id
+                ));
+            }
+        }
+
+        (merged, report)
+    }
+
+    fn same<T: Serialize>(a: &T, b: &T) -> bool {
+        match (serde_json::to_value(a), serde_json::to_value(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -588,4 +1199,152 @@ mod tests {
         );
         println!("{:?}", method_data);
     }
+
+    fn method(hash_code: u16, name: &str, rate_limit: Option<f32>) -> MethodData {
+        MethodData {
+            hash_code,
+            sub_class: "QuickStart.PlayerScript".to_string(),
+            name: name.to_string(),
+            requires_authority: true,
+            r#type: MethodType::Command,
+            parameters: Vec::new(),
+            rpc_list: Vec::new(),
+            var_list: Vec::new(),
+            rate_limit,
+            rate_limit_burst: None,
+        }
+    }
+
+    fn sync_var(sub_class: &str, name: &str) -> SyncVarData {
+        SyncVarData {
+            full_name: format!("{}.{}", sub_class, name),
+            sub_class: sub_class.to_string(),
+            name: name.to_string(),
+            r#type: "System.Int32".to_string(),
+            value: vec![0],
+            dirty_bit: 1,
+            owner_only: false,
+        }
+    }
+
+    #[test]
+    fn test_merge_reload_report() {
+        let old = BackendData {
+            methods: vec![
+                method(1, "CmdMove", None),
+                method(2, "CmdFire", None),
+                method(3, "CmdJump", None),
+            ],
+            sync_vars: vec![sync_var("QuickStart.PlayerScript", "health")],
+            assets: vec![KeyValue {
+                key: 100,
+                value: "Player".to_string(),
+            }],
+            ..Default::default()
+        };
+        let mut changed_method = method(2, "CmdFire", None);
+        changed_method.requires_authority = false;
+        let new = BackendData {
+            methods: vec![
+                method(1, "CmdMove", Some(10.0)),
+                changed_method,
+                method(4, "CmdChat", None),
+            ],
+            sync_vars: vec![
+                sync_var("QuickStart.PlayerScript", "health"),
+                sync_var("QuickStart.PlayerScript", "mana"),
+                sync_var("QuickStart.DoorScript", "open"),
+            ],
+            assets: vec![
+                KeyValue {
+                    key: 100,
+                    value: "Player".to_string(),
+                },
+                KeyValue {
+                    key: 200,
+                    value: "Door".to_string(),
+                },
+            ],
+            enums: vec![EnumData {
+                full_name: "QuickStart.Team".to_string(),
+                underlying_type: "System.Byte".to_string(),
+            }],
+            kcp2k_config: Kcp2kTransportConfig {
+                port: 7778,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (merged, report) = old.merge_reload(&new);
+        assert_eq!(
+            report.applied,
+            vec![
+                "method rate limit changed: CmdMove",
+                "method added: CmdChat",
+                "sync var added: QuickStart.DoorScript.open",
+                "enums changed",
+                "asset added: Door",
+            ]
+        );
+        assert_eq!(
+            report.restart_required,
+            vec![
+                "kcp2k_config changed",
+                "method changed: CmdFire",
+                "method removed: CmdJump",
+                "sync var added to existing class: QuickStart.PlayerScript.mana",
+            ]
+        );
+
+        // 只合并可以在线应用的修改
+        assert_eq!(
+            merged.get_method_data_by_hash_code(1).unwrap().rate_limit,
+            Some(10.0)
+        );
+        assert!(
+            merged
+                .get_method_data_by_hash_code(2)
+                .unwrap()
+                .requires_authority
+        );
+        assert!(merged.get_method_data_by_hash_code(3).is_some());
+        assert!(merged.get_method_data_by_hash_code(4).is_some());
+        assert_eq!(merged.sync_vars.len(), 2);
+        assert_eq!(merged.assets.len(), 2);
+        assert_eq!(merged.enums.len(), 1);
+        assert_eq!(merged.kcp2k_config.port, old.kcp2k_config.port);
+
+        // 没有修改时报告为空
+        let (_, report) = merged.merge_reload(&merged);
+        assert!(report.applied.is_empty() && report.restart_required.is_empty());
+    }
+
+    #[test]
+    fn test_scene_hash_by_scene_name() {
+        let scene_hash: u64 = 0x1234_5678;
+        let backend_data = BackendData {
+            scene_ids: vec![
+                KeyValue {
+                    key: "Dungeon".to_string(),
+                    value: (scene_hash << 32).to_string(),
+                },
+                KeyValue {
+                    key: "Door".to_string(),
+                    value: ((scene_hash << 32) | 1).to_string(),
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(
+            backend_data.get_scene_hash_by_scene_name("Dungeon"),
+            Some(0x1234_5678)
+        );
+        assert_eq!(
+            backend_data.get_scene_object_names_by_scene_hash(0x1234_5678),
+            vec!["Dungeon".to_string(), "Door".to_string()]
+        );
+        // 不在 sceneIds 中的场景不能用名称的哈希代替
+        assert_eq!(backend_data.get_scene_hash_by_scene_name("Missing"), None);
+    }
 }
@@ -1,5 +1,7 @@
 use crate::mirror::core::batching::batcher::Batcher;
-use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait};
+use crate::mirror::core::network_reader::{
+    NetworkReader, NetworkReaderTrait, NetworkReaderTryTrait, ReadError,
+};
 use crate::mirror::core::network_writer::NetworkWriter;
 use crate::mirror::core::network_writer_pool::NetworkWriterPool;
 use std::collections::VecDeque;
@@ -10,6 +12,12 @@ pub struct UnBatcher {
     un_batch_timestamp: f64,
 }
 
+impl Default for UnBatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl UnBatcher {
     pub fn new() -> UnBatcher {
         UnBatcher {
@@ -54,15 +62,14 @@ impl UnBatcher {
         true
     }
 
-    pub fn get_next_message(&mut self) -> Option<(&[u8], f64)> {
-        let message: &[u8];
-        let remote_time_stamp: f64;
+    // 批次数据不完整时返回错误，调用方需要 clear
+    pub fn get_next_message(&mut self) -> Result<Option<(&[u8], f64)>, ReadError> {
         if self.un_batches.is_empty() {
-            return None;
+            return Ok(None);
         }
 
         if self.un_batcher.capacity() == 0 {
-            return None;
+            return Ok(None);
         }
 
         if self.un_batcher.remaining() == 0 {
@@ -72,27 +79,22 @@ impl UnBatcher {
 
             if let Some(next) = self.un_batches.front() {
                 self.un_batcher.set_array_segment(next.to_array_segment());
-                self.un_batch_timestamp = self.un_batcher.read_double();
+                self.un_batch_timestamp = self.un_batcher.try_read_double()?;
             } else {
-                return None;
+                return Ok(None);
             }
         }
 
-        remote_time_stamp = self.un_batch_timestamp;
+        let remote_time_stamp = self.un_batch_timestamp;
 
         if self.un_batcher.remaining() == 0 {
-            return None;
-        }
-
-        let size = self.un_batcher.decompress_var_ulong() as usize;
-
-        if self.un_batcher.remaining() < size {
-            return None;
+            return Ok(None);
         }
 
-        message = self.un_batcher.read_array_segment(size);
+        let size = self.un_batcher.try_decompress_var_ulong()? as usize;
+        let message = self.un_batcher.try_read_array_segment(size)?;
 
-        Some((message, remote_time_stamp))
+        Ok(Some((message, remote_time_stamp)))
     }
 
     pub fn clear(&mut self) {
@@ -114,14 +116,14 @@ mod tests {
         batch_writer.write_double(0.1);
         batch_writer.compress_var_ulong(5);
         batch_writer.write_array_segment_all(&[1, 2, 3, 4, 5]);
-        batch.extend_from_slice(&batch_writer.to_array_segment());
+        batch.extend_from_slice(batch_writer.to_array_segment());
 
         assert_eq!(un_batcher.batches_count(), 0);
-        assert_eq!(un_batcher.add_batch_with_array_segment(&batch), true);
-        assert_eq!(un_batcher.add_batch_with_array_segment(&batch), true);
+        assert!(un_batcher.add_batch_with_array_segment(&batch));
+        assert!(un_batcher.add_batch_with_array_segment(&batch));
         assert_eq!(un_batcher.batches_count(), 2);
 
-        while let Some((message, remote_time_stamp)) = un_batcher.get_next_message() {
+        while let Ok(Some((message, remote_time_stamp))) = un_batcher.get_next_message() {
             println!(
                 "Message: {:?}, Remote Time Stamp: {}",
                 message, remote_time_stamp
@@ -129,4 +131,17 @@ mod tests {
         }
         println!("Batches Count: {}", un_batcher.batches_count());
     }
+
+    #[test]
+    fn test_un_batcher_truncated() {
+        let mut un_batcher = UnBatcher::new();
+        let mut batch_writer = NetworkWriter::new();
+
+        batch_writer.write_double(0.1);
+        batch_writer.compress_var_ulong(5);
+        batch_writer.write_array_segment_all(&[1, 2, 3]);
+
+        assert!(un_batcher.add_batch_with_array_segment(batch_writer.to_array_segment()));
+        assert!(un_batcher.get_next_message().is_err());
+    }
 }
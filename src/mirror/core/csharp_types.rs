@@ -0,0 +1,525 @@
+use crate::mirror::core::backend_data::BackendData;
+use crate::mirror::core::network_field::NetworkField;
+use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTryTrait, ReadError};
+use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::fmt;
+
+// 手写的读取函数，读取一个完整的值
+pub type CSharpTypeReader = fn(&mut NetworkReader) -> Result<(), ReadError>;
+
+// 字段名和字段值的原始字节，按结构体描述的顺序排列
+pub type StructFields = Vec<(String, Vec<u8>)>;
+
+lazy_static! {
+    static ref CUSTOM_TYPE_READERS: DashMap<String, CSharpTypeReader> = DashMap::new();
+}
+
+#[derive(Debug)]
+pub enum CSharpTypeError {
+    UnknownType(String),
+    // 结构体名.字段名
+    MissingField(String),
+    // 值的字节与类型不符
+    InvalidValue(String),
+    // 嵌套超过 MAX_DEPTH 层的类型
+    TooDeep(String),
+    // 集合长度超过剩余字节数或 MAX_COLLECTION_LENGTH
+    TooLong { type_name: String, length: usize },
+    Read(ReadError),
+}
+
+impl fmt::Display for CSharpTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CSharpTypeError::UnknownType(type_name) => write!(f, "unknown type {}", type_name),
+            CSharpTypeError::MissingField(field) => write!(f, "missing field {}", field),
+            CSharpTypeError::InvalidValue(type_name) => {
+                write!(f, "invalid value of type {}", type_name)
+            }
+            CSharpTypeError::TooDeep(type_name) => {
+                write!(f, "type {} is nested too deeply", type_name)
+            }
+            CSharpTypeError::TooLong { type_name, length } => {
+                write!(f, "collection of {} is too long: {}", type_name, length)
+            }
+            CSharpTypeError::Read(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for CSharpTypeError {}
+
+impl From<ReadError> for CSharpTypeError {
+    fn from(err: ReadError) -> Self {
+        CSharpTypeError::Read(err)
+    }
+}
+
+// 按 SyncVarData.type / MethodData.parameters 中的 C# 类型名读取值
+// 格式与 Mirror weaver 使用的 NetworkReaderExtensions 一致
+pub struct CSharpTypes;
+
+impl CSharpTypes {
+    // 结构体、集合和 Nullable 的最大嵌套层数，防止递归的结构体描述耗尽栈
+    pub const MAX_DEPTH: usize = 32;
+    // 集合的最大元素数量
+    pub const MAX_COLLECTION_LENGTH: usize = 1024 * 64;
+
+    // 为 C# 类型名注册手写的读写，优先于 BackendData 中的结构体描述
+    pub fn register_type<T: NetworkField>(type_name: &str) {
+        CUSTOM_TYPE_READERS.insert(type_name.to_string(), |reader| {
+            T::read_field(reader).map(|_| ())
+        });
+    }
+
+    pub fn unregister_type(type_name: &str) {
+        CUSTOM_TYPE_READERS.remove(type_name);
+    }
+
+    // 注册类型的值与原始字节互转
+    pub fn to_value_bytes<T: NetworkField>(value: &T) -> Vec<u8> {
+        let mut writer = NetworkWriter::new();
+        value.write_field(&mut writer);
+        writer.to_bytes()
+    }
+
+    pub fn from_value_bytes<T: NetworkField>(value: &[u8]) -> Result<T, ReadError> {
+        T::read_field(&mut NetworkReader::new_with_array_segment(value))
+    }
+
+    // 读取一个值，返回它的原始字节，用于原样保存和转发
+    pub fn read_value_bytes(
+        backend_data: &BackendData,
+        type_name: &str,
+        reader: &mut NetworkReader,
+    ) -> Result<Vec<u8>, CSharpTypeError> {
+        let start = reader.get_position();
+        Self::skip_value(backend_data, type_name, reader)?;
+        Ok(reader.to_array_segment()[start..].to_vec())
+    }
+
+    // 检查原始字节正好是一个该类型的值后写入
+    pub fn write_value_bytes(
+        backend_data: &BackendData,
+        type_name: &str,
+        value: &[u8],
+        writer: &mut NetworkWriter,
+    ) -> Result<(), CSharpTypeError> {
+        let mut reader = NetworkReader::new_with_array_segment(value);
+        match Self::skip_value(backend_data, type_name, &mut reader) {
+            Ok(()) if reader.get_position() == value.len() => {}
+            Ok(()) | Err(CSharpTypeError::Read(_)) => {
+                return Err(CSharpTypeError::InvalidValue(type_name.to_string()));
+            }
+            Err(err) => return Err(err),
+        }
+        writer.write_array_segment_all(value);
+        Ok(())
+    }
+
+    // 按 BackendData 中的结构体描述读取，返回各字段的原始字节，null 的 class 返回 None
+    pub fn read_struct(
+        backend_data: &BackendData,
+        type_name: &str,
+        reader: &mut NetworkReader,
+    ) -> Result<Option<StructFields>, CSharpTypeError> {
+        let Some(struct_data) = backend_data.get_struct_data(type_name) else {
+            return Err(CSharpTypeError::UnknownType(type_name.to_string()));
+        };
+        if struct_data.is_class && !reader.try_read_bool()? {
+            return Ok(None);
+        }
+        let mut fields = Vec::with_capacity(struct_data.fields.len());
+        for field in struct_data.fields.iter() {
+            let value = Self::read_value_bytes(backend_data, &field.r#type, reader)?;
+            fields.push((field.name.clone(), value));
+        }
+        Ok(Some(fields))
+    }
+
+    // 按结构体描述的字段顺序写入，None 写入 null 的 class
+    pub fn write_struct(
+        backend_data: &BackendData,
+        type_name: &str,
+        fields: Option<&HashMap<String, Vec<u8>>>,
+        writer: &mut NetworkWriter,
+    ) -> Result<(), CSharpTypeError> {
+        let Some(struct_data) = backend_data.get_struct_data(type_name) else {
+            return Err(CSharpTypeError::UnknownType(type_name.to_string()));
+        };
+        let fields = match fields {
+            Some(fields) => fields,
+            None if struct_data.is_class => {
+                writer.write_bool(false);
+                return Ok(());
+            }
+            None => return Err(CSharpTypeError::InvalidValue(type_name.to_string())),
+        };
+        if struct_data.is_class {
+            writer.write_bool(true);
+        }
+        for field in struct_data.fields.iter() {
+            let Some(value) = fields.get(&field.name) else {
+                return Err(CSharpTypeError::MissingField(format!(
+                    "{}.{}",
+                    type_name, field.name
+                )));
+            };
+            Self::write_value_bytes(backend_data, &field.r#type, value, writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn skip_value(
+        backend_data: &BackendData,
+        type_name: &str,
+        reader: &mut NetworkReader,
+    ) -> Result<(), CSharpTypeError> {
+        Self::skip_value_with_depth(backend_data, type_name, reader, 0)
+    }
+
+    fn skip_value_with_depth(
+        backend_data: &BackendData,
+        type_name: &str,
+        reader: &mut NetworkReader,
+        depth: usize,
+    ) -> Result<(), CSharpTypeError> {
+        if depth > Self::MAX_DEPTH {
+            return Err(CSharpTypeError::TooDeep(type_name.to_string()));
+        }
+        if let Some(size) = Self::fixed_size(type_name) {
+            reader.try_read_array_segment(size)?;
+            return Ok(());
+        }
+        match type_name {
+            // 压缩整数，LayerMask 写入的是 int
+            "System.Int32"
+            | "System.UInt32"
+            | "System.Int64"
+            | "System.UInt64"
+            | "System.Long"
+            | "System.ULong"
+            | "UnityEngine.LayerMask" => {
+                reader.try_decompress_var_ulong()?;
+            }
+            "UnityEngine.Vector2Int" => {
+                for _ in 0..2 {
+                    reader.try_decompress_var_ulong()?;
+                }
+            }
+            "UnityEngine.Vector3Int" => {
+                for _ in 0..3 {
+                    reader.try_decompress_var_ulong()?;
+                }
+            }
+            "System.String" | "System.Uri" => {
+                reader.try_read_string()?;
+            }
+            "System.Byte[]" => {
+                reader.try_read_bytes_and_size()?;
+            }
+            // netId
+            "Mirror.NetworkIdentity" | "UnityEngine.GameObject" | "UnityEngine.Transform" => {
+                reader.try_decompress_var_uint()?;
+            }
+            // netId，不为 0 时后面是组件下标
+            "Mirror.NetworkBehaviour" => {
+                if reader.try_decompress_var_uint()? != 0 {
+                    reader.try_read_byte()?;
+                }
+            }
+            _ => {
+                let custom_reader = CUSTOM_TYPE_READERS.get(type_name).map(|entry| *entry);
+                if let Some(custom_reader) = custom_reader {
+                    custom_reader(reader)?;
+                    return Ok(());
+                }
+                if let Some(element_type) = type_name.strip_suffix("[]") {
+                    return Self::skip_collection(backend_data, element_type, reader, depth + 1);
+                }
+                if let Some(element_type) =
+                    Self::generic_argument(type_name, "System.Collections.Generic.List`1")
+                {
+                    return Self::skip_collection(backend_data, element_type, reader, depth + 1);
+                }
+                if let Some(value_type) = Self::generic_argument(type_name, "System.Nullable`1") {
+                    if reader.try_read_bool()? {
+                        Self::skip_value_with_depth(backend_data, value_type, reader, depth + 1)?;
+                    }
+                    return Ok(());
+                }
+                if let Some(struct_data) = backend_data.get_struct_data(type_name) {
+                    if struct_data.is_class && !reader.try_read_bool()? {
+                        return Ok(());
+                    }
+                    for field in struct_data.fields.iter() {
+                        Self::skip_value_with_depth(
+                            backend_data,
+                            &field.r#type,
+                            reader,
+                            depth + 1,
+                        )?;
+                    }
+                    return Ok(());
+                }
+                // 枚举按底层类型读取
+                if let Some(underlying_type) = backend_data.get_enum_underlying_type(type_name) {
+                    return Self::skip_value_with_depth(
+                        backend_data,
+                        underlying_type,
+                        reader,
+                        depth + 1,
+                    );
+                }
+                return Err(CSharpTypeError::UnknownType(type_name.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    fn fixed_size(type_name: &str) -> Option<usize> {
+        match type_name {
+            "System.Boolean" | "System.Byte" | "System.SByte" => Some(1),
+            "System.Char" | "System.Int16" | "System.UInt16" | "System.Half" => Some(2),
+            "System.Single" | "System.Float" | "UnityEngine.Color32" => Some(4),
+            // DateTime 写入的是 ToOADate() 的 double
+            "System.Double" | "System.DateTime" | "UnityEngine.Vector2" => Some(8),
+            "UnityEngine.Vector3" => Some(12),
+            "System.Decimal"
+            | "System.Guid"
+            | "UnityEngine.Vector4"
+            | "UnityEngine.Quaternion"
+            | "UnityEngine.Color"
+            | "UnityEngine.Rect"
+            | "UnityEngine.Plane" => Some(16),
+            "UnityEngine.Ray" => Some(24),
+            "UnityEngine.Matrix4x4" => Some(64),
+            _ => None,
+        }
+    }
+
+    // T[] 和 List<T>：压缩的 int 长度，null 为 -1
+    fn skip_collection(
+        backend_data: &BackendData,
+        element_type: &str,
+        reader: &mut NetworkReader,
+        depth: usize,
+    ) -> Result<(), CSharpTypeError> {
+        let length = reader.try_decompress_var_int()?.max(0) as usize;
+        // 长度不能超过剩余字节数，防止伪造的长度导致长时间循环
+        if length > reader.remaining() || length > Self::MAX_COLLECTION_LENGTH {
+            return Err(CSharpTypeError::TooLong {
+                type_name: element_type.to_string(),
+                length,
+            });
+        }
+        for _ in 0..length {
+            Self::skip_value_with_depth(backend_data, element_type, reader, depth)?;
+        }
+        Ok(())
+    }
+
+    // 泛型参数，支持 List`1[System.Int32] 和 List`1[[System.Int32, mscorlib, ...]]
+    fn generic_argument<'a>(type_name: &'a str, generic: &str) -> Option<&'a str> {
+        let arguments = type_name
+            .strip_prefix(generic)?
+            .strip_prefix('[')?
+            .strip_suffix(']')?;
+        let qualified = match arguments.strip_prefix('[') {
+            None => return Some(arguments),
+            Some(qualified) => qualified.strip_suffix(']')?,
+        };
+        // 去掉程序集名
+        let mut depth = 0;
+        for (i, c) in qualified.char_indices() {
+            match c {
+                '[' => depth += 1,
+                ']' => depth -= 1,
+                ',' if depth == 0 => return Some(qualified[..i].trim()),
+                _ => {}
+            }
+        }
+        Some(qualified.trim())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirror::core::backend_data::{EnumData, StructData, StructFieldData};
+
+    #[test]
+    fn test_read_value_bytes() {
+        let backend_data = BackendData {
+            enums: vec![EnumData {
+                full_name: "QuickStart.Team".to_string(),
+                underlying_type: "System.Int32".to_string(),
+            }],
+            ..Default::default()
+        };
+        let list_type = "System.Collections.Generic.List`1[[System.Nullable`1[[UnityEngine.Vector3, UnityEngine.CoreModule]], mscorlib]]";
+        let mut writer = NetworkWriter::new();
+        // List<Vector3?> { null, Vector3 }
+        writer.compress_var_int(2);
+        writer.write_bool(false);
+        writer.write_bool(true);
+        writer.write_vector3(nalgebra::Vector3::new(1.0, 2.0, 3.0));
+        let list_size = writer.get_position();
+        // Team
+        writer.compress_var_int(300);
+        writer.write_str("end");
+
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        let value = CSharpTypes::read_value_bytes(&backend_data, list_type, &mut reader).unwrap();
+        assert_eq!(value.as_slice(), &writer.to_array_segment()[..list_size]);
+        CSharpTypes::skip_value(&backend_data, "QuickStart.Team", &mut reader).unwrap();
+        assert_eq!(reader.try_read_string().unwrap(), "end");
+        assert!(matches!(
+            CSharpTypes::skip_value(&backend_data, "QuickStart.Unknown", &mut reader),
+            Err(CSharpTypeError::UnknownType(_))
+        ));
+    }
+
+    fn field(name: &str, r#type: &str) -> StructFieldData {
+        StructFieldData {
+            name: name.to_string(),
+            r#type: r#type.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_struct_schema() {
+        let backend_data = BackendData {
+            structs: vec![
+                StructData {
+                    full_name: "QuickStart.Item".to_string(),
+                    is_class: false,
+                    fields: vec![field("id", "System.Int32"), field("tag", "QuickStart.Tag")],
+                },
+                StructData {
+                    full_name: "QuickStart.Loot".to_string(),
+                    is_class: true,
+                    fields: vec![field("items", "QuickStart.Item[]")],
+                },
+            ],
+            ..Default::default()
+        };
+        // 结构体描述中引用了未注册的类型
+        let mut reader = NetworkReader::new_with_array_segment(&[1, 7, 0, 0]);
+        assert!(matches!(
+            CSharpTypes::skip_value(&backend_data, "QuickStart.Item", &mut reader),
+            Err(CSharpTypeError::UnknownType(_))
+        ));
+
+        // QuickStart.Tag 手写为 ushort
+        CSharpTypes::register_type::<u16>("QuickStart.Tag");
+        let mut item = HashMap::new();
+        item.insert("id".to_string(), CSharpTypes::to_value_bytes(&300i32));
+        item.insert("tag".to_string(), CSharpTypes::to_value_bytes(&7u16));
+        let mut writer = NetworkWriter::new();
+        CSharpTypes::write_struct(&backend_data, "QuickStart.Item", Some(&item), &mut writer)
+            .unwrap();
+        let item_bytes = writer.to_bytes();
+
+        let mut loot = HashMap::new();
+        let mut items = NetworkWriter::new();
+        items.compress_var_int(1);
+        items.write_array_segment_all(&item_bytes);
+        loot.insert("items".to_string(), items.to_bytes());
+        let mut writer = NetworkWriter::new();
+        CSharpTypes::write_struct(&backend_data, "QuickStart.Loot", Some(&loot), &mut writer)
+            .unwrap();
+        CSharpTypes::write_struct(&backend_data, "QuickStart.Loot", None, &mut writer).unwrap();
+
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        let fields = CSharpTypes::read_struct(&backend_data, "QuickStart.Loot", &mut reader)
+            .unwrap()
+            .unwrap();
+        assert_eq!(fields, vec![("items".to_string(), items.to_bytes())]);
+        assert_eq!(
+            CSharpTypes::read_struct(&backend_data, "QuickStart.Loot", &mut reader).unwrap(),
+            None
+        );
+
+        let mut reader = NetworkReader::new_with_array_segment(&item_bytes);
+        let fields = CSharpTypes::read_struct(&backend_data, "QuickStart.Item", &mut reader)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            CSharpTypes::from_value_bytes::<u16>(&fields[1].1).unwrap(),
+            7
+        );
+
+        // 缺少字段
+        item.remove("tag");
+        assert!(matches!(
+            CSharpTypes::write_struct(&backend_data, "QuickStart.Item", Some(&item), &mut writer),
+            Err(CSharpTypeError::MissingField(_))
+        ));
+        CSharpTypes::unregister_type("QuickStart.Tag");
+    }
+
+    #[test]
+    fn test_skip_value_limits() {
+        let backend_data = BackendData {
+            structs: vec![StructData {
+                full_name: "QuickStart.Node".to_string(),
+                is_class: true,
+                fields: vec![field("next", "QuickStart.Node")],
+            }],
+            ..Default::default()
+        };
+        // 链表的深度超过 MAX_DEPTH
+        let mut writer = NetworkWriter::new();
+        for _ in 0..CSharpTypes::MAX_DEPTH + 2 {
+            writer.write_bool(true);
+        }
+        writer.write_bool(false);
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        assert!(matches!(
+            CSharpTypes::skip_value(&backend_data, "QuickStart.Node", &mut reader),
+            Err(CSharpTypeError::TooDeep(_))
+        ));
+
+        // 深度以内正常读取
+        let mut writer = NetworkWriter::new();
+        writer.write_bool(true);
+        writer.write_bool(false);
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        CSharpTypes::skip_value(&backend_data, "QuickStart.Node", &mut reader).unwrap();
+        assert_eq!(reader.remaining(), 0);
+
+        // 长度超过剩余字节数
+        let mut writer = NetworkWriter::new();
+        writer.compress_var_int(i32::MAX);
+        writer.write_bool(false);
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        assert!(matches!(
+            CSharpTypes::skip_value(&backend_data, "QuickStart.Node[]", &mut reader),
+            Err(CSharpTypeError::TooLong { length, .. }) if length == i32::MAX as usize
+        ));
+
+        // 长度超过 MAX_COLLECTION_LENGTH
+        let mut writer = NetworkWriter::new();
+        let length = CSharpTypes::MAX_COLLECTION_LENGTH + 1;
+        writer.compress_var_int(length as i32);
+        for _ in 0..length {
+            writer.write_bool(false);
+        }
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        assert!(matches!(
+            CSharpTypes::skip_value(&backend_data, "System.Boolean[]", &mut reader),
+            Err(CSharpTypeError::TooLong { .. })
+        ));
+
+        // null 数组
+        let mut writer = NetworkWriter::new();
+        writer.compress_var_int(-1);
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        CSharpTypes::skip_value(&backend_data, "QuickStart.Node[]", &mut reader).unwrap();
+        assert_eq!(reader.remaining(), 0);
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+// 每个连接每个组件的客户端输入缓冲
+#[derive(Debug)]
+pub struct InputBuffer {
+    // 还未处理的输入 sequence -> payload
+    pending: BTreeMap<u32, Vec<u8>>,
+    // 最多缓冲的输入数量，超过时丢弃最旧的，避免延迟越积越大
+    capacity: usize,
+    // 最后处理的输入 sequence，0 表示还没有处理过
+    pub last_processed: u32,
+    // last_processed 改变后还没有发送确认
+    pub ack_dirty: bool,
+}
+
+impl InputBuffer {
+    pub const DEFAULT_CAPACITY: usize = 32;
+
+    #[allow(dead_code)]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            pending: BTreeMap::new(),
+            capacity,
+            last_processed: 0,
+            ack_dirty: false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    // 冗余发送的输入可能重复到达，已处理或已缓冲的直接忽略
+    pub fn insert(&mut self, sequence: u32, payload: Vec<u8>) -> bool {
+        if sequence <= self.last_processed || self.pending.contains_key(&sequence) {
+            return false;
+        }
+        self.pending.insert(sequence, payload);
+        while self.pending.len() > self.capacity {
+            self.pending.pop_first();
+        }
+        true
+    }
+
+    // 取出下一条输入，缺失的 sequence 直接跳过
+    pub fn consume(&mut self) -> Option<(u32, Vec<u8>)> {
+        let (sequence, payload) = self.pending.pop_first()?;
+        self.last_processed = sequence;
+        self.ack_dirty = true;
+        Some((sequence, payload))
+    }
+}
+
+impl Default for InputBuffer {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_buffer_order_and_duplicates() {
+        let mut buffer = InputBuffer::new(8);
+        assert!(buffer.is_empty());
+        assert!(buffer.consume().is_none());
+        assert!(!buffer.ack_dirty);
+
+        // 乱序到达，按 sequence 顺序取出
+        assert!(buffer.insert(3, vec![3]));
+        assert!(buffer.insert(1, vec![1]));
+        assert!(buffer.insert(2, vec![2]));
+        // 冗余发送的重复输入
+        assert!(!buffer.insert(2, vec![22]));
+        assert_eq!(buffer.len(), 3);
+
+        assert_eq!(buffer.consume(), Some((1, vec![1])));
+        assert_eq!(buffer.consume(), Some((2, vec![2])));
+        // 已经处理过的输入不再缓冲
+        assert!(!buffer.insert(1, vec![1]));
+        assert!(!buffer.insert(2, vec![2]));
+        assert_eq!(buffer.consume(), Some((3, vec![3])));
+        assert!(buffer.consume().is_none());
+        assert_eq!(buffer.last_processed, 3);
+
+        // 缺失的 sequence 直接跳过
+        assert!(buffer.insert(6, vec![6]));
+        assert!(buffer.insert(5, vec![5]));
+        assert_eq!(buffer.consume(), Some((5, vec![5])));
+        assert_eq!(buffer.consume(), Some((6, vec![6])));
+        assert!(!buffer.insert(4, vec![4]));
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_input_buffer_ack() {
+        let mut buffer = InputBuffer::default();
+        assert_eq!(buffer.last_processed, 0);
+
+        buffer.insert(1, vec![1]);
+        buffer.insert(2, vec![2]);
+        // 只插入不会确认
+        assert!(!buffer.ack_dirty);
+
+        buffer.consume();
+        assert!(buffer.ack_dirty);
+        assert_eq!(buffer.last_processed, 1);
+
+        // 发送确认后清除
+        buffer.ack_dirty = false;
+        buffer.insert(3, vec![3]);
+        assert!(!buffer.ack_dirty);
+        buffer.consume();
+        buffer.consume();
+        assert!(buffer.ack_dirty);
+        assert_eq!(buffer.last_processed, 3);
+    }
+
+    #[test]
+    fn test_input_buffer_capacity() {
+        let mut buffer = InputBuffer::new(2);
+        assert!(buffer.insert(1, vec![1]));
+        assert!(buffer.insert(2, vec![2]));
+        assert!(buffer.insert(3, vec![3]));
+        // 超过容量时丢弃最旧的
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.consume(), Some((2, vec![2])));
+        assert_eq!(buffer.consume(), Some((3, vec![3])));
+        // 丢弃的输入之后到达也不会处理
+        assert!(!buffer.insert(1, vec![1]));
+    }
+}
@@ -0,0 +1,874 @@
+use crate::mirror::components::network_rigidbody::network_rigidbody_reliable::NetworkRigidbodyReliable;
+use crate::mirror::components::network_rigidbody::network_rigidbody_unreliable::NetworkRigidbodyUnreliable;
+use crate::mirror::components::network_transform::network_transform_base::Transform;
+use crate::mirror::components::network_transform::network_transform_reliable::NetworkTransformReliable;
+use crate::mirror::components::network_transform::network_transform_unreliable::NetworkTransformUnreliable;
+use crate::mirror::core::backend_data::{
+    BackendData, BackendDataStatic, ColliderShape, HitboxData,
+};
+use crate::mirror::core::network_server::{NetworkServerStatic, NETWORK_BEHAVIOURS};
+use crate::mirror::core::network_time::NetworkTime;
+use crate::{log_error, log_warn};
+use atomic::Atomic;
+use dashmap::try_result::TryResult;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use nalgebra::{Quaternion, UnitQuaternion, Vector3};
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
+
+lazy_static! {
+    static ref LAG_COMPENSATION_ENABLED: Atomic<bool> = Atomic::new(false);
+    // 保存历史的时长（秒）
+    static ref LAG_COMPENSATION_HISTORY_DURATION: Atomic<f64> = Atomic::new(1.0);
+    // 最多回溯的时长（秒），防止客户端用很大的延迟回溯太久
+    static ref LAG_COMPENSATION_MAX_REWIND: Atomic<f64> = Atomic::new(0.5);
+    static ref LAG_COMPENSATION_TRACKS: DashMap<u32, LagCompensationTrack> = DashMap::new();
+}
+
+// 每个 identity 的 NetworkTransform 历史和命中盒
+#[derive(Debug, Clone)]
+pub struct LagCompensationTrack {
+    pub asset_id: u32,
+    pub scene_id: u64,
+    // NetworkTransform 组件在 NETWORK_BEHAVIOURS 中的 key，没有 NetworkTransform 时为 None
+    pub component_key: Option<String>,
+    pub local_space: bool,
+    pub hitboxes: Vec<HitboxData>,
+    pub history: VecDeque<(f64, Transform)>,
+}
+
+// 回溯前的 Transform，用于恢复
+#[derive(Debug, Clone, Default)]
+pub struct LagCompensationRewind {
+    pub time: f64,
+    saved: Vec<(String, Transform)>,
+}
+
+// 世界空间的命中盒
+#[derive(Debug, Clone)]
+pub struct WorldHitbox {
+    pub net_id: u32,
+    pub name: String,
+    pub shape: ColliderShape,
+    pub center: Vector3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    pub half_extents: Vector3<f32>,
+    pub radius: f32,
+    // 胶囊体两端球心到中心的距离
+    pub half_height: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct LagCompensationHit {
+    pub net_id: u32,
+    pub hitbox: String,
+    pub distance: f32,
+}
+
+pub struct LagCompensation;
+
+impl LagCompensation {
+    pub fn enabled() -> bool {
+        LAG_COMPENSATION_ENABLED.load(Ordering::Relaxed)
+    }
+    pub fn set_enabled(value: bool) {
+        LAG_COMPENSATION_ENABLED.store(value, Ordering::Relaxed);
+    }
+    pub fn history_duration() -> f64 {
+        LAG_COMPENSATION_HISTORY_DURATION.load(Ordering::Relaxed)
+    }
+    pub fn set_history_duration(value: f64) {
+        LAG_COMPENSATION_HISTORY_DURATION.store(value, Ordering::Relaxed);
+    }
+    pub fn max_rewind() -> f64 {
+        LAG_COMPENSATION_MAX_REWIND.load(Ordering::Relaxed)
+    }
+    pub fn set_max_rewind(value: f64) {
+        LAG_COMPENSATION_MAX_REWIND.store(value, Ordering::Relaxed);
+    }
+
+    pub fn reset() {
+        LAG_COMPENSATION_TRACKS.clear();
+    }
+
+    // 每个 tick 记录所有 NetworkTransform 的位置
+    pub fn record() {
+        if !Self::enabled() {
+            return;
+        }
+        let now = NetworkTime::local_time();
+        let oldest = now - Self::history_duration();
+
+        // 移除已经销毁的对象
+        LAG_COMPENSATION_TRACKS.retain(|net_id, _| {
+            NetworkServerStatic::spawned_network_identities().contains_key(net_id)
+        });
+
+        let mut backend_data = None;
+        NetworkServerStatic::spawned_network_identities()
+            .iter()
+            .for_each(|identity| {
+                let net_id = identity.net_id();
+                if !LAG_COMPENSATION_TRACKS.contains_key(&net_id) {
+                    let backend_data =
+                        backend_data.get_or_insert_with(BackendDataStatic::get_backend_data);
+                    let track = Self::create_track(
+                        backend_data,
+                        identity.asset_id,
+                        identity.scene_id,
+                        net_id,
+                    );
+                    LAG_COMPENSATION_TRACKS.insert(net_id, track);
+                }
+                match LAG_COMPENSATION_TRACKS.try_get_mut(&net_id) {
+                    TryResult::Present(mut track) => {
+                        let component_key = match &track.component_key {
+                            Some(component_key) => component_key.clone(),
+                            None => return,
+                        };
+                        let transform = match NETWORK_BEHAVIOURS.try_get(&component_key) {
+                            TryResult::Present(network_behaviour) => {
+                                network_behaviour.game_object().transform
+                            }
+                            TryResult::Absent => return,
+                            TryResult::Locked => {
+                                log_warn!(format!(
+                                    "LagCompensation.record: NetworkBehaviour {} is locked",
+                                    component_key
+                                ));
+                                return;
+                            }
+                        };
+                        track.history.push_back((now, transform));
+                        while let Some((time, _)) = track.history.front() {
+                            if *time >= oldest {
+                                break;
+                            }
+                            track.history.pop_front();
+                        }
+                    }
+                    TryResult::Absent => {}
+                    TryResult::Locked => {
+                        log_warn!(format!(
+                            "LagCompensation.record: track {} is locked",
+                            net_id
+                        ));
+                    }
+                }
+            });
+    }
+
+    fn create_track(
+        backend_data: &BackendData,
+        asset_id: u32,
+        scene_id: u64,
+        net_id: u32,
+    ) -> LagCompensationTrack {
+        let components = match asset_id {
+            0 => backend_data
+                .get_network_identity_data_network_behaviour_components_by_scene_id(scene_id),
+            asset_id => backend_data
+                .get_network_identity_data_network_behaviour_components_by_asset_id(asset_id),
+        };
+        let network_transform = components.iter().find(|component| {
+            component.sub_class == NetworkTransformReliable::COMPONENT_TAG
+                || component.sub_class == NetworkTransformUnreliable::COMPONENT_TAG
+                || component.sub_class == NetworkRigidbodyReliable::COMPONENT_TAG
+                || component.sub_class == NetworkRigidbodyUnreliable::COMPONENT_TAG
+        });
+        LagCompensationTrack {
+            asset_id,
+            scene_id,
+            component_key: network_transform
+                .map(|component| format!("{}_{}", net_id, component.index)),
+            local_space: network_transform
+                .map(|component| component.network_transform_base_setting.coordinate_space == 0)
+                .unwrap_or(false),
+            hitboxes: backend_data.get_hitboxes_by_asset_id_or_scene_id(asset_id, scene_id),
+            history: VecDeque::new(),
+        }
+    }
+
+    // 热重载后更新命中盒
+    pub fn on_backend_data_reload() {
+        let backend_data = BackendDataStatic::get_backend_data();
+        LAG_COMPENSATION_TRACKS.iter_mut().for_each(|mut track| {
+            track.hitboxes =
+                backend_data.get_hitboxes_by_asset_id_or_scene_id(track.asset_id, track.scene_id);
+        });
+    }
+
+    // 连接看到的服务器时间：rtt/2 之前发出，再经过客户端插值缓冲的延迟
+    // 服务器对这个连接的插值延迟（remote_timeline 落后最新快照的时间）与客户端的缓冲时间一致
+    pub fn view_time(conn_id: u64) -> Option<f64> {
+        match NetworkServerStatic::network_connections().try_get(&conn_id) {
+            TryResult::Present(conn) => {
+                let interpolation_delay = match conn.snapshots.last_key_value() {
+                    Some((remote_time, _)) => (remote_time.0 - conn.remote_timeline).max(0.0),
+                    None => conn.buffer_time,
+                };
+                let now = NetworkTime::local_time();
+                let view_time = now - conn._rtt.value / 2.0 - interpolation_delay;
+                Some(view_time.max(now - Self::max_rewind()))
+            }
+            TryResult::Absent => {
+                log_error!(format!(
+                    "LagCompensation.view_time: connection {} not found",
+                    conn_id
+                ));
+                None
+            }
+            TryResult::Locked => {
+                log_error!(format!(
+                    "LagCompensation.view_time: connection {} is locked",
+                    conn_id
+                ));
+                None
+            }
+        }
+    }
+
+    // 在历史中插值得到 time 时的 Transform
+    pub fn sample(net_id: u32, time: f64) -> Option<Transform> {
+        match LAG_COMPENSATION_TRACKS.try_get(&net_id) {
+            TryResult::Present(track) => Self::sample_history(&track.history, time),
+            _ => None,
+        }
+    }
+
+    fn sample_history(history: &VecDeque<(f64, Transform)>, time: f64) -> Option<Transform> {
+        let (first_time, first) = history.front()?;
+        if time <= *first_time {
+            return Some(*first);
+        }
+        let (last_time, last) = history.back()?;
+        if time >= *last_time {
+            return Some(*last);
+        }
+        for i in 0..history.len() - 1 {
+            let (from_time, from) = &history[i];
+            let (to_time, to) = &history[i + 1];
+            if time >= *from_time && time < *to_time {
+                let t = ((time - from_time) / (to_time - from_time)) as f32;
+                return Some(Self::interpolate(from, to, t));
+            }
+        }
+        Some(*last)
+    }
+
+    fn interpolate(from: &Transform, to: &Transform, t: f32) -> Transform {
+        let slerp = |a: Quaternion<f32>, b: Quaternion<f32>| {
+            UnitQuaternion::from_quaternion(a)
+                .try_slerp(&UnitQuaternion::from_quaternion(b), t, 1.0e-6)
+                .map(|rotation| rotation.into_inner())
+                .unwrap_or(b)
+        };
+        Transform::new(
+            from.position.lerp(&to.position, t),
+            slerp(from.rotation, to.rotation),
+            from.scale.lerp(&to.scale, t),
+            from.local_position.lerp(&to.local_position, t),
+            slerp(from.local_rotation, to.local_rotation),
+            from.local_scale.lerp(&to.local_scale, t),
+        )
+    }
+
+    // 把其他连接的对象回溯到 conn_id 看到的时间，用完后需要 restore
+    pub fn rewind(conn_id: u64) -> Option<LagCompensationRewind> {
+        let time = Self::view_time(conn_id)?;
+        Some(Self::rewind_to(time, conn_id))
+    }
+
+    // 回溯到 time，exclude_conn_id 拥有的对象保持不变
+    pub fn rewind_to(time: f64, exclude_conn_id: u64) -> LagCompensationRewind {
+        let mut rewind = LagCompensationRewind {
+            time,
+            saved: Vec::new(),
+        };
+        LAG_COMPENSATION_TRACKS.iter().for_each(|track| {
+            if Self::is_owned_by(*track.key(), exclude_conn_id) {
+                return;
+            }
+            let component_key = match &track.component_key {
+                Some(component_key) => component_key,
+                None => return,
+            };
+            let transform = match Self::sample_history(&track.history, time) {
+                Some(transform) => transform,
+                None => return,
+            };
+            match NETWORK_BEHAVIOURS.try_get_mut(component_key) {
+                TryResult::Present(mut network_behaviour) => {
+                    let mut game_object = network_behaviour.game_object().clone();
+                    rewind
+                        .saved
+                        .push((component_key.clone(), game_object.transform));
+                    game_object.transform = transform;
+                    network_behaviour.set_game_object(game_object);
+                }
+                TryResult::Absent => {}
+                TryResult::Locked => {
+                    log_warn!(format!(
+                        "LagCompensation.rewind: NetworkBehaviour {} is locked",
+                        component_key
+                    ));
+                }
+            }
+        });
+        rewind
+    }
+
+    pub fn restore(rewind: LagCompensationRewind) {
+        for (component_key, transform) in rewind.saved {
+            match NETWORK_BEHAVIOURS.try_get_mut(&component_key) {
+                TryResult::Present(mut network_behaviour) => {
+                    let mut game_object = network_behaviour.game_object().clone();
+                    game_object.transform = transform;
+                    network_behaviour.set_game_object(game_object);
+                }
+                TryResult::Absent => {}
+                TryResult::Locked => {
+                    log_error!(format!(
+                        "LagCompensation.restore: NetworkBehaviour {} is locked",
+                        component_key
+                    ));
+                }
+            }
+        }
+    }
+
+    // 回溯，执行 func，然后恢复
+    pub fn with_rewind<T, F: FnOnce(f64) -> T>(conn_id: u64, func: F) -> Option<T> {
+        let rewind = Self::rewind(conn_id)?;
+        let result = func(rewind.time);
+        Self::restore(rewind);
+        Some(result)
+    }
+
+    fn is_owned_by(net_id: u32, conn_id: u64) -> bool {
+        match NetworkServerStatic::spawned_network_identities().try_get(&net_id) {
+            TryResult::Present(identity) => identity.connection_to_client() == conn_id,
+            _ => false,
+        }
+    }
+
+    // time 时所有命中盒的世界空间位置，不修改 NetworkTransform
+    pub fn hitboxes_at(time: f64, exclude_conn_id: u64) -> Vec<WorldHitbox> {
+        let mut hitboxes = Vec::new();
+        LAG_COMPENSATION_TRACKS.iter().for_each(|track| {
+            if track.hitboxes.is_empty() || Self::is_owned_by(*track.key(), exclude_conn_id) {
+                return;
+            }
+            let transform = match Self::sample_history(&track.history, time) {
+                Some(transform) => transform,
+                None => return,
+            };
+            let (position, rotation, scale) = match track.local_space {
+                true => (
+                    transform.local_position,
+                    transform.local_rotation,
+                    transform.local_scale,
+                ),
+                false => (transform.position, transform.rotation, transform.scale),
+            };
+            let rotation = UnitQuaternion::from_quaternion(rotation);
+            let scale = scale.abs();
+            for hitbox in track.hitboxes.iter() {
+                let radius = hitbox.radius * scale.x.max(scale.z);
+                hitboxes.push(WorldHitbox {
+                    net_id: *track.key(),
+                    name: hitbox.name.clone(),
+                    shape: hitbox.shape,
+                    center: position + rotation * hitbox.center.to_vector3().component_mul(&scale),
+                    rotation,
+                    half_extents: hitbox.size.to_vector3().component_mul(&scale) / 2.0,
+                    radius,
+                    half_height: (hitbox.height * scale.y / 2.0 - radius).max(0.0),
+                });
+            }
+        });
+        hitboxes
+    }
+
+    // 在 conn_id 看到的时间做射线检测，返回最近的命中
+    pub fn raycast(
+        conn_id: u64,
+        origin: Vector3<f32>,
+        direction: Vector3<f32>,
+        max_distance: f32,
+    ) -> Option<LagCompensationHit> {
+        let time = Self::view_time(conn_id)?;
+        let direction = direction.try_normalize(f32::EPSILON)?;
+        let mut closest: Option<LagCompensationHit> = None;
+        for hitbox in Self::hitboxes_at(time, conn_id) {
+            if let Some(distance) = hitbox.raycast(origin, direction) {
+                if distance <= max_distance
+                    && closest.as_ref().is_none_or(|hit| distance < hit.distance)
+                {
+                    closest = Some(LagCompensationHit {
+                        net_id: hitbox.net_id,
+                        hitbox: hitbox.name,
+                        distance,
+                    });
+                }
+            }
+        }
+        closest
+    }
+
+    // 在 conn_id 看到的时间做球形重叠检测
+    pub fn overlap_sphere(
+        conn_id: u64,
+        center: Vector3<f32>,
+        radius: f32,
+    ) -> Vec<LagCompensationHit> {
+        let time = match Self::view_time(conn_id) {
+            Some(time) => time,
+            None => return Vec::new(),
+        };
+        Self::hitboxes_at(time, conn_id)
+            .into_iter()
+            .filter_map(|hitbox| {
+                let distance = hitbox.distance_to_point(center);
+                match distance <= radius {
+                    true => Some(LagCompensationHit {
+                        net_id: hitbox.net_id,
+                        hitbox: hitbox.name,
+                        distance,
+                    }),
+                    false => None,
+                }
+            })
+            .collect()
+    }
+}
+
+impl WorldHitbox {
+    fn capsule_points(&self) -> (Vector3<f32>, Vector3<f32>) {
+        let axis = self.rotation * Vector3::y() * self.half_height;
+        (self.center - axis, self.center + axis)
+    }
+
+    // 射线与命中盒相交的距离，direction 需要归一化
+    pub fn raycast(&self, origin: Vector3<f32>, direction: Vector3<f32>) -> Option<f32> {
+        match self.shape {
+            ColliderShape::Sphere => {
+                Self::raycast_sphere(origin, direction, self.center, self.radius)
+            }
+            ColliderShape::Box => {
+                // 转到命中盒的局部空间做 slab 检测
+                let inverse = self.rotation.inverse();
+                let local_origin = inverse * (origin - self.center);
+                let local_direction = inverse * direction;
+                let mut t_min = 0.0f32;
+                let mut t_max = f32::MAX;
+                for i in 0..3 {
+                    if local_direction[i].abs() < f32::EPSILON {
+                        if local_origin[i].abs() > self.half_extents[i] {
+                            return None;
+                        }
+                        continue;
+                    }
+                    let t1 = (-self.half_extents[i] - local_origin[i]) / local_direction[i];
+                    let t2 = (self.half_extents[i] - local_origin[i]) / local_direction[i];
+                    t_min = t_min.max(t1.min(t2));
+                    t_max = t_max.min(t1.max(t2));
+                    if t_min > t_max {
+                        return None;
+                    }
+                }
+                Some(t_min)
+            }
+            ColliderShape::Capsule => {
+                // 胶囊体 = 两端的球 + 中间的圆柱
+                let (a, b) = self.capsule_points();
+                let mut closest = [
+                    Self::raycast_sphere(origin, direction, a, self.radius),
+                    Self::raycast_sphere(origin, direction, b, self.radius),
+                ]
+                .into_iter()
+                .flatten()
+                .fold(None, |closest: Option<f32>, t| {
+                    Some(closest.map_or(t, |c| c.min(t)))
+                });
+                let ba = b - a;
+                let oa = origin - a;
+                let baba = ba.dot(&ba);
+                let bard = ba.dot(&direction);
+                let baoa = ba.dot(&oa);
+                let k2 = baba - bard * bard;
+                if baba > f32::EPSILON && k2 > f32::EPSILON {
+                    let k1 = baba * direction.dot(&oa) - baoa * bard;
+                    let k0 = baba * oa.dot(&oa) - baoa * baoa - self.radius * self.radius * baba;
+                    let h = k1 * k1 - k2 * k0;
+                    if h >= 0.0 {
+                        let t = ((-k1 - h.sqrt()) / k2).max(0.0);
+                        let y = baoa + t * bard;
+                        if y > 0.0 && y < baba && (k0 <= 0.0 || -k1 - h.sqrt() >= 0.0) {
+                            closest = Some(closest.map_or(t, |c| c.min(t)));
+                        }
+                    }
+                }
+                closest
+            }
+        }
+    }
+
+    fn raycast_sphere(
+        origin: Vector3<f32>,
+        direction: Vector3<f32>,
+        center: Vector3<f32>,
+        radius: f32,
+    ) -> Option<f32> {
+        let oc = origin - center;
+        let b = oc.dot(&direction);
+        let c = oc.dot(&oc) - radius * radius;
+        // 起点在球内
+        if c <= 0.0 {
+            return Some(0.0);
+        }
+        let h = b * b - c;
+        if b > 0.0 || h < 0.0 {
+            return None;
+        }
+        Some(-b - h.sqrt())
+    }
+
+    // 点到命中盒表面的距离，在内部时为 0
+    pub fn distance_to_point(&self, point: Vector3<f32>) -> f32 {
+        match self.shape {
+            ColliderShape::Sphere => ((point - self.center).norm() - self.radius).max(0.0),
+            ColliderShape::Box => {
+                let local = self.rotation.inverse() * (point - self.center);
+                let outside = Vector3::new(
+                    (local.x.abs() - self.half_extents.x).max(0.0),
+                    (local.y.abs() - self.half_extents.y).max(0.0),
+                    (local.z.abs() - self.half_extents.z).max(0.0),
+                );
+                outside.norm()
+            }
+            ColliderShape::Capsule => {
+                let (a, b) = self.capsule_points();
+                let ba = b - a;
+                let baba = ba.dot(&ba);
+                let t = match baba > f32::EPSILON {
+                    true => ((point - a).dot(&ba) / baba).clamp(0.0, 1.0),
+                    false => 0.0,
+                };
+                ((point - (a + ba * t)).norm() - self.radius).max(0.0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviourTrait};
+    use crate::mirror::core::network_connection::NetworkConnectionTrait;
+    use crate::mirror::core::network_connection_to_client::NetworkConnectionToClient;
+    use crate::mirror::core::network_identity::tests::common_behaviour;
+    use crate::mirror::core::network_identity::NetworkIdentity;
+    use crate::mirror::core::network_server::tests::lock_server;
+    use crate::mirror::core::snapshot_interpolation::time_snapshot::TimeSnapshot;
+    use ordered_float::OrderedFloat;
+
+    fn transform(x: f32, angle: f32) -> Transform {
+        let rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), angle).into_inner();
+        let position = Vector3::new(x, 0.0, 0.0);
+        let scale = Vector3::new(1.0, 1.0, 1.0);
+        Transform::new(position, rotation, scale, position, rotation, scale)
+    }
+
+    // 生成一个带历史的对象，当前位置为 current_x，history 中为 (time, x)
+    fn spawn_track(owner: u64, current_x: f32, history: &[(f64, f32)]) -> u32 {
+        let mut identity = NetworkIdentity::new_with_asset_id(0);
+        let net_id = NetworkIdentity::get_static_next_network_id();
+        identity.set_net_id(net_id);
+        identity.set_connection_to_client(owner);
+        NetworkServerStatic::spawned_network_identities().insert(net_id, identity);
+
+        let component_key = format!("{}_0", net_id);
+        let mut behaviour = common_behaviour(0, 0, &[]);
+        let game_object = GameObject {
+            transform: transform(current_x, 0.0),
+            ..Default::default()
+        };
+        behaviour.set_game_object(game_object);
+        NETWORK_BEHAVIOURS.insert(component_key.clone(), Box::new(behaviour));
+
+        LAG_COMPENSATION_TRACKS.insert(
+            net_id,
+            LagCompensationTrack {
+                asset_id: 0,
+                scene_id: 0,
+                component_key: Some(component_key),
+                local_space: false,
+                hitboxes: Vec::new(),
+                history: history
+                    .iter()
+                    .map(|(time, x)| (*time, transform(*x, 0.0)))
+                    .collect(),
+            },
+        );
+        net_id
+    }
+
+    fn position_x(net_id: u32) -> f32 {
+        NETWORK_BEHAVIOURS
+            .get(&format!("{}_0", net_id))
+            .unwrap()
+            .game_object()
+            .transform
+            .position
+            .x
+    }
+
+    fn remove_tracks(net_ids: &[u32]) {
+        for net_id in net_ids {
+            NetworkServerStatic::spawned_network_identities().remove(net_id);
+            NETWORK_BEHAVIOURS.remove(&format!("{}_0", net_id));
+        }
+        LagCompensation::reset();
+    }
+
+    fn hitbox(shape: ColliderShape, rotation: UnitQuaternion<f32>) -> WorldHitbox {
+        WorldHitbox {
+            net_id: 1,
+            name: "body".to_string(),
+            shape,
+            center: Vector3::new(0.0, 0.0, 10.0),
+            rotation,
+            half_extents: Vector3::new(1.0, 1.0, 1.0),
+            radius: 0.5,
+            half_height: 1.0,
+        }
+    }
+
+    fn assert_near(value: Option<f32>, expected: f32) {
+        let value = value.expect("expected a hit");
+        assert!(
+            (value - expected).abs() < 1.0e-4,
+            "{} != {}",
+            value,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_world_hitbox_raycast() {
+        let forward = Vector3::z();
+        let origin = |x: f32, y: f32| Vector3::new(x, y, 0.0);
+        let identity = UnitQuaternion::identity();
+
+        let mut sphere = hitbox(ColliderShape::Sphere, identity);
+        sphere.radius = 1.0;
+        assert_near(sphere.raycast(origin(0.0, 0.0), forward), 9.0);
+        assert_eq!(sphere.raycast(origin(0.0, 0.0), Vector3::x()), None);
+        assert_eq!(sphere.raycast(origin(1.5, 0.0), forward), None);
+        // 起点在球内
+        assert_near(sphere.raycast(Vector3::new(0.0, 0.0, 10.0), forward), 0.0);
+
+        let cube = hitbox(ColliderShape::Box, identity);
+        assert_near(cube.raycast(origin(0.0, 0.0), forward), 9.0);
+        assert_near(cube.raycast(origin(0.9, 0.9), forward), 9.0);
+        assert_eq!(cube.raycast(origin(1.5, 0.0), forward), None);
+        assert_eq!(cube.raycast(origin(0.0, 0.0), -forward), None);
+
+        // 绕 y 轴旋转 45 度后棱角朝向射线
+        let rotated =
+            UnitQuaternion::from_axis_angle(&Vector3::y_axis(), std::f32::consts::FRAC_PI_4);
+        let cube = hitbox(ColliderShape::Box, rotated);
+        assert_near(
+            cube.raycast(origin(0.0, 0.0), forward),
+            10.0 - 2.0f32.sqrt(),
+        );
+        assert_near(
+            cube.raycast(origin(1.2, 0.0), forward),
+            10.0 - 2.0f32.sqrt() + 1.2,
+        );
+        assert_eq!(cube.raycast(origin(1.5, 0.0), forward), None);
+
+        let capsule = hitbox(ColliderShape::Capsule, identity);
+        // 圆柱部分
+        assert_near(capsule.raycast(origin(0.0, 0.0), forward), 9.5);
+        assert_near(capsule.raycast(origin(0.0, 0.9), forward), 9.5);
+        // 顶端的球
+        assert_near(
+            capsule.raycast(origin(0.0, 1.2), forward),
+            10.0 - 0.21f32.sqrt(),
+        );
+        assert_eq!(capsule.raycast(origin(0.0, 1.6), forward), None);
+        assert_eq!(capsule.raycast(origin(0.6, 0.0), forward), None);
+        // 从上往下打到顶端
+        assert_near(
+            capsule.raycast(Vector3::new(0.0, 5.0, 10.0), -Vector3::y()),
+            3.5,
+        );
+    }
+
+    #[test]
+    fn test_world_hitbox_distance_to_point() {
+        let identity = UnitQuaternion::identity();
+        let point = Vector3::new(0.0, 0.0, 13.0);
+
+        let mut sphere = hitbox(ColliderShape::Sphere, identity);
+        sphere.radius = 1.0;
+        assert!((sphere.distance_to_point(point) - 2.0).abs() < 1.0e-4);
+        assert_eq!(sphere.distance_to_point(Vector3::new(0.0, 0.5, 10.0)), 0.0);
+
+        let cube = hitbox(ColliderShape::Box, identity);
+        assert!((cube.distance_to_point(point) - 2.0).abs() < 1.0e-4);
+        assert!(
+            (cube.distance_to_point(Vector3::new(2.0, 2.0, 10.0)) - 2.0f32.sqrt()).abs() < 1.0e-4
+        );
+        assert_eq!(cube.distance_to_point(Vector3::new(0.9, -0.9, 10.9)), 0.0);
+
+        let capsule = hitbox(ColliderShape::Capsule, identity);
+        assert!((capsule.distance_to_point(Vector3::new(0.0, 3.0, 10.0)) - 1.5).abs() < 1.0e-4);
+        assert!((capsule.distance_to_point(Vector3::new(2.0, 0.5, 10.0)) - 1.5).abs() < 1.0e-4);
+        assert_eq!(capsule.distance_to_point(Vector3::new(0.0, 1.2, 10.0)), 0.0);
+    }
+
+    #[test]
+    fn test_sample_history() {
+        let mut history = VecDeque::new();
+        assert!(LagCompensation::sample_history(&history, 1.0).is_none());
+
+        history.push_back((1.0, transform(0.0, 0.0)));
+        history.push_back((2.0, transform(10.0, std::f32::consts::FRAC_PI_2)));
+        history.push_back((3.0, transform(20.0, std::f32::consts::FRAC_PI_2)));
+
+        // 超出范围时使用最早和最新的记录
+        assert_eq!(
+            LagCompensation::sample_history(&history, 0.5)
+                .unwrap()
+                .position
+                .x,
+            0.0
+        );
+        assert_eq!(
+            LagCompensation::sample_history(&history, 3.5)
+                .unwrap()
+                .position
+                .x,
+            20.0
+        );
+        assert_eq!(
+            LagCompensation::sample_history(&history, 2.0)
+                .unwrap()
+                .position
+                .x,
+            10.0
+        );
+
+        let sample = LagCompensation::sample_history(&history, 1.5).unwrap();
+        assert!((sample.position.x - 5.0).abs() < 1.0e-4);
+        assert!((sample.local_position.x - 5.0).abs() < 1.0e-4);
+        let angle = UnitQuaternion::from_quaternion(sample.rotation).angle();
+        assert!((angle - std::f32::consts::FRAC_PI_4).abs() < 1.0e-4);
+
+        let sample = LagCompensation::sample_history(&history, 2.25).unwrap();
+        assert!((sample.position.x - 12.5).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_rewind_to_and_restore() {
+        let _lock = lock_server();
+        let owned = spawn_track(1, 100.0, &[(1.0, 0.0), (2.0, 10.0)]);
+        let other = spawn_track(2, 100.0, &[(1.0, 0.0), (2.0, 10.0), (3.0, 30.0)]);
+
+        // 插值到 time 所在的两个快照之间，请求的连接拥有的对象不回溯
+        let rewind = LagCompensation::rewind_to(2.5, 1);
+        assert_eq!(rewind.time, 2.5);
+        assert!((position_x(other) - 20.0).abs() < 1.0e-4);
+        assert_eq!(position_x(owned), 100.0);
+
+        LagCompensation::restore(rewind);
+        assert_eq!(position_x(other), 100.0);
+        assert_eq!(position_x(owned), 100.0);
+
+        // 另一个连接请求时回溯 owned
+        let rewind = LagCompensation::rewind_to(1.5, 2);
+        assert!((position_x(owned) - 5.0).abs() < 1.0e-4);
+        assert_eq!(position_x(other), 100.0);
+        LagCompensation::restore(rewind);
+        assert_eq!(position_x(owned), 100.0);
+
+        remove_tracks(&[owned, other]);
+    }
+
+    #[test]
+    fn test_view_time() {
+        let _lock = lock_server();
+        assert!(LagCompensation::view_time(1).is_none());
+
+        let mut conn = NetworkConnectionToClient::new(1);
+        conn._rtt.value = 0.1;
+        conn.buffer_time = 0.2;
+        NetworkServerStatic::network_connections().insert(1, conn);
+
+        // 没有快照时使用 buffer_time
+        let before = NetworkTime::local_time();
+        let view_time = LagCompensation::view_time(1).unwrap();
+        let after = NetworkTime::local_time();
+        assert!(view_time >= before - 0.25 && view_time <= after - 0.25);
+
+        // 有快照时使用 remote_timeline 落后最新快照的时间
+        if let Some(mut conn) = NetworkServerStatic::network_connections().get_mut(&1) {
+            conn.snapshots
+                .insert(OrderedFloat(10.0), TimeSnapshot::new(10.0, 0.0));
+            conn.remote_timeline = 9.9;
+        }
+        let before = NetworkTime::local_time();
+        let view_time = LagCompensation::view_time(1).unwrap();
+        let after = NetworkTime::local_time();
+        assert!(view_time >= before - 0.15 - 1.0e-6 && view_time <= after - 0.15 + 1.0e-6);
+
+        // 不超过 max_rewind
+        if let Some(mut conn) = NetworkServerStatic::network_connections().get_mut(&1) {
+            conn.remote_timeline = 5.0;
+        }
+        let before = NetworkTime::local_time();
+        let view_time = LagCompensation::view_time(1).unwrap();
+        let after = NetworkTime::local_time();
+        let max_rewind = LagCompensation::max_rewind();
+        assert!(view_time >= before - max_rewind && view_time <= after - max_rewind);
+
+        NetworkServerStatic::network_connections().clear();
+    }
+
+    #[test]
+    fn test_with_rewind_restores_after_early_return() {
+        let _lock = lock_server();
+        let mut conn = NetworkConnectionToClient::new(1);
+        conn._rtt.value = 0.0;
+        conn.buffer_time = 0.1;
+        NetworkServerStatic::network_connections().insert(1, conn);
+
+        // 历史覆盖 [now - 1, now + 1]，x 与时间对应
+        let now = NetworkTime::local_time();
+        let history = [(now - 1.0, 0.0), (now + 1.0, 20.0)];
+        let owned = spawn_track(1, 100.0, &history);
+        let other = spawn_track(2, 100.0, &history);
+
+        let result = LagCompensation::with_rewind(1, |time| -> Option<f32> {
+            let x = position_x(other);
+            assert!((x - (time - now + 1.0) as f32 * 10.0).abs() < 1.0e-3);
+            assert_eq!(position_x(owned), 100.0);
+            // 没有历史的对象提前返回
+            let missing = LagCompensation::sample(u32::MAX, time)?;
+            Some(missing.position.x)
+        });
+        assert_eq!(result, Some(None));
+        assert_eq!(position_x(other), 100.0);
+        assert_eq!(position_x(owned), 100.0);
+
+        // 找不到连接时不回溯也不执行
+        assert_eq!(LagCompensation::with_rewind(3, |_| ()), None);
+
+        remove_tracks(&[owned, other]);
+        NetworkServerStatic::network_connections().clear();
+    }
+}
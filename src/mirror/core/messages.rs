@@ -1,28 +1,55 @@
-use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait};
+use crate::log_error;
+use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTryTrait, ReadError};
 use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
 use crate::mirror::core::tools::stable_hash::StableHash;
 use crate::mirror::core::transport::TransportChannel;
 use nalgebra::{Quaternion, Vector3};
 use std::any::Any;
+use std::sync::{Arc, Mutex};
 
 pub type NetworkMessageHandlerFunc = fn(u64, &mut NetworkReader, TransportChannel);
+// 可以携带状态的处理程序
+pub type NetworkMessageHandlerClosure =
+    Box<dyn FnMut(u64, &mut NetworkReader, TransportChannel) + Send>;
 
 pub struct NetworkMessageHandler {
-    pub func: NetworkMessageHandlerFunc,
+    pub func: Arc<Mutex<NetworkMessageHandlerClosure>>,
     pub require_authentication: bool,
 }
 
 impl NetworkMessageHandler {
     pub fn wrap_handler(func: NetworkMessageHandlerFunc, require_authentication: bool) -> Self {
+        Self::wrap_closure(Box::new(func), require_authentication)
+    }
+
+    pub fn wrap_closure(func: NetworkMessageHandlerClosure, require_authentication: bool) -> Self {
         Self {
-            func,
+            func: Arc::new(Mutex::new(func)),
             require_authentication,
         }
     }
+
+    // 调用前需要释放 NETWORK_MESSAGE_HANDLERS 的锁，处理程序内部可以注册或替换处理程序
+    pub fn invoke(
+        func: &Mutex<NetworkMessageHandlerClosure>,
+        connection_id: u64,
+        reader: &mut NetworkReader,
+        channel: TransportChannel,
+    ) {
+        match func.try_lock() {
+            Ok(mut func) => (*func)(connection_id, reader, channel),
+            Err(e) => {
+                log_error!(format!(
+                    "NetworkMessageHandler.invoke() connectionId: {} error: {}",
+                    connection_id, e
+                ));
+            }
+        }
+    }
 }
 
 pub trait NetworkMessageTrait: Send + Sync {
-    fn deserialize(reader: &mut NetworkReader) -> Self
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError>
     where
         Self: Sized;
     fn serialize(&mut self, writer: &mut NetworkWriter)
@@ -43,9 +70,9 @@ pub trait NetworkMessageTrait: Send + Sync {
 #[derive(Debug, PartialEq, Clone, Copy, Default)]
 pub struct TimeSnapshotMessage;
 impl NetworkMessageTrait for TimeSnapshotMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
         let _ = reader;
-        Self
+        Ok(Self)
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -57,7 +84,7 @@ impl NetworkMessageTrait for TimeSnapshotMessage {
     where
         Self: Sized,
     {
-        &"Mirror.TimeSnapshotMessage"
+        "Mirror.TimeSnapshotMessage"
     }
 
     fn as_any_mut(&mut self) -> &mut dyn Any {
@@ -68,9 +95,9 @@ impl NetworkMessageTrait for TimeSnapshotMessage {
 #[derive(Debug, PartialEq, Clone, Copy, Default)]
 pub struct ReadyMessage;
 impl NetworkMessageTrait for ReadyMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
         let _ = reader;
-        Self
+        Ok(Self)
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -93,9 +120,9 @@ impl NetworkMessageTrait for ReadyMessage {
 #[derive(Debug, PartialEq, Clone, Copy, Default)]
 pub struct NotReadyMessage;
 impl NetworkMessageTrait for NotReadyMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
         let _ = reader;
-        Self
+        Ok(Self)
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -118,9 +145,9 @@ impl NetworkMessageTrait for NotReadyMessage {
 #[derive(Debug, PartialEq, Clone, Copy, Default)]
 pub struct AddPlayerMessage;
 impl NetworkMessageTrait for AddPlayerMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
         let _ = reader;
-        Self
+        Ok(Self)
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -182,15 +209,15 @@ impl SceneMessage {
     }
 }
 impl NetworkMessageTrait for SceneMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        let scene_name = reader.read_string();
-        let operation = SceneOperation::from(reader.read_byte());
-        let custom_handling = reader.read_bool();
-        Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let scene_name = reader.try_read_string()?;
+        let operation = SceneOperation::from(reader.try_read_byte()?);
+        let custom_handling = reader.try_read_bool()?;
+        Ok(Self {
             scene_name,
             operation,
             custom_handling,
-        }
+        })
     }
     fn serialize(&mut self, writer: &mut NetworkWriter) {
         // 3552
@@ -245,17 +272,17 @@ impl CommandMessage {
 }
 
 impl NetworkMessageTrait for CommandMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        let net_id = reader.decompress_var_uint();
-        let component_index = reader.read_byte();
-        let function_hash = reader.read_ushort();
-        let payload = reader.read_bytes_and_size();
-        Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        let component_index = reader.try_read_byte()?;
+        let function_hash = reader.try_read_ushort()?;
+        let payload = reader.try_read_bytes_and_size()?;
+        Ok(Self {
             net_id,
             component_index,
             function_hash,
             payload,
-        }
+        })
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -308,17 +335,17 @@ impl RpcMessage {
     }
 }
 impl NetworkMessageTrait for RpcMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        let net_id = reader.decompress_var_uint();
-        let component_index = reader.read_byte();
-        let function_hash = reader.read_ushort();
-        let payload = reader.read_bytes_and_size();
-        Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        let component_index = reader.try_read_byte()?;
+        let function_hash = reader.try_read_ushort()?;
+        let payload = reader.try_read_bytes_and_size()?;
+        Ok(Self {
             net_id,
             component_index,
             function_hash,
             payload,
-        }
+        })
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -355,7 +382,7 @@ pub struct SpawnMessage {
     pub payload: Vec<u8>,
 }
 impl SpawnMessage {
-    #[allow(dead_code)]
+    #[allow(dead_code, clippy::too_many_arguments)]
     pub fn new(
         net_id: u32,
         is_local_player: bool,
@@ -385,17 +412,17 @@ impl SpawnMessage {
     }
 }
 impl NetworkMessageTrait for SpawnMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        let net_id = reader.decompress_var_uint();
-        let is_local_player = reader.read_bool();
-        let is_owner = reader.read_bool();
-        let scene_id = reader.decompress_var_ulong();
-        let asset_id = reader.decompress_var_uint();
-        let position = reader.read_vector3();
-        let rotation = reader.read_quaternion();
-        let scale = reader.read_vector3();
-        let payload = reader.read_bytes_and_size();
-        Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        let is_local_player = reader.try_read_bool()?;
+        let is_owner = reader.try_read_bool()?;
+        let scene_id = reader.try_decompress_var_ulong()?;
+        let asset_id = reader.try_decompress_var_uint()?;
+        let position = reader.try_read_vector3()?;
+        let rotation = reader.try_read_quaternion()?;
+        let scale = reader.try_read_vector3()?;
+        let payload = reader.try_read_bytes_and_size()?;
+        Ok(Self {
             net_id,
             is_local_player,
             is_owner,
@@ -405,7 +432,7 @@ impl NetworkMessageTrait for SpawnMessage {
             rotation,
             scale,
             payload,
-        }
+        })
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -451,15 +478,15 @@ impl ChangeOwnerMessage {
     }
 }
 impl NetworkMessageTrait for ChangeOwnerMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        let net_id = reader.decompress_var_uint();
-        let is_owner = reader.read_bool();
-        let is_local_player = reader.read_bool();
-        Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        let is_owner = reader.try_read_bool()?;
+        let is_local_player = reader.try_read_bool()?;
+        Ok(Self {
             net_id,
             is_owner,
             is_local_player,
-        }
+        })
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -484,9 +511,9 @@ impl NetworkMessageTrait for ChangeOwnerMessage {
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct ObjectSpawnStartedMessage;
 impl NetworkMessageTrait for ObjectSpawnStartedMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
         let _ = reader;
-        Self
+        Ok(Self)
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -509,9 +536,9 @@ impl NetworkMessageTrait for ObjectSpawnStartedMessage {
 #[derive(Debug, PartialEq, Clone, Default)]
 pub struct ObjectSpawnFinishedMessage;
 impl NetworkMessageTrait for ObjectSpawnFinishedMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
         let _ = reader;
-        Self
+        Ok(Self)
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -542,9 +569,9 @@ impl ObjectDestroyMessage {
     }
 }
 impl NetworkMessageTrait for ObjectDestroyMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        let net_id = reader.decompress_var_uint();
-        Self { net_id }
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        Ok(Self { net_id })
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -576,9 +603,9 @@ impl ObjectHideMessage {
     }
 }
 impl NetworkMessageTrait for ObjectHideMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        let net_id = reader.decompress_var_uint();
-        Self { net_id }
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        Ok(Self { net_id })
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -616,10 +643,10 @@ impl EntityStateMessage {
     }
 }
 impl NetworkMessageTrait for EntityStateMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        let net_id = reader.decompress_var_uint();
-        let payload = reader.read_bytes_and_size();
-        Self { net_id, payload }
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        let payload = reader.try_read_bytes_and_size()?;
+        Ok(Self { net_id, payload })
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -657,13 +684,13 @@ impl NetworkPingMessage {
 }
 
 impl NetworkMessageTrait for NetworkPingMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        let local_time = reader.read_double();
-        let predicted_time_adjusted = reader.read_double();
-        Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let local_time = reader.try_read_double()?;
+        let predicted_time_adjusted = reader.try_read_double()?;
+        Ok(Self {
             local_time,
             predicted_time_adjusted,
-        }
+        })
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -706,15 +733,15 @@ impl NetworkPongMessage {
     }
 }
 impl NetworkMessageTrait for NetworkPongMessage {
-    fn deserialize(reader: &mut NetworkReader) -> Self {
-        let local_time = reader.read_double();
-        let prediction_error_unadjusted = reader.read_double();
-        let prediction_error_adjusted = reader.read_double();
-        Self {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let local_time = reader.try_read_double()?;
+        let prediction_error_unadjusted = reader.try_read_double()?;
+        let prediction_error_adjusted = reader.try_read_double()?;
+        Ok(Self {
             local_time,
             prediction_error_unadjusted,
             prediction_error_adjusted,
-        }
+        })
     }
 
     fn serialize(&mut self, writer: &mut NetworkWriter) {
@@ -736,3 +763,338 @@ impl NetworkMessageTrait for NetworkPongMessage {
         self
     }
 }
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[repr(u8)]
+pub enum DisconnectReason {
+    #[default]
+    None = 0,
+    ServerShutdown = 1,
+    ServerRestart = 2,
+}
+impl DisconnectReason {
+    pub fn from(value: u8) -> DisconnectReason {
+        match value {
+            0 => DisconnectReason::None,
+            1 => DisconnectReason::ServerShutdown,
+            2 => DisconnectReason::ServerRestart,
+            _ => DisconnectReason::None,
+        }
+    }
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct DisconnectMessage {
+    pub reason: DisconnectReason,
+    pub message: String,
+}
+impl DisconnectMessage {
+    #[allow(dead_code)]
+    pub fn new(reason: DisconnectReason, message: String) -> Self {
+        Self { reason, message }
+    }
+}
+impl NetworkMessageTrait for DisconnectMessage {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let reason = DisconnectReason::from(reader.try_read_byte()?);
+        let message = reader.try_read_string()?;
+        Ok(Self { reason, message })
+    }
+
+    fn serialize(&mut self, writer: &mut NetworkWriter) {
+        writer.write_ushort(Self::get_full_name().get_stable_hash_code16());
+        writer.write_byte(self.reason.to_u8());
+        writer.write_str(self.message.as_str());
+    }
+
+    fn get_full_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        "Mirror.DisconnectMessage"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ServerRestartMessage {
+    pub seconds_remaining: u32,
+    pub message: String,
+}
+impl ServerRestartMessage {
+    #[allow(dead_code)]
+    pub fn new(seconds_remaining: u32, message: String) -> Self {
+        Self {
+            seconds_remaining,
+            message,
+        }
+    }
+}
+impl NetworkMessageTrait for ServerRestartMessage {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let seconds_remaining = reader.try_read_uint()?;
+        let message = reader.try_read_string()?;
+        Ok(Self {
+            seconds_remaining,
+            message,
+        })
+    }
+
+    fn serialize(&mut self, writer: &mut NetworkWriter) {
+        writer.write_ushort(Self::get_full_name().get_stable_hash_code16());
+        writer.write_uint(self.seconds_remaining);
+        writer.write_str(self.message.as_str());
+    }
+
+    fn get_full_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        "Mirror.ServerRestartMessage"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct UnreliableStateMessage {
+    pub net_id: u32,
+    pub tick: u32,
+    // None 时 payload 为完整状态，否则为相对该 tick 基线的差分
+    pub baseline_tick: Option<u32>,
+    pub payload: Vec<u8>,
+}
+impl UnreliableStateMessage {
+    #[allow(dead_code)]
+    pub fn new(net_id: u32, tick: u32, baseline_tick: Option<u32>, payload: Vec<u8>) -> Self {
+        Self {
+            net_id,
+            tick,
+            baseline_tick,
+            payload,
+        }
+    }
+}
+impl NetworkMessageTrait for UnreliableStateMessage {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        let tick = reader.try_read_uint()?;
+        let baseline_tick = reader.try_read_uint_nullable()?;
+        let payload = reader.try_read_bytes_and_size()?;
+        Ok(Self {
+            net_id,
+            tick,
+            baseline_tick,
+            payload,
+        })
+    }
+
+    fn serialize(&mut self, writer: &mut NetworkWriter) {
+        writer.write_ushort(Self::get_full_name().get_stable_hash_code16());
+        writer.compress_var_uint(self.net_id);
+        writer.write_uint(self.tick);
+        writer.write_uint_nullable(self.baseline_tick);
+        writer.write_array_segment_and_size(self.payload.as_slice());
+    }
+
+    fn get_full_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        "Mirror.UnreliableStateMessage"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct StateAckMessage {
+    pub net_id: u32,
+    pub tick: u32,
+}
+impl StateAckMessage {
+    #[allow(dead_code)]
+    pub fn new(net_id: u32, tick: u32) -> Self {
+        Self { net_id, tick }
+    }
+}
+impl NetworkMessageTrait for StateAckMessage {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        let tick = reader.try_read_uint()?;
+        Ok(Self { net_id, tick })
+    }
+
+    fn serialize(&mut self, writer: &mut NetworkWriter) {
+        writer.write_ushort(Self::get_full_name().get_stable_hash_code16());
+        writer.compress_var_uint(self.net_id);
+        writer.write_uint(self.tick);
+    }
+
+    fn get_full_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        "Mirror.StateAckMessage"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct InputMessage {
+    pub net_id: u32,
+    pub component_index: u8,
+    // 最新一条输入的 sequence，inputs[i] 的 sequence 为 sequence - i
+    pub sequence: u32,
+    // 从新到旧，包含之前的输入作为冗余
+    pub inputs: Vec<Vec<u8>>,
+}
+impl InputMessage {
+    #[allow(dead_code)]
+    pub fn new(net_id: u32, component_index: u8, sequence: u32, inputs: Vec<Vec<u8>>) -> Self {
+        Self {
+            net_id,
+            component_index,
+            sequence,
+            inputs,
+        }
+    }
+}
+impl NetworkMessageTrait for InputMessage {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        let component_index = reader.try_read_byte()?;
+        let sequence = reader.try_read_uint()?;
+        let count = reader.try_read_byte()?;
+        let mut inputs = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            inputs.push(reader.try_read_bytes_and_size()?);
+        }
+        Ok(Self {
+            net_id,
+            component_index,
+            sequence,
+            inputs,
+        })
+    }
+
+    fn serialize(&mut self, writer: &mut NetworkWriter) {
+        writer.write_ushort(Self::get_full_name().get_stable_hash_code16());
+        writer.compress_var_uint(self.net_id);
+        writer.write_byte(self.component_index);
+        writer.write_uint(self.sequence);
+        writer.write_byte(self.inputs.len() as u8);
+        for input in self.inputs.iter() {
+            writer.write_array_segment_and_size(input.as_slice());
+        }
+    }
+
+    fn get_full_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        "Mirror.InputMessage"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct InputAckMessage {
+    pub net_id: u32,
+    pub component_index: u8,
+    // 服务器最后处理的输入 sequence
+    pub sequence: u32,
+}
+impl InputAckMessage {
+    #[allow(dead_code)]
+    pub fn new(net_id: u32, component_index: u8, sequence: u32) -> Self {
+        Self {
+            net_id,
+            component_index,
+            sequence,
+        }
+    }
+}
+impl NetworkMessageTrait for InputAckMessage {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        let component_index = reader.try_read_byte()?;
+        let sequence = reader.try_read_uint()?;
+        Ok(Self {
+            net_id,
+            component_index,
+            sequence,
+        })
+    }
+
+    fn serialize(&mut self, writer: &mut NetworkWriter) {
+        writer.write_ushort(Self::get_full_name().get_stable_hash_code16());
+        writer.compress_var_uint(self.net_id);
+        writer.write_byte(self.component_index);
+        writer.write_uint(self.sequence);
+    }
+
+    fn get_full_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        "Mirror.InputAckMessage"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct RequestAuthorityMessage {
+    pub net_id: u32,
+    // true 表示释放权限
+    pub release: bool,
+}
+impl RequestAuthorityMessage {
+    #[allow(dead_code)]
+    pub fn new(net_id: u32, release: bool) -> Self {
+        Self { net_id, release }
+    }
+}
+impl NetworkMessageTrait for RequestAuthorityMessage {
+    fn deserialize(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        let net_id = reader.try_decompress_var_uint()?;
+        let release = reader.try_read_bool()?;
+        Ok(Self { net_id, release })
+    }
+
+    fn serialize(&mut self, writer: &mut NetworkWriter) {
+        writer.write_ushort(Self::get_full_name().get_stable_hash_code16());
+        writer.compress_var_uint(self.net_id);
+        writer.write_bool(self.release);
+    }
+
+    fn get_full_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        "Mirror.RequestAuthorityMessage"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -12,7 +12,7 @@ pub mod messages;
 
 mod network_writer_extensions;
 pub mod network_writer_pool;
-mod batching;
+pub mod batching;
 pub mod connection_quality;
 pub mod network_reader;
 mod network_reader_extensions;
@@ -21,6 +21,17 @@ pub mod network_reader_pool;
 pub mod network_connection_to_client;
 pub mod network_connection;
 pub mod sync_object;
+pub mod sync_list;
+pub mod sync_dictionary;
+pub mod sync_hash_set;
+pub mod csharp_types;
 pub mod network_loop;
 pub mod network_behaviour;
 pub mod network_start_position;
+pub mod state_baseline;
+pub mod input_buffer;
+pub mod authority_policy;
+pub mod rate_limiter;
+pub mod server_events;
+pub mod network_field;
+pub mod lag_compensation;
@@ -9,13 +9,16 @@ use crate::mirror::components::network_transform::network_transform_unreliable::
 use crate::mirror::core::backend_data::{
     BackendDataStatic, NetworkBehaviourComponent, NetworkBehaviourSetting,
 };
-use crate::mirror::core::messages::{EntityStateMessage, RpcMessage};
+use crate::mirror::core::messages::{EntityStateMessage, NetworkMessageTrait, RpcMessage};
 use crate::mirror::core::network_connection::NetworkConnectionTrait;
+use crate::mirror::core::network_field::NetworkField;
 use crate::mirror::core::network_identity::NetworkIdentity;
+use crate::mirror::core::network_messages::NetworkMessages;
 use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait};
-use crate::mirror::core::network_server::NetworkServerStatic;
+use crate::mirror::core::network_server::{NetworkServerStatic, NETWORK_BEHAVIOURS};
 use crate::mirror::core::network_time::NetworkTime;
 use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
+use crate::mirror::core::network_writer_pool::NetworkWriterPool;
 use crate::mirror::core::sync_object::SyncObject;
 use crate::mirror::core::transport::TransportChannel;
 use crate::{log_error, log_warn};
@@ -55,47 +58,33 @@ impl NetworkBehaviourFactory {
     }
     pub fn register_network_behaviour_factory() {
         // NetworkTransformUnreliable
-        Self::add_network_behaviour_factory(
-            NetworkTransformUnreliable::COMPONENT_TAG.to_string(),
-            |game_object: GameObject, component: &NetworkBehaviourComponent| {
-                Box::new(NetworkTransformUnreliable::new(game_object, component))
-            },
-        );
+        NetworkTransformUnreliable::register_network_behaviour_factory();
         // NetworkTransformReliable
-        Self::add_network_behaviour_factory(
-            NetworkTransformReliable::COMPONENT_TAG.to_string(),
-            |game_object: GameObject, component: &NetworkBehaviourComponent| {
-                Box::new(NetworkTransformReliable::new(game_object, component))
-            },
-        );
+        NetworkTransformReliable::register_network_behaviour_factory();
         // NetworkRigidbodyUnreliable
         Self::add_network_behaviour_factory(
             NetworkRigidbodyUnreliable::COMPONENT_TAG.to_string(),
             |game_object: GameObject, component: &NetworkBehaviourComponent| {
-                Box::new(NetworkTransformUnreliable::new(game_object, component))
+                Box::new(NetworkRigidbodyUnreliable::create_network_transform(
+                    game_object,
+                    component,
+                ))
             },
         );
         // NetworkRigidbodyReliable
         Self::add_network_behaviour_factory(
             NetworkRigidbodyReliable::COMPONENT_TAG.to_string(),
             |game_object: GameObject, component: &NetworkBehaviourComponent| {
-                Box::new(NetworkTransformReliable::new(game_object, component))
+                Box::new(NetworkRigidbodyReliable::create_network_transform(
+                    game_object,
+                    component,
+                ))
             },
         );
         // NetworkAnimator
-        Self::add_network_behaviour_factory(
-            NetworkAnimator::COMPONENT_TAG.to_string(),
-            |game_object: GameObject, component: &NetworkBehaviourComponent| {
-                Box::new(NetworkAnimator::new(game_object, component))
-            },
-        );
+        NetworkAnimator::register_network_behaviour_factory();
         // Mirror.NetworkRoomPlayer
-        Self::add_network_behaviour_factory(
-            NetworkRoomPlayer::COMPONENT_TAG.to_string(),
-            |game_object: GameObject, component: &NetworkBehaviourComponent| {
-                Box::new(NetworkRoomPlayer::new(game_object, component))
-            },
-        );
+        NetworkRoomPlayer::register_network_behaviour_factory();
     }
 }
 
@@ -126,24 +115,13 @@ impl GameObject {
             active: false,
         }
     }
-    pub fn default() -> Self {
-        Self {
-            scene_name: "".to_string(),
-            prefab: "".to_string(),
-            transform: Transform::default(),
-            active: false,
-        }
-    }
     pub fn is_has_component(&self) -> bool {
-        if self.prefab == "" {
-            return false;
-        }
-        if let None =
-            BackendDataStatic::get_backend_data().get_asset_id_by_asset_name(self.prefab.as_str())
-        {
+        if self.prefab.is_empty() {
             return false;
         }
-        true
+        BackendDataStatic::get_backend_data()
+            .get_asset_id_by_asset_name(self.prefab.as_str())
+            .is_some()
     }
     pub fn get_identity_by_prefab(&self) -> Option<NetworkIdentity> {
         // 如果 prefab 不为空
@@ -168,12 +146,23 @@ impl GameObject {
         None
     }
     pub fn is_null(&self) -> bool {
-        self.scene_name == "" && self.prefab == ""
+        self.scene_name.is_empty() && self.prefab.is_empty()
     }
     pub fn set_active(&mut self, value: bool) {
         self.active = value;
     }
 }
+
+impl Default for GameObject {
+    fn default() -> Self {
+        Self {
+            scene_name: "".to_string(),
+            prefab: "".to_string(),
+            transform: Transform::default(),
+            active: false,
+        }
+    }
+}
 // GameObject 的 PartialEq 实现
 impl PartialEq for GameObject {
     fn eq(&self, other: &Self) -> bool {
@@ -203,6 +192,16 @@ pub enum SyncMode {
     Owners,
 }
 
+impl SyncMode {
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            0 => SyncMode::Observers,
+            1 => SyncMode::Owners,
+            _ => SyncMode::Observers,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct NetworkBehaviour {
     pub sync_interval: f64,
@@ -233,10 +232,10 @@ impl NetworkBehaviour {
         sub_class: String,
     ) -> Self {
         NetworkBehaviour {
-            sync_interval: 0.0,
+            sync_interval: network_behaviour_setting.sync_interval,
             last_sync_time: 0.0,
             sync_direction: SyncDirection::from_u8(network_behaviour_setting.sync_direction),
-            sync_mode: SyncMode::Observers,
+            sync_mode: SyncMode::from_u8(network_behaviour_setting.sync_mode),
             index: component_index,
             sub_class,
             sync_var_dirty_bits: u64::MAX,
@@ -250,8 +249,21 @@ impl NetworkBehaviour {
             run_start: true,
         }
     }
+    // syncObjectDirtyBits 加上有未发送修改的 SyncObject
+    pub fn sync_object_dirty_bits(&self) -> u64 {
+        self.sync_objects
+            .iter()
+            .take(64)
+            .enumerate()
+            .filter(|(_, sync_object)| sync_object.is_dirty())
+            .fold(self.sync_object_dirty_bits, |bits, (i, _)| bits | (1 << i))
+    }
     pub fn is_dirty(&self) -> bool {
-        self.sync_var_dirty_bits | self.sync_object_dirty_bits != 0u64
+        self.is_dirty_with_sync_var_mask(u64::MAX)
+    }
+    // 只考虑 sync_var_mask 中的 SyncVar，用于排除拥有者专属的 SyncVar
+    pub fn is_dirty_with_sync_var_mask(&self, sync_var_mask: u64) -> bool {
+        (self.sync_var_dirty_bits & sync_var_mask) | self.sync_object_dirty_bits() != 0u64
             && NetworkTime::local_time() - self.last_sync_time > self.sync_interval
     }
     pub fn late_invoke(net_id: u32, game_object: GameObject) {
@@ -267,6 +279,74 @@ impl NetworkBehaviour {
             }
         }
     }
+    // 序列化 RpcMessage，超过通道最大消息长度时不发送
+    pub fn serialize_rpc_message<F>(
+        net_id: u32,
+        component_index: u8,
+        function_full_name: &str,
+        function_hash_code: i32,
+        writer: &NetworkWriter,
+        channel: TransportChannel,
+        func: F,
+    ) where
+        F: FnOnce(&[u8]),
+    {
+        let mut rpc = RpcMessage::new(
+            net_id,
+            component_index,
+            function_hash_code as u16,
+            writer.to_bytes(),
+        );
+        NetworkWriterPool::get_return(|message_writer| {
+            rpc.serialize(message_writer);
+            if message_writer.get_position() > NetworkMessages::max_message_size(channel) {
+                log_error!(format!(
+                    "RPC Function {} message too large to send: {}",
+                    function_full_name,
+                    message_writer.get_position()
+                ));
+                return;
+            }
+            func(message_writer.to_array_segment());
+        });
+    }
+    // 找到组件并调用，#[command] 生成的委托使用
+    pub fn invoke_component<T, F>(net_id: u32, component_index: u8, func: F)
+    where
+        T: NetworkBehaviourTrait,
+        F: FnOnce(&mut T),
+    {
+        match NETWORK_BEHAVIOURS.try_get_mut(&format!("{}_{}", net_id, component_index)) {
+            TryResult::Present(mut component) => {
+                match component.as_any_mut().downcast_mut::<T>() {
+                    Some(component) => func(component),
+                    None => {
+                        log_error!(
+                            "NetworkBehaviour type mismatch by net_id: {}, component_index: {}",
+                            net_id,
+                            component_index
+                        );
+                        return;
+                    }
+                }
+                Self::late_invoke(net_id, component.game_object().clone());
+            }
+            TryResult::Absent => {
+                log_error!(
+                    "NetworkBehaviour not found by net_id: {}, component_index: {}",
+                    net_id,
+                    component_index
+                );
+            }
+            TryResult::Locked => {
+                log_error!(
+                    "NetworkBehaviour locked by net_id: {}, component_index: {}",
+                    net_id,
+                    component_index
+                );
+            }
+        }
+    }
     pub fn error_correction(size: usize, safety: u8) -> usize {
         let cleared = size & 0xFFFFFF00;
         cleared | safety as usize
@@ -335,7 +415,25 @@ pub trait NetworkBehaviourTrait: Any + Send + Sync + Debug {
     fn set_sync_objects(&mut self, value: Vec<Box<dyn SyncObject>>);
     fn add_sync_object(&mut self, value: Box<dyn SyncObject>);
     fn has_sync_objects(&mut self) -> bool {
-        self.sync_objects().len() > 0
+        !self.sync_objects().is_empty()
+    }
+    // 获取第 index 个 SyncObject 用于修改，对应 Mirror 的 OnDirty
+    fn sync_object_mut<T: SyncObject>(&mut self, index: usize) -> Option<&mut T>
+    where
+        Self: Sized,
+    {
+        if index >= self.sync_objects().len() || index >= 64 {
+            return None;
+        }
+        // 类型不匹配时不修改脏位和记录状态
+        if !self.sync_objects()[index].as_any_mut().is::<T>() {
+            return None;
+        }
+        // 没有观察者时不记录修改，脏位由 SyncObject 的修改决定
+        let recording = !self.observers().is_empty();
+        let sync_object = &mut self.sync_objects()[index];
+        sync_object.set_recording(recording);
+        sync_object.as_any_mut().downcast_mut::<T>()
     }
     fn sync_var_hook_guard(&self) -> u64;
     fn get_sync_var_hook_guard(&self, dirty_bit: u64) -> bool {
@@ -360,6 +458,14 @@ pub trait NetworkBehaviourTrait: Any + Send + Sync + Debug {
     }
     // 字段 get  set end
     fn is_dirty(&self) -> bool;
+    // 是否有只发送给拥有者的 SyncVar
+    fn has_owner_only_sync_vars(&self) -> bool {
+        false
+    }
+    // 排除拥有者专属 SyncVar 后是否需要同步给观察者
+    fn is_dirty_for_observers(&self) -> bool {
+        self.is_dirty()
+    }
     // DeserializeObjectsAll
     // Serialize
     fn serialize(&mut self, writer: &mut NetworkWriter, initial_state: bool) {
@@ -369,8 +475,22 @@ pub trait NetworkBehaviourTrait: Any + Send + Sync + Debug {
         self.on_serialize(writer, initial_state);
         let end_position = writer.get_position();
         writer.set_position(header_position);
-        let size = (end_position - content_position) as u8;
-        let safety = size & 0xFF;
+        // 只写入大小的低 8 位用于校验
+        let safety = (end_position - content_position) as u8;
+        writer.write_byte(safety);
+        writer.set_position(end_position);
+    }
+    // 观察者的序列化，不包含拥有者专属的 SyncVar
+    fn serialize_for_observers(&mut self, writer: &mut NetworkWriter, initial_state: bool) {
+        let header_position = writer.get_position();
+        writer.write_byte(0);
+        let content_position = writer.get_position();
+        self.serialize_sync_objects(writer, initial_state);
+        self.serialize_sync_vars_for_observers(writer, initial_state);
+        let end_position = writer.get_position();
+        writer.set_position(header_position);
+        // 只写入大小的低 8 位用于校验
+        let safety = (end_position - content_position) as u8;
         writer.write_byte(safety);
         writer.set_position(end_position);
     }
@@ -393,9 +513,10 @@ pub trait NetworkBehaviourTrait: Any + Send + Sync + Debug {
         }
     }
     fn serialize_sync_object_delta(&mut self, writer: &mut NetworkWriter) {
-        writer.write_ulong(self.sync_object_dirty_bits());
-        for i in 0..self.sync_objects().len() {
-            if self.sync_object_dirty_bits() & (1 << i) != 0 {
+        let dirty = self.sync_object_dirty_bits();
+        dirty.write_field(writer);
+        for i in 0..self.sync_objects().len().min(64) {
+            if dirty & (1 << i) != 0 {
                 let sync_object = &mut self.sync_objects()[i];
                 sync_object.on_serialize_delta(writer);
             }
@@ -416,7 +537,7 @@ pub trait NetworkBehaviourTrait: Any + Send + Sync + Debug {
         result = self.on_deserialize(reader, initial_state);
 
         let size = reader.get_position() - chunk_start;
-        let size_hash = size as u8 & 0xFF;
+        let size_hash = size as u8;
         if size_hash != safety {
             log_warn!(format!(
                 "Deserialize failed. Size mismatch. Expected: {}, Received: {}",
@@ -454,8 +575,17 @@ pub trait NetworkBehaviourTrait: Any + Send + Sync + Debug {
     // DeserializeSyncObjectDelta
     fn deserialize_sync_object_delta(&mut self, reader: &mut NetworkReader) -> bool {
         let mut result = true;
-        let dirty = reader.read_ulong();
-        for i in 0..self.sync_objects().len() {
+        let dirty = match u64::read_field(reader) {
+            Ok(dirty) => dirty,
+            Err(err) => {
+                log_error!(format!(
+                    "Failed to deserialize sync object dirty bits: {}",
+                    err
+                ));
+                return false;
+            }
+        };
+        for i in 0..self.sync_objects().len().min(64) {
             if dirty & (1 << i) != 0 {
                 let sync_object = &mut self.sync_objects()[i];
                 let succ = sync_object.on_deserialize_delta(reader);
@@ -494,29 +624,87 @@ pub trait NetworkBehaviourTrait: Any + Send + Sync + Debug {
             ));
             return;
         }
-        let mut rpc = RpcMessage::new(
+        // 只序列化一次，所有观察者共用
+        NetworkBehaviour::serialize_rpc_message(
             self.net_id(),
             self.index(),
-            function_hash_code as u16,
-            writer.to_bytes(),
-        );
-        self.observers().iter().for_each(
-            |observer| match NetworkServerStatic::network_connections().try_get_mut(observer) {
-                TryResult::Present(mut conn_to_client) => {
-                    let is_owner = conn_to_client.connection_id() == self.connection_to_client();
-                    if (!is_owner || include_owner) && conn_to_client.is_ready() {
-                        conn_to_client.send_network_message(&mut rpc, channel);
+            function_full_name,
+            function_hash_code,
+            writer,
+            channel,
+            |message| {
+                self.observers().iter().for_each(|observer| {
+                    match NetworkServerStatic::network_connections().try_get_mut(observer) {
+                        TryResult::Present(mut conn_to_client) => {
+                            let is_owner =
+                                conn_to_client.connection_id() == self.connection_to_client();
+                            if (!is_owner || include_owner) && conn_to_client.is_ready() {
+                                conn_to_client.buffer_rpc(message, channel);
+                            }
+                        }
+                        TryResult::Absent => {
+                            log_error!(format!(
+                                "Failed because connection {} is absent.",
+                                observer
+                            ));
+                        }
+                        TryResult::Locked => {
+                            log_error!(format!(
+                                "Failed because connection {} is locked.",
+                                observer
+                            ));
+                        }
                     }
-                }
-                TryResult::Absent => {
-                    log_error!(format!("Failed because connection {} is absent.", observer));
-                }
-                TryResult::Locked => {
-                    log_error!(format!("Failed because connection {} is locked.", observer));
-                }
+                });
             },
         );
     }
+    // SendTargetRPCInternal，conn_id 为 None 时发送给拥有者
+    fn send_target_rpc_internal(
+        &self,
+        conn_id: Option<u64>,
+        function_full_name: &str,
+        function_hash_code: i32,
+        writer: &NetworkWriter,
+        channel: TransportChannel,
+    ) {
+        if !NetworkServerStatic::active() {
+            log_error!(format!(
+                "TargetRPC Function {} called without an active server.",
+                function_full_name
+            ));
+            return;
+        }
+        let conn_id = conn_id.unwrap_or(self.connection_to_client());
+        if conn_id == 0 {
+            log_error!(format!(
+                "TargetRPC {} was called on {} when it was not owned by a connection.",
+                function_full_name,
+                self.sub_class()
+            ));
+            return;
+        }
+        match NetworkServerStatic::network_connections().try_get_mut(&conn_id) {
+            TryResult::Present(mut conn_to_client) => {
+                // 与 ClientRpc 共用缓存，保证发送顺序
+                NetworkBehaviour::serialize_rpc_message(
+                    self.net_id(),
+                    self.index(),
+                    function_full_name,
+                    function_hash_code,
+                    writer,
+                    channel,
+                    |message| conn_to_client.buffer_rpc(message, channel),
+                );
+            }
+            TryResult::Absent => {
+                log_error!(format!("Failed because connection {} is absent.", conn_id));
+            }
+            TryResult::Locked => {
+                log_error!(format!("Failed because connection {} is locked.", conn_id));
+            }
+        }
+    }
     fn send_entity_internal(
         &self,
         writer: &NetworkWriter,
@@ -547,13 +735,78 @@ pub trait NetworkBehaviourTrait: Any + Send + Sync + Debug {
     }
     fn on_start_server(&mut self) {}
     fn on_stop_server(&mut self) {}
+    // 对象的拥有者变化时调用
+    fn on_start_authority(&mut self) {}
+    fn on_stop_authority(&mut self) {}
     fn start(&mut self) {}
     fn update(&mut self) {
         self.start()
     }
     fn late_update(&mut self) {}
+    // 拥有者的客户端输入，每个 tick 最多调用一次
+    fn on_input(&mut self, _sequence: u32, _reader: &mut NetworkReader) {}
+    // tobackend.json 热重载后调用，用于应用可以在线修改的设置
+    fn on_backend_data_reload(&mut self, _network_behaviour_component: &NetworkBehaviourComponent) {}
     // SerializeSyncVars
     fn serialize_sync_vars(&mut self, writer: &mut NetworkWriter, initial_state: bool);
+    fn serialize_sync_vars_for_observers(
+        &mut self,
+        writer: &mut NetworkWriter,
+        initial_state: bool,
+    ) {
+        self.serialize_sync_vars(writer, initial_state);
+    }
     // DeserializeSyncVars
     fn deserialize_sync_vars(&mut self, reader: &mut NetworkReader, initial_state: bool) -> bool;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mirror::components::network_room_player::NetworkRoomPlayer;
+    use crate::mirror::core::sync_hash_set::SyncHashSet;
+    use crate::mirror::core::sync_list::SyncList;
+
+    #[test]
+    fn test_sync_object_mut_type_mismatch() {
+        let mut behaviour = NetworkRoomPlayer {
+            network_behaviour: NetworkBehaviour::new(
+                GameObject::default(),
+                NetworkBehaviourSetting::default(),
+                0,
+                "".to_string(),
+            ),
+            ready_to_begin: false,
+            index: 0,
+        };
+        behaviour.add_sync_object(Box::new(SyncList::<i32>::new()));
+        behaviour.clear_all_dirty_bits();
+        behaviour.add_observer(1);
+
+        // 类型不匹配时不设置脏位
+        assert!(behaviour.sync_object_mut::<SyncHashSet<i32>>(0).is_none());
+        assert_eq!(behaviour.sync_object_dirty_bits(), 0);
+        assert!(behaviour.sync_object_mut::<SyncList<i32>>(1).is_none());
+        assert_eq!(behaviour.sync_object_dirty_bits(), 0);
+
+        // 只访问不修改时不设置脏位
+        assert!(behaviour.sync_object_mut::<SyncList<i32>>(0).is_some());
+        assert_eq!(behaviour.sync_object_dirty_bits(), 0);
+
+        behaviour
+            .sync_object_mut::<SyncList<i32>>(0)
+            .unwrap()
+            .add(1);
+        assert_eq!(behaviour.sync_object_dirty_bits(), 1);
+
+        // 脏位用变长整数写入
+        let mut writer = NetworkWriter::new();
+        behaviour.serialize_sync_objects(&mut writer, false);
+        assert_eq!(writer.to_array_segment(), vec![1, 1, 0, 2]);
+        behaviour.clear_all_dirty_bits();
+        assert_eq!(behaviour.sync_object_dirty_bits(), 0);
+
+        let mut reader = NetworkReader::new_with_array_segment(&[]);
+        assert!(!behaviour.deserialize_sync_objects(&mut reader, false));
+    }
+}
@@ -1,19 +1,24 @@
 use crate::log_error;
+use crate::mirror::core::input_buffer::InputBuffer;
 use crate::mirror::core::messages::NetworkMessageTrait;
 use crate::mirror::core::network_connection::{NetworkConnection, NetworkConnectionTrait};
 use crate::mirror::core::network_identity::NetworkIdentity;
 use crate::mirror::core::network_manager::NetworkManagerStatic;
+use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTryTrait};
 use crate::mirror::core::network_server::{
     NetworkServer, NetworkServerStatic, RemovePlayerOptions,
 };
 use crate::mirror::core::network_time::{ExponentialMovingAverage, NetworkTime};
-use crate::mirror::core::network_writer::NetworkWriter;
+use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
+use crate::mirror::core::rate_limiter::RateLimiter;
+use crate::mirror::core::server_events::{ServerEvent, ServerEvents};
 use crate::mirror::core::snapshot_interpolation::snapshot_interpolation::SnapshotInterpolation;
 use crate::mirror::core::snapshot_interpolation::time_snapshot::TimeSnapshot;
+use crate::mirror::core::state_baseline::StateBaseline;
 use crate::mirror::core::transport::{Transport, TransportChannel};
 use dashmap::try_result::TryResult;
 use ordered_float::OrderedFloat;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::RwLock;
 
 pub struct NetworkConnectionToClient {
@@ -31,6 +36,22 @@ pub struct NetworkConnectionToClient {
     pub snapshots: BTreeMap<OrderedFloat<f64>, TimeSnapshot>,
     pub snapshot_buffer_size_limit: i32,
     pub _rtt: ExponentialMovingAverage,
+    // 每个 identity 的发送优先级累加值
+    pub priority_accumulators: HashMap<u32, f32>,
+    // 因带宽不足跳过了增量，需要发送完整状态的 identity
+    pub stale_observing: HashSet<u32>,
+    // 当前可用的带宽（字节）
+    pub bandwidth_credit: f64,
+    // 不可靠状态同步时每个 identity 的已确认基线
+    pub state_baselines: HashMap<u32, StateBaseline>,
+    // 每个 (net_id, component_index) 的客户端输入缓冲
+    pub input_buffers: HashMap<(u32, u8), InputBuffer>,
+    // 已加载的叠加场景
+    pub loaded_scenes: HashSet<String>,
+    // 消息和 Command 限流
+    pub rate_limiter: RateLimiter,
+    // 已调用 disconnect，等待传输层回调移除连接，剩余的消息直接丢弃
+    pub disconnected: bool,
 }
 impl Default for NetworkConnectionToClient {
     fn default() -> Self {
@@ -50,6 +71,14 @@ impl Default for NetworkConnectionToClient {
             snapshots: Default::default(),
             snapshot_buffer_size_limit: 64,
             _rtt: ExponentialMovingAverage::new(NetworkTime::PING_WINDOW_SIZE),
+            priority_accumulators: Default::default(),
+            stale_observing: Default::default(),
+            bandwidth_credit: 0.0,
+            state_baselines: Default::default(),
+            input_buffers: Default::default(),
+            loaded_scenes: Default::default(),
+            rate_limiter: Default::default(),
+            disconnected: false,
         }
     }
 }
@@ -71,6 +100,14 @@ impl NetworkConnectionTrait for NetworkConnectionToClient {
             snapshots: Default::default(),
             snapshot_buffer_size_limit: 64,
             _rtt: ExponentialMovingAverage::new(NetworkTime::PING_WINDOW_SIZE),
+            priority_accumulators: Default::default(),
+            stale_observing: Default::default(),
+            bandwidth_credit: 0.0,
+            state_baselines: Default::default(),
+            input_buffers: Default::default(),
+            loaded_scenes: Default::default(),
+            rate_limiter: Default::default(),
+            disconnected: false,
         };
         network_connection_to_client.buffer_time = NetworkServerStatic::send_interval() as f64
             * network_connection_to_client.buffer_time_multiplier;
@@ -126,6 +163,11 @@ impl NetworkConnectionTrait for NetworkConnectionToClient {
 
     fn set_ready(&mut self, ready: bool) {
         self.network_connection.set_ready(ready);
+        // 未准备就绪的连接不再接收缓存的 RPC
+        if !ready {
+            self.reliable_rpcs_batch.reset();
+            self.unreliable_rpcs_batch.reset();
+        }
     }
 
     fn is_authenticated(&self) -> bool {
@@ -133,7 +175,11 @@ impl NetworkConnectionTrait for NetworkConnectionToClient {
     }
 
     fn set_authenticated(&mut self, authenticated: bool) {
+        let changed = authenticated && !self.network_connection.is_authenticated();
         self.network_connection.set_authenticated(authenticated);
+        if changed {
+            ServerEvents::invoke(&mut ServerEvent::Authenticated(self));
+        }
     }
 
     fn set_authenticated_data(&mut self, data: Box<RwLock<dyn NetworkMessageTrait>>) {
@@ -161,9 +207,15 @@ impl NetworkConnectionTrait for NetworkConnectionToClient {
     }
 
     fn disconnect(&mut self) {
-        self.reliable_rpcs_batch.reset();
-        self.unreliable_rpcs_batch.reset();
+        self.disconnected = true;
+        // 先发送已缓存的 RPC，再关闭连接
+        self.flush_rpcs();
+        self.network_connection.update();
         self.network_connection.disconnect();
+        // 关闭传输层连接，OnServerDisconnected 回调中移除连接
+        if let Some(transport) = Transport::active_transport() {
+            transport.server_disconnect(self.connection_id());
+        }
     }
 
     fn cleanup(&mut self) {
@@ -172,6 +224,47 @@ impl NetworkConnectionTrait for NetworkConnectionToClient {
 }
 
 impl NetworkConnectionToClient {
+    // 缓存已序列化的 RpcMessage，在 broadcast_to_connection 中和实体状态一起发送
+    pub fn buffer_rpc(&mut self, message: &[u8], channel: TransportChannel) {
+        let batch = match channel {
+            TransportChannel::Reliable => &mut self.reliable_rpcs_batch,
+            TransportChannel::Unreliable => &mut self.unreliable_rpcs_batch,
+        };
+        batch.compress_var_uint(message.len() as u32);
+        batch.write_array_segment_all(message);
+    }
+
+    // 按缓存顺序发送本 tick 的 RPC
+    pub fn flush_rpcs(&mut self) {
+        for channel in [TransportChannel::Reliable, TransportChannel::Unreliable] {
+            let batch = match channel {
+                TransportChannel::Reliable => &mut self.reliable_rpcs_batch,
+                TransportChannel::Unreliable => &mut self.unreliable_rpcs_batch,
+            };
+            if batch.get_position() == 0 {
+                continue;
+            }
+            let mut reader = NetworkReader::new_with_array_segment(batch.to_array_segment());
+            batch.reset();
+            while reader.remaining() > 0 {
+                let size = match reader.try_decompress_var_uint() {
+                    Ok(size) => size as usize,
+                    Err(err) => {
+                        log_error!(format!("Failed to read buffered rpc size: {}", err));
+                        break;
+                    }
+                };
+                match reader.try_read_array_segment(size) {
+                    Ok(message) => self.network_connection.send(message, channel),
+                    Err(err) => {
+                        log_error!(format!("Failed to read buffered rpc: {}", err));
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
     pub fn on_time_snapshot(&mut self, snapshot: TimeSnapshot) {
         if self.snapshots.len() >= self.snapshot_buffer_size_limit as usize {
             return;
@@ -206,7 +299,7 @@ impl NetworkConnectionToClient {
         );
     }
     pub fn update_time_interpolation(&mut self) {
-        if self.snapshots.len() > 0 {
+        if !self.snapshots.is_empty() {
             SnapshotInterpolation::step_time(
                 NetworkTime::get_ping_interval(),
                 &mut self.remote_timeline,
@@ -248,10 +341,17 @@ impl NetworkConnectionToClient {
             }
         }
         self.observing.clear();
+        self.priority_accumulators.clear();
+        self.stale_observing.clear();
+        self.state_baselines.clear();
+        self.input_buffers.clear();
     }
 
     pub fn add_owned_object(&mut self, net_id: u32) {
-        self.owned().push(net_id);
+        // Mirror 中 owned 是 HashSet，重复设置拥有者时不重复添加
+        if !self.owned().contains(&net_id) {
+            self.owned().push(net_id);
+        }
     }
     pub fn remove_owned_object(&mut self, net_id: u32) {
         self.owned().retain(|id| *id != net_id);
@@ -307,6 +407,11 @@ impl NetworkConnectionToClient {
     // RemoveFromObserving
     pub fn remove_from_observing(&mut self, identity: &mut NetworkIdentity, is_destroyed: bool) {
         self.observing.retain(|net_id| *net_id != identity.net_id());
+        self.priority_accumulators.remove(&identity.net_id());
+        self.stale_observing.remove(&identity.net_id());
+        self.state_baselines.remove(&identity.net_id());
+        self.input_buffers
+            .retain(|(net_id, _), _| *net_id != identity.net_id());
         if !is_destroyed {
             NetworkServer::hide_for_connection(self, identity);
         }
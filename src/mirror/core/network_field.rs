@@ -0,0 +1,190 @@
+use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTryTrait, ReadError};
+use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
+use nalgebra::{Quaternion, Vector2, Vector3, Vector4};
+
+// 单个字段的读写，#[derive(NetworkMessage)] 生成的代码按字段类型调用
+// 与 Mirror weaver 生成的 NetworkWriterExtensions / NetworkReaderExtensions 调用保持一致
+pub trait NetworkField: Sized {
+    fn write_field(&self, writer: &mut NetworkWriter);
+    fn read_field(reader: &mut NetworkReader) -> Result<Self, ReadError>;
+}
+
+// 不压缩的整数字段，对应 #[network_message(fixed)]
+pub trait NetworkFixedField: Sized {
+    fn write_fixed_field(&self, writer: &mut NetworkWriter);
+    fn read_fixed_field(reader: &mut NetworkReader) -> Result<Self, ReadError>;
+}
+
+macro_rules! impl_network_field {
+    ($ty:ty, $write:ident, $read:ident) => {
+        impl NetworkField for $ty {
+            fn write_field(&self, writer: &mut NetworkWriter) {
+                writer.$write(*self);
+            }
+            fn read_field(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+                reader.$read()
+            }
+        }
+    };
+}
+
+macro_rules! impl_network_fixed_field {
+    ($ty:ty, $write:ident, $read:ident) => {
+        impl NetworkFixedField for $ty {
+            fn write_fixed_field(&self, writer: &mut NetworkWriter) {
+                writer.$write(*self);
+            }
+            fn read_fixed_field(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+                reader.$read()
+            }
+        }
+    };
+}
+
+impl_network_field!(u8, write_byte, try_read_byte);
+impl_network_field!(i8, write_sbyte, try_read_sbyte);
+impl_network_field!(bool, write_bool, try_read_bool);
+impl_network_field!(i16, write_short, try_read_short);
+impl_network_field!(u16, write_ushort, try_read_ushort);
+// int / uint / long / ulong 与 Mirror weaver 一致默认压缩
+impl_network_field!(i32, compress_var_int, try_decompress_var_int);
+impl_network_field!(u32, compress_var_uint, try_decompress_var_uint);
+impl_network_field!(i64, compress_var_long, try_decompress_var_long);
+impl_network_field!(u64, compress_var_ulong, try_decompress_var_ulong);
+impl_network_field!(f32, write_float, try_read_float);
+impl_network_field!(f64, write_double, try_read_double);
+impl_network_field!(Vector2<f32>, write_vector2, try_read_vector2);
+impl_network_field!(Vector3<f32>, write_vector3, try_read_vector3);
+impl_network_field!(Vector4<f32>, write_vector4, try_read_vector4);
+impl_network_field!(Quaternion<f32>, write_quaternion, try_read_quaternion);
+
+impl_network_fixed_field!(i32, write_int, try_read_int);
+impl_network_fixed_field!(u32, write_uint, try_read_uint);
+impl_network_fixed_field!(i64, write_long, try_read_long);
+impl_network_fixed_field!(u64, write_ulong, try_read_ulong);
+
+impl NetworkField for String {
+    fn write_field(&self, writer: &mut NetworkWriter) {
+        writer.write_str(self);
+    }
+    fn read_field(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        reader.try_read_string()
+    }
+}
+
+// byte[]
+impl NetworkField for Vec<u8> {
+    fn write_field(&self, writer: &mut NetworkWriter) {
+        writer.write_array_segment_and_size(self.as_slice());
+    }
+    fn read_field(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        reader.try_read_bytes_and_size()
+    }
+}
+
+// Nullable<T>，先写是否有值
+impl<T: NetworkField> NetworkField for Option<T> {
+    fn write_field(&self, writer: &mut NetworkWriter) {
+        match self {
+            Some(value) => {
+                writer.write_bool(true);
+                value.write_field(writer);
+            }
+            None => writer.write_bool(false),
+        }
+    }
+    fn read_field(reader: &mut NetworkReader) -> Result<Self, ReadError> {
+        match reader.try_read_bool()? {
+            true => Ok(Some(T::read_field(reader)?)),
+            false => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mirror::core::messages::{CommandMessage, NetworkMessageTrait, StateAckMessage};
+    use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTryTrait};
+    use crate::mirror::core::network_writer::NetworkWriter;
+    use crate::NetworkMessage;
+
+    #[derive(NetworkMessage, Debug, PartialEq)]
+    #[network_message(name = "Mirror.CommandMessage")]
+    struct DerivedCommandMessage {
+        net_id: u32,
+        component_index: u8,
+        function_hash: u16,
+        payload: Vec<u8>,
+        #[network_message(skip)]
+        local: bool,
+    }
+
+    #[derive(NetworkMessage, Debug, PartialEq)]
+    #[network_message(name = "Mirror.StateAckMessage")]
+    struct DerivedStateAckMessage {
+        net_id: u32,
+        #[network_message(fixed)]
+        tick: u32,
+    }
+
+    #[test]
+    fn test_derive_network_message_matches_hand_written() {
+        let mut hand_written = CommandMessage::new(300, 2, 12345, vec![1, 2, 3]);
+        let mut derived = DerivedCommandMessage {
+            net_id: 300,
+            component_index: 2,
+            function_hash: 12345,
+            payload: vec![1, 2, 3],
+            local: true,
+        };
+        assert_eq!(
+            DerivedCommandMessage::get_hash_code(),
+            CommandMessage::get_hash_code()
+        );
+
+        let mut hand_written_writer = NetworkWriter::new();
+        hand_written.serialize(&mut hand_written_writer);
+        let mut derived_writer = NetworkWriter::new();
+        derived.serialize(&mut derived_writer);
+        assert_eq!(
+            hand_written_writer.to_array_segment(),
+            derived_writer.to_array_segment()
+        );
+
+        let mut reader = NetworkReader::new_with_array_segment(derived_writer.to_array_segment());
+        assert_eq!(
+            reader.try_read_ushort().unwrap(),
+            DerivedCommandMessage::get_hash_code()
+        );
+        let message = DerivedCommandMessage::deserialize(&mut reader).unwrap();
+        assert_eq!(message.net_id, 300);
+        assert_eq!(message.payload, vec![1, 2, 3]);
+        assert!(!message.local);
+    }
+
+    #[test]
+    fn test_derive_network_message_fixed() {
+        let mut hand_written = StateAckMessage::new(300, 300);
+        let mut derived = DerivedStateAckMessage {
+            net_id: 300,
+            tick: 300,
+        };
+        let mut hand_written_writer = NetworkWriter::new();
+        hand_written.serialize(&mut hand_written_writer);
+        let mut derived_writer = NetworkWriter::new();
+        derived.serialize(&mut derived_writer);
+        // 消息 id 2 字节 + 压缩的 net_id 2 字节 + 不压缩的 tick 4 字节
+        assert_eq!(derived_writer.to_array_segment().len(), 8);
+        assert_eq!(
+            hand_written_writer.to_array_segment(),
+            derived_writer.to_array_segment()
+        );
+
+        let mut reader = NetworkReader::new_with_array_segment(derived_writer.to_array_segment());
+        reader.try_read_ushort().unwrap();
+        assert_eq!(
+            DerivedStateAckMessage::deserialize(&mut reader).unwrap(),
+            derived
+        );
+    }
+}
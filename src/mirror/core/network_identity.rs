@@ -10,6 +10,7 @@ use crate::mirror::core::network_server::{NetworkServer, NetworkServerStatic, NE
 use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
 use crate::mirror::core::network_writer_pool::NetworkWriterPool;
 use crate::mirror::core::remote_calls::{RemoteCallType, RemoteProcedureCalls};
+use crate::mirror::core::server_events::{ServerEvent, ServerEvents};
 use atomic::Atomic;
 use dashmap::mapref::one::RefMut;
 use dashmap::try_result::TryResult;
@@ -17,9 +18,12 @@ use dashmap::DashMap;
 use lazy_static::lazy_static;
 use std::default::Default;
 use std::sync::atomic::Ordering;
+use std::sync::Mutex;
 
 lazy_static! {
     static ref NEXT_NETWORK_ID: Atomic<u32> = Atomic::new(1);
+    // 等待通知的拥有者变化 (net_id, old_conn_id, new_conn_id)
+    static ref PENDING_OWNERSHIP_CHANGES: Mutex<Vec<(u32, u64, u64)>> = Mutex::new(Vec::new());
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -72,6 +76,7 @@ pub struct NetworkIdentity {
     pub destroy_called: bool,
     pub visibility: Visibility,
     pub last_serialization: NetworkIdentitySerialization,
+    pub last_full_serialization: NetworkIdentitySerialization,
     pub scene_ids: DashMap<u64, u32>,
     pub has_spawned: bool,
     pub spawned_from_instantiate: bool,
@@ -107,6 +112,7 @@ impl NetworkIdentity {
             destroy_called: false,
             visibility: Visibility::Default,
             last_serialization: NetworkIdentitySerialization::new(0),
+            last_full_serialization: NetworkIdentitySerialization::new(0),
             scene_ids: Default::default(),
             has_spawned: false,
             spawned_from_instantiate: false,
@@ -164,7 +170,25 @@ impl NetworkIdentity {
             self.network_behaviours_count,
         );
         // 设置 conn_id
+        let old_conn_id = self.conn_to_client;
         self.conn_to_client = conn_id;
+        self.is_owned = conn_id != 0;
+        // 已生成的对象才通知所有权变化，生成时的初始 owner 不算
+        // 生成时由 spawn_object 在 on_start_server 之后调用 notify_authority
+        let spawned = NetworkServerStatic::spawned_network_ids().contains(&self.net_id);
+        if spawned {
+            self.notify_authority();
+        }
+        // 调用方可能持有 identity 和连接的锁，事件在 invoke_ownership_changes 中通知
+        if old_conn_id != conn_id && spawned {
+            match PENDING_OWNERSHIP_CHANGES.lock() {
+                Ok(mut changes) => changes.push((self.net_id, old_conn_id, conn_id)),
+                Err(e) => log_error!(format!(
+                    "NetworkIdentity.set_connection_to_client() failed to queue ownership change: {}",
+                    e
+                )),
+            }
+        }
         // 如果 conn_to_client 不为0，设置 connection_to_client 的 net_id
         if self.conn_to_client == 0 {
             return;
@@ -182,6 +206,26 @@ impl NetworkIdentity {
             }
         }
     }
+    // 通知拥有者变化，调用时不能持有 identity 和连接的锁
+    pub fn invoke_ownership_changes() {
+        let changes = match PENDING_OWNERSHIP_CHANGES.lock() {
+            Ok(mut changes) => std::mem::take(&mut *changes),
+            Err(e) => {
+                log_error!(format!(
+                    "NetworkIdentity.invoke_ownership_changes() failed: {}",
+                    e
+                ));
+                return;
+            }
+        };
+        for (net_id, old_conn_id, new_conn_id) in changes {
+            ServerEvents::invoke(&mut ServerEvent::OwnershipChanged {
+                net_id,
+                old_conn_id,
+                new_conn_id,
+            });
+        }
+    }
     pub fn game_object(&self) -> &GameObject {
         &self.game_object
     }
@@ -225,7 +269,7 @@ impl NetworkIdentity {
     pub fn reset_server_statics() {
         Self::set_static_next_network_id(1);
     }
-    pub fn get_scene_identity(&self, scene_id: u64) -> Option<RefMut<u64, u32>> {
+    pub fn get_scene_identity(&self, scene_id: u64) -> Option<RefMut<'_, u64, u32>> {
         if let Some(scene_identity) = self.scene_ids.get_mut(&scene_id) {
             return Some(scene_identity);
         }
@@ -347,10 +391,10 @@ impl NetworkIdentity {
                         owner_mask |= nth_bit;
                     }
 
-                    if *component.sync_mode() == SyncMode::Observers {
-                        if initial_state || dirty {
-                            observers_mask |= nth_bit;
-                        }
+                    if *component.sync_mode() == SyncMode::Observers
+                        && (initial_state || component.is_dirty_for_observers())
+                    {
+                        observers_mask |= nth_bit;
                     }
                 }
                 TryResult::Absent => {
@@ -389,7 +433,18 @@ impl NetworkIdentity {
                         let owner_dirty = Self::is_dirty(owner_mask, i);
                         let observers_dirty = Self::is_dirty(observers_mask, i);
 
-                        if owner_dirty || observers_dirty {
+                        if !owner_dirty && !observers_dirty {
+                            continue;
+                        }
+                        if component.has_owner_only_sync_vars() {
+                            // 拥有者和观察者分别序列化
+                            if owner_dirty {
+                                component.serialize(owner_writer, initial_state);
+                            }
+                            if observers_dirty {
+                                component.serialize_for_observers(observers_writer, initial_state);
+                            }
+                        } else {
                             NetworkWriterPool::get_return(|temp| {
                                 // Serialize the component
                                 component.serialize(temp, initial_state);
@@ -403,9 +458,9 @@ impl NetworkIdentity {
                                     observers_writer.write_array_segment_all(&segment);
                                 }
                             });
-                            if !initial_state {
-                                component.clear_all_dirty_bits();
-                            }
+                        }
+                        if !initial_state {
+                            component.clear_all_dirty_bits();
                         }
                     }
                     TryResult::Absent => {
@@ -466,6 +521,35 @@ impl NetworkIdentity {
         }
         &mut self.last_serialization
     }
+    // 完整状态的序列化，只在这个 tick 有变化或者还没有缓存时重新计算
+    pub fn get_server_full_serialization_at_tick(
+        &mut self,
+        tick: u32,
+    ) -> &mut NetworkIdentitySerialization {
+        let changed = {
+            let serialization = self.get_server_serialization_at_tick(tick);
+            serialization.owner_writer.get_position() > 0
+                || serialization.observers_writer.get_position() > 0
+        };
+        if self.last_full_serialization.tick != tick
+            && (changed || self.last_full_serialization.owner_writer.get_position() == 0)
+        {
+            self.last_full_serialization.reset_writers();
+            NetworkWriterPool::get_return(|owner_writer| {
+                NetworkWriterPool::get_return(|observers_writer| {
+                    self.serialize_server(true, owner_writer, observers_writer);
+                    self.last_full_serialization
+                        .owner_writer
+                        .write_array_segment_all(owner_writer.to_array_segment());
+                    self.last_full_serialization
+                        .observers_writer
+                        .write_array_segment_all(observers_writer.to_array_segment());
+                });
+            });
+            self.last_full_serialization.tick = tick;
+        }
+        &mut self.last_full_serialization
+    }
     pub fn clear_observers(&mut self) {
         for conn_id in self.observers.to_vec().iter() {
             match NetworkServerStatic::network_connections().try_get_mut(conn_id) {
@@ -498,8 +582,43 @@ impl NetworkIdentity {
     }
 
     pub fn notify_authority(&mut self) {
-        if !self.had_authority && self.is_owned {}
-        if self.had_authority && !self.is_owned {}
+        if !self.had_authority && self.is_owned {
+            self.on_start_authority();
+        }
+        if self.had_authority && !self.is_owned {
+            self.on_stop_authority();
+        }
+        self.had_authority = self.is_owned;
+    }
+    fn on_start_authority(&mut self) {
+        for i in 0..self.network_behaviours_count {
+            match NETWORK_BEHAVIOURS.try_get_mut(&format!("{}_{}", self.net_id, i)) {
+                TryResult::Present(mut component) => {
+                    component.on_start_authority();
+                }
+                TryResult::Absent => {
+                    log_error!("Failed to start authority because component is absent.");
+                }
+                TryResult::Locked => {
+                    log_error!("Failed to start authority because component is locked.");
+                }
+            }
+        }
+    }
+    fn on_stop_authority(&mut self) {
+        for i in 0..self.network_behaviours_count {
+            match NETWORK_BEHAVIOURS.try_get_mut(&format!("{}_{}", self.net_id, i)) {
+                TryResult::Present(mut component) => {
+                    component.on_stop_authority();
+                }
+                TryResult::Absent => {
+                    log_error!("Failed to stop authority because component is absent.");
+                }
+                TryResult::Locked => {
+                    log_error!("Failed to stop authority because component is locked.");
+                }
+            }
+        }
     }
 
     // AddObserver(NetworkConnectionToClient conn)
@@ -510,7 +629,7 @@ impl NetworkIdentity {
         }
 
         // 如果没有观察者
-        if self.observers.len() == 0 {
+        if self.observers.is_empty() {
             self.clear_all_components_dirty_bits()
         }
 
@@ -646,14 +765,47 @@ impl NetworkIdentity {
     //     None
     // }
 
+    // 把权限交给连接，已有拥有者时需要先 remove_client_authority
+    pub fn assign_client_authority(&mut self, conn_id: u64) -> bool {
+        if conn_id == 0 {
+            log_error!("AssignClientAuthority: connection is null.");
+            return false;
+        }
+        if self.conn_to_client == conn_id {
+            return true;
+        }
+        if self.conn_to_client != 0 {
+            log_error!(format!(
+                "AssignClientAuthority for {} already has an owner. Use RemoveClientAuthority() first.",
+                self.net_id
+            ));
+            return false;
+        }
+        self.set_connection_to_client(conn_id);
+        match NetworkServerStatic::network_connections().try_get_mut(&conn_id) {
+            TryResult::Present(mut conn) => {
+                NetworkServer::send_change_owner_message(self, &mut conn);
+                true
+            }
+            TryResult::Absent => {
+                log_error!("Failed to assign client authority because connection is absent.");
+                false
+            }
+            TryResult::Locked => {
+                log_error!("Failed to assign client authority because connection is locked.");
+                false
+            }
+        }
+    }
+
     pub fn remove_client_authority(&mut self) {
         if self.conn_to_client == 0 {
             return;
         }
         match NetworkServerStatic::network_connections().try_get_mut(&self.conn_to_client) {
             TryResult::Present(mut conn) => {
-                // TODO clientAuthorityCallback?.Invoke(connectionToClient, this, false);
-                self.conn_to_client = 0;
+                conn.remove_owned_object(self.net_id);
+                self.set_connection_to_client(0);
                 NetworkServer::send_change_owner_message(self, &mut conn);
             }
             TryResult::Absent => {
@@ -665,3 +817,264 @@ impl NetworkIdentity {
         }
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::mirror::components::network_common_behaviour::NetworkCommonBehaviour;
+    use crate::mirror::core::backend_data::{NetworkBehaviourSetting, SyncVarData};
+    use crate::mirror::core::network_behaviour::NetworkBehaviour;
+    use crate::mirror::core::network_server::tests::{lock_server, TestTransport};
+    use crate::mirror::core::server_events::EventHandlerType;
+    use dashmap::DashMap;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn owned(conn_id: u64) -> Vec<u32> {
+        match NetworkServerStatic::network_connections().try_get_mut(&conn_id) {
+            TryResult::Present(mut conn) => conn.owned().clone(),
+            _ => panic!("connection {} not found", conn_id),
+        }
+    }
+
+    #[test]
+    fn test_assign_and_remove_client_authority() {
+        let _lock = lock_server();
+        TestTransport::install();
+        NetworkServerStatic::set_active(true);
+        for conn_id in [1, 2] {
+            NetworkServerStatic::network_connections()
+                .insert(conn_id, NetworkConnectionToClient::new(conn_id));
+        }
+
+        let mut identity = NetworkIdentity::new();
+        let net_id = NetworkIdentity::get_static_next_network_id();
+        identity.set_net_id(net_id);
+
+        // 还没有生成时不通知权限，由 spawn_object 在 on_start_server 之后通知
+        assert!(identity.assign_client_authority(1));
+        assert_eq!(identity.connection_to_client(), 1);
+        assert!(identity.is_owned);
+        assert!(!identity.had_authority);
+        assert_eq!(owned(1), vec![net_id]);
+        identity.notify_authority();
+        assert!(identity.had_authority);
+
+        // 已有拥有者时需要先移除
+        assert!(!identity.assign_client_authority(2));
+        assert!(identity.assign_client_authority(1));
+        assert_eq!(owned(1), vec![net_id]);
+
+        NetworkServerStatic::spawned_network_ids().insert(net_id);
+        identity.remove_client_authority();
+        assert_eq!(identity.connection_to_client(), 0);
+        assert!(!identity.had_authority);
+        assert!(owned(1).is_empty());
+
+        // 已生成的对象立即通知权限
+        assert!(identity.assign_client_authority(2));
+        assert!(identity.had_authority);
+        assert_eq!(owned(2), vec![net_id]);
+        identity.remove_client_authority();
+        assert!(owned(2).is_empty());
+        // 没有拥有者时什么都不做
+        identity.remove_client_authority();
+        assert_eq!(identity.connection_to_client(), 0);
+
+        NetworkServerStatic::spawned_network_ids().remove(&net_id);
+        NetworkServerStatic::network_connections().clear();
+        NetworkServerStatic::set_active(false);
+    }
+
+    #[test]
+    fn test_ownership_changed_invoked_without_locks() {
+        let _lock = lock_server();
+        TestTransport::install();
+        NetworkServerStatic::set_active(true);
+        NetworkServerStatic::network_connections().insert(1, NetworkConnectionToClient::new(1));
+        // 清掉其他测试留下的通知
+        NetworkIdentity::invoke_ownership_changes();
+
+        let mut identity = NetworkIdentity::new();
+        let net_id = NetworkIdentity::get_static_next_network_id();
+        identity.set_net_id(net_id);
+        NetworkServerStatic::spawned_network_identities().insert(net_id, identity);
+        NetworkServerStatic::spawned_network_ids().insert(net_id);
+
+        // 处理程序中可以访问 identity 和连接
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let handler_changes = changes.clone();
+        let subscription =
+            ServerEvents::subscribe(EventHandlerType::OnOwnershipChangedEvent, move |event| {
+                if let ServerEvent::OwnershipChanged {
+                    net_id,
+                    old_conn_id,
+                    new_conn_id,
+                } = event
+                {
+                    let identity_present = matches!(
+                        NetworkServerStatic::spawned_network_identities().try_get_mut(net_id),
+                        TryResult::Present(_)
+                    );
+                    let conn_present = matches!(
+                        NetworkServerStatic::network_connections().try_get_mut(&1),
+                        TryResult::Present(_)
+                    );
+                    handler_changes.lock().unwrap().push((
+                        *old_conn_id,
+                        *new_conn_id,
+                        identity_present && conn_present,
+                    ));
+                }
+            });
+
+        if let Some(mut identity) =
+            NetworkServerStatic::spawned_network_identities().get_mut(&net_id)
+        {
+            assert!(identity.assign_client_authority(1));
+            identity.remove_client_authority();
+            // 持有锁时只记录，不通知
+            assert!(changes.lock().unwrap().is_empty());
+        }
+        NetworkIdentity::invoke_ownership_changes();
+        assert_eq!(*changes.lock().unwrap(), vec![(0, 1, true), (1, 0, true)]);
+        NetworkIdentity::invoke_ownership_changes();
+        assert_eq!(changes.lock().unwrap().len(), 2);
+
+        subscription.unsubscribe();
+        NetworkServerStatic::remove_spawned_network_identity(&net_id);
+        NetworkServerStatic::network_connections().clear();
+        NetworkServerStatic::set_active(false);
+    }
+
+    // sync_vars 为 (值, 是否拥有者专属)，拥有者专属 SyncVar 的初始值为 0
+    pub(crate) fn common_behaviour(
+        index: u8,
+        sync_mode: u8,
+        sync_vars: &[(u8, bool)],
+    ) -> NetworkCommonBehaviour {
+        let setting = NetworkBehaviourSetting {
+            sync_mode,
+            ..NetworkBehaviourSetting::default()
+        };
+        let mut behaviour = NetworkCommonBehaviour {
+            network_behaviour: NetworkBehaviour::new(
+                GameObject::default(),
+                setting,
+                index,
+                "Test".to_string(),
+            ),
+            sync_vars: DashMap::new(),
+            sync_object_names: Vec::new(),
+            owner_only_initial_values: HashMap::new(),
+        };
+        for (i, (value, owner_only)) in sync_vars.iter().enumerate() {
+            behaviour.sync_vars.insert(
+                i as u8,
+                SyncVarData {
+                    full_name: format!("Test.var{}", i),
+                    sub_class: "Test".to_string(),
+                    name: format!("var{}", i),
+                    r#type: "System.Byte".to_string(),
+                    value: vec![*value],
+                    dirty_bit: 1 << i,
+                    owner_only: *owner_only,
+                },
+            );
+            if *owner_only {
+                behaviour.owner_only_initial_values.insert(i as u8, vec![0]);
+            }
+        }
+        behaviour.clear_all_dirty_bits();
+        behaviour
+    }
+
+    fn serialize(identity: &mut NetworkIdentity, initial_state: bool) -> (Vec<u8>, Vec<u8>) {
+        let mut owner_writer = NetworkWriter::new();
+        let mut observers_writer = NetworkWriter::new();
+        identity.serialize_server(initial_state, &mut owner_writer, &mut observers_writer);
+        (owner_writer.to_bytes(), observers_writer.to_bytes())
+    }
+
+    pub(crate) fn set_sync_var(net_id: u32, component_index: u8, index: u8, value: u8) {
+        let key = format!("{}_{}", net_id, component_index);
+        let mut component = NETWORK_BEHAVIOURS.get_mut(&key).unwrap();
+        let behaviour = component
+            .as_any_mut()
+            .downcast_mut::<NetworkCommonBehaviour>()
+            .unwrap();
+        behaviour.sync_vars.get_mut(&index).unwrap().value = vec![value];
+        behaviour.set_sync_var_dirty_bits(1 << index);
+    }
+
+    #[test]
+    fn test_serialize_server_sync_mode_owners() {
+        let _lock = lock_server();
+        let mut identity = NetworkIdentity::new();
+        let net_id = NetworkIdentity::get_static_next_network_id();
+        identity.set_net_id(net_id);
+        identity.network_behaviours_count = 2;
+        // 组件 0 只同步给拥有者
+        NETWORK_BEHAVIOURS.insert(
+            format!("{}_0", net_id),
+            Box::new(common_behaviour(0, 1, &[(1, false)])),
+        );
+        NETWORK_BEHAVIOURS.insert(
+            format!("{}_1", net_id),
+            Box::new(common_behaviour(1, 0, &[(10, false)])),
+        );
+
+        let (owner, observers) = serialize(&mut identity, true);
+        assert_eq!(owner, vec![0b11, 1, 1, 1, 10]);
+        assert_eq!(observers, vec![0b10, 1, 10]);
+
+        // 组件 0 变化时观察者不需要同步
+        set_sync_var(net_id, 0, 0, 2);
+        let (owner, observers) = serialize(&mut identity, false);
+        assert_eq!(owner, vec![0b01, 3, 0, 0b1, 2]);
+        assert!(observers.is_empty());
+
+        NETWORK_BEHAVIOURS.remove(&format!("{}_0", net_id));
+        NETWORK_BEHAVIOURS.remove(&format!("{}_1", net_id));
+    }
+
+    #[test]
+    fn test_serialize_server_owner_only() {
+        let _lock = lock_server();
+        let mut identity = NetworkIdentity::new();
+        let net_id = NetworkIdentity::get_static_next_network_id();
+        identity.set_net_id(net_id);
+        identity.network_behaviours_count = 2;
+        // 组件 0 只同步给拥有者，组件 1 的第二个 SyncVar 只同步给拥有者
+        NETWORK_BEHAVIOURS.insert(
+            format!("{}_0", net_id),
+            Box::new(common_behaviour(0, 1, &[(1, false)])),
+        );
+        NETWORK_BEHAVIOURS.insert(
+            format!("{}_1", net_id),
+            Box::new(common_behaviour(1, 0, &[(10, false), (7, true)])),
+        );
+
+        // 初始状态：观察者没有组件 0，拥有者专属 SyncVar 写入初始值
+        let (owner, observers) = serialize(&mut identity, true);
+        assert_eq!(owner, vec![0b11, 1, 1, 2, 10, 7]);
+        assert_eq!(observers, vec![0b10, 2, 10, 0]);
+
+        // 只有拥有者专属 SyncVar 变化时观察者不需要同步
+        set_sync_var(net_id, 1, 1, 8);
+        let (owner, observers) = serialize(&mut identity, false);
+        assert_eq!(owner, vec![0b10, 3, 0, 0b10, 8]);
+        assert!(observers.is_empty());
+
+        // 观察者的脏位不包含拥有者专属 SyncVar
+        set_sync_var(net_id, 0, 0, 2);
+        set_sync_var(net_id, 1, 0, 11);
+        set_sync_var(net_id, 1, 1, 9);
+        let (owner, observers) = serialize(&mut identity, false);
+        assert_eq!(owner, vec![0b11, 3, 0, 0b01, 2, 4, 0, 0b11, 11, 9]);
+        assert_eq!(observers, vec![0b10, 3, 0, 0b01, 11]);
+
+        NETWORK_BEHAVIOURS.remove(&format!("{}_0", net_id));
+        NETWORK_BEHAVIOURS.remove(&format!("{}_1", net_id));
+    }
+}
@@ -1,4 +1,7 @@
 use crate::log_error;
+#[cfg(feature = "rapier")]
+use crate::mirror::components::network_rigidbody::physics_world::PhysicsWorld;
+use crate::mirror::core::messages::DisconnectReason;
 use crate::mirror::core::network_behaviour::NetworkBehaviourFactory;
 use crate::mirror::core::network_manager::NetworkManagerStatic;
 use crate::mirror::core::network_server::{NetworkServer, NetworkServerStatic, NETWORK_BEHAVIOURS};
@@ -6,27 +9,39 @@ use crate::mirror::core::network_time::NetworkTime;
 use dashmap::try_result::TryResult;
 use lazy_static::lazy_static;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
 use std::time::Duration;
 
+// 循环各阶段的函数，可以携带状态
+pub type NetworkLoopFunction = Box<dyn FnMut() + Send>;
+
+// 阶段函数执行时持有 functions 的锁，期间添加的函数先放到 pending，下次执行前合并
+#[derive(Default)]
+pub struct NetworkLoopFunctions {
+    functions: Mutex<Vec<NetworkLoopFunction>>,
+    pending: Mutex<Vec<NetworkLoopFunction>>,
+}
+
 lazy_static! {
     // 需要 添加的 awake 函数列表
-    static ref AWAKE_FUNCTIONS: RwLock<Vec<fn()>> = RwLock::new(vec![]);
+    static ref AWAKE_FUNCTIONS: NetworkLoopFunctions = NetworkLoopFunctions::default();
     // 需要 添加的 on_enable 函数列表
-    static ref ON_ENABLE_FUNCTIONS: RwLock<Vec<fn()>> = RwLock::new(vec![]);
+    static ref ON_ENABLE_FUNCTIONS: NetworkLoopFunctions = NetworkLoopFunctions::default();
     // 需要 添加的 start 函数列表
-    static ref START_FUNCTIONS: RwLock<Vec<fn()>> = RwLock::new(vec![]);
+    static ref START_FUNCTIONS: NetworkLoopFunctions = NetworkLoopFunctions::default();
     // 需要 添加的 early_update 函数列表
-    static ref EARLY_UPDATE_FUNCTIONS: RwLock<Vec<fn()>> = RwLock::new(vec![]);
+    static ref EARLY_UPDATE_FUNCTIONS: NetworkLoopFunctions = NetworkLoopFunctions::default();
     // 需要 添加的 update 函数列表
-    static ref UPDATE_FUNCTIONS: RwLock<Vec<fn()>> = RwLock::new(vec![]);
+    static ref UPDATE_FUNCTIONS: NetworkLoopFunctions = NetworkLoopFunctions::default();
     // 需要 添加的 late_update 函数列表
-    static ref LATE_UPDATE_FUNCTIONS: RwLock<Vec<fn()>> = RwLock::new(vec![]);
+    static ref LATE_UPDATE_FUNCTIONS: NetworkLoopFunctions = NetworkLoopFunctions::default();
+    // 需要 添加的 on_shutdown 函数列表
+    static ref ON_SHUTDOWN_FUNCTIONS: NetworkLoopFunctions = NetworkLoopFunctions::default();
     // 需要 添加的 disable 函数列表
-    static ref ON_DISABLE_FUNCTIONS: RwLock<Vec<fn()>> = RwLock::new(vec![]);
+    static ref ON_DISABLE_FUNCTIONS: NetworkLoopFunctions = NetworkLoopFunctions::default();
     // 需要 添加的 destroy 函数列表
-    static ref ON_DESTROY_FUNCTIONS: RwLock<Vec<fn()>> = RwLock::new(vec![]);
+    static ref ON_DESTROY_FUNCTIONS: NetworkLoopFunctions = NetworkLoopFunctions::default();
     // 需要 添加的 network_behaviour_factory 函数列表
     static ref NETWORK_BEHAVIOUR_FACTORY_FUNCTIONS: RwLock<Vec<fn()>> = RwLock::new(vec![]);
     // 需要 添加的 network_common_behaviour_delegate 函数列表
@@ -49,129 +64,174 @@ impl NetworkLoop {
     pub fn stop_signal() -> bool {
         STOP.load(Ordering::Relaxed)
     }
-    pub fn add_awake_function(func: fn()) {
-        match AWAKE_FUNCTIONS.write() {
-            Ok(mut awake_functions) => {
-                awake_functions.push(func);
+
+    fn add_function(functions: &NetworkLoopFunctions, func: NetworkLoopFunction, name: &str) {
+        match functions.pending.lock() {
+            Ok(mut pending) => {
+                pending.push(func);
             }
             Err(e) => {
-                log_error!(format!("add_awake_function error: {}", e));
+                log_error!(format!("{} error: {}", name, e));
             }
         }
     }
 
-    fn awake_functions() -> &'static RwLock<Vec<fn()>> {
-        &AWAKE_FUNCTIONS
-    }
-
-    pub fn add_on_enable_function(func: fn()) {
-        match ON_ENABLE_FUNCTIONS.write() {
-            Ok(mut on_enable_functions) => {
-                on_enable_functions.push(func);
+    // 阶段函数内部添加的函数从下一次执行开始调用
+    fn invoke_functions(functions: &NetworkLoopFunctions, name: &str) {
+        match functions.functions.try_lock() {
+            Ok(mut functions_guard) => {
+                match functions.pending.lock() {
+                    Ok(mut pending) => functions_guard.append(&mut pending),
+                    Err(e) => {
+                        log_error!(format!("{} error: {}", name, e));
+                    }
+                }
+                for func in functions_guard.iter_mut() {
+                    func();
+                }
             }
             Err(e) => {
-                log_error!(format!("add_on_enable_function error: {}", e));
+                log_error!(format!("{} error: {}", name, e));
             }
         }
     }
 
-    fn on_enable_functions() -> &'static RwLock<Vec<fn()>> {
+    pub fn add_awake_function(func: fn()) {
+        Self::add_awake_closure(func);
+    }
+
+    pub fn add_awake_closure(func: impl FnMut() + Send + 'static) {
+        Self::add_function(&AWAKE_FUNCTIONS, Box::new(func), "add_awake_closure");
+    }
+
+    fn awake_functions() -> &'static NetworkLoopFunctions {
+        &AWAKE_FUNCTIONS
+    }
+
+    pub fn add_on_enable_function(func: fn()) {
+        Self::add_on_enable_closure(func);
+    }
+
+    pub fn add_on_enable_closure(func: impl FnMut() + Send + 'static) {
+        Self::add_function(
+            &ON_ENABLE_FUNCTIONS,
+            Box::new(func),
+            "add_on_enable_closure",
+        );
+    }
+
+    fn on_enable_functions() -> &'static NetworkLoopFunctions {
         &ON_ENABLE_FUNCTIONS
     }
 
     pub fn add_start_function(func: fn()) {
-        match START_FUNCTIONS.write() {
-            Ok(mut start_functions) => {
-                start_functions.push(func);
-            }
-            Err(e) => {
-                log_error!(format!("add_start_function error: {}", e));
-            }
-        }
+        Self::add_start_closure(func);
+    }
+
+    pub fn add_start_closure(func: impl FnMut() + Send + 'static) {
+        Self::add_function(&START_FUNCTIONS, Box::new(func), "add_start_closure");
     }
 
-    fn start_functions() -> &'static RwLock<Vec<fn()>> {
+    fn start_functions() -> &'static NetworkLoopFunctions {
         &START_FUNCTIONS
     }
 
     // early_update
     pub fn add_early_update_function(func: fn()) {
-        match EARLY_UPDATE_FUNCTIONS.write() {
-            Ok(mut early_update_functions) => {
-                early_update_functions.push(func);
-            }
-            Err(e) => {
-                log_error!(format!("add_early_update_function error: {}", e));
-            }
-        }
+        Self::add_early_update_closure(func);
+    }
+
+    pub fn add_early_update_closure(func: impl FnMut() + Send + 'static) {
+        Self::add_function(
+            &EARLY_UPDATE_FUNCTIONS,
+            Box::new(func),
+            "add_early_update_closure",
+        );
     }
 
     // early_update
-    pub fn early_update_functions() -> &'static RwLock<Vec<fn()>> {
+    pub fn early_update_functions() -> &'static NetworkLoopFunctions {
         &EARLY_UPDATE_FUNCTIONS
     }
 
     // update
     pub fn add_update_function(func: fn()) {
-        match UPDATE_FUNCTIONS.write() {
-            Ok(mut update_functions) => {
-                update_functions.push(func);
-            }
-            Err(e) => {
-                log_error!(format!("add_update_function error: {}", e));
-            }
-        }
+        Self::add_update_closure(func);
+    }
+
+    pub fn add_update_closure(func: impl FnMut() + Send + 'static) {
+        Self::add_function(&UPDATE_FUNCTIONS, Box::new(func), "add_update_closure");
     }
 
     // update
-    pub fn update_functions() -> &'static RwLock<Vec<fn()>> {
+    pub fn update_functions() -> &'static NetworkLoopFunctions {
         &UPDATE_FUNCTIONS
     }
 
     // late_update
     pub fn add_late_update_function(func: fn()) {
-        match LATE_UPDATE_FUNCTIONS.write() {
-            Ok(mut late_update_functions) => {
-                late_update_functions.push(func);
-            }
-            Err(e) => {
-                log_error!(format!("add_late_update_function error: {}", e));
-            }
-        }
+        Self::add_late_update_closure(func);
+    }
+
+    pub fn add_late_update_closure(func: impl FnMut() + Send + 'static) {
+        Self::add_function(
+            &LATE_UPDATE_FUNCTIONS,
+            Box::new(func),
+            "add_late_update_closure",
+        );
     }
 
     // late_update
-    pub fn late_update_functions() -> &'static RwLock<Vec<fn()>> {
+    pub fn late_update_functions() -> &'static NetworkLoopFunctions {
         &LATE_UPDATE_FUNCTIONS
     }
 
+    // on_shutdown 在客户端收到断开原因之后、transport 停止之前调用，用于持久化等
+    pub fn add_on_shutdown_function(func: fn()) {
+        Self::add_on_shutdown_closure(func);
+    }
+
+    pub fn add_on_shutdown_closure(func: impl FnMut() + Send + 'static) {
+        Self::add_function(
+            &ON_SHUTDOWN_FUNCTIONS,
+            Box::new(func),
+            "add_on_shutdown_closure",
+        );
+    }
+
+    fn on_shutdown_functions() -> &'static NetworkLoopFunctions {
+        &ON_SHUTDOWN_FUNCTIONS
+    }
+
     pub fn add_on_disable_function(func: fn()) {
-        match ON_DISABLE_FUNCTIONS.write() {
-            Ok(mut on_disable_functions) => {
-                on_disable_functions.push(func);
-            }
-            Err(e) => {
-                log_error!(format!("add_on_disable_function error: {}", e));
-            }
-        }
+        Self::add_on_disable_closure(func);
     }
 
-    fn on_disable_functions() -> &'static RwLock<Vec<fn()>> {
+    pub fn add_on_disable_closure(func: impl FnMut() + Send + 'static) {
+        Self::add_function(
+            &ON_DISABLE_FUNCTIONS,
+            Box::new(func),
+            "add_on_disable_closure",
+        );
+    }
+
+    fn on_disable_functions() -> &'static NetworkLoopFunctions {
         &ON_DISABLE_FUNCTIONS
     }
 
     pub fn add_on_destroy_function(func: fn()) {
-        match ON_DESTROY_FUNCTIONS.write() {
-            Ok(mut on_destroy_functions) => {
-                on_destroy_functions.push(func);
-            }
-            Err(e) => {
-                log_error!(format!("add_on_destroy_function error: {}", e));
-            }
-        }
+        Self::add_on_destroy_closure(func);
+    }
+
+    pub fn add_on_destroy_closure(func: impl FnMut() + Send + 'static) {
+        Self::add_function(
+            &ON_DESTROY_FUNCTIONS,
+            Box::new(func),
+            "add_on_destroy_closure",
+        );
     }
 
-    fn on_destroy_functions() -> &'static RwLock<Vec<fn()>> {
+    fn on_destroy_functions() -> &'static NetworkLoopFunctions {
         &ON_DESTROY_FUNCTIONS
     }
 
@@ -230,30 +290,12 @@ impl NetworkLoop {
 
     // 1
     fn awake() {
-        match Self::awake_functions().try_read() {
-            Ok(awake_functions) => {
-                for func in awake_functions.iter() {
-                    func();
-                }
-            }
-            Err(e) => {
-                log_error!(format!("NetworkLoop.awake() error: {}", e));
-            }
-        }
+        Self::invoke_functions(Self::awake_functions(), "NetworkLoop.awake()");
     }
 
     // 2
     fn on_enable() {
-        match Self::on_enable_functions().try_read() {
-            Ok(on_enable_functions) => {
-                for func in on_enable_functions.iter() {
-                    func();
-                }
-            }
-            Err(e) => {
-                log_error!(format!("NetworkLoop.on_enable() error: {}", e));
-            }
-        }
+        Self::invoke_functions(Self::on_enable_functions(), "NetworkLoop.on_enable()");
     }
 
     // 3
@@ -261,16 +303,7 @@ impl NetworkLoop {
         let network_manager_singleton = NetworkManagerStatic::network_manager_singleton();
         network_manager_singleton.start();
 
-        match Self::start_functions().try_read() {
-            Ok(start_functions) => {
-                for func in start_functions.iter() {
-                    func();
-                }
-            }
-            Err(e) => {
-                log_error!(format!("NetworkLoop.start() error: {}", e));
-            }
-        }
+        Self::invoke_functions(Self::start_functions(), "NetworkLoop.start()");
     }
 
     // 4
@@ -279,16 +312,7 @@ impl NetworkLoop {
         // AddToPlayerLoop(NetworkEarlyUpdate, typeof(NetworkLoop), ref playerLoop, typeof(EarlyUpdate), AddMode.End);
         NetworkServer::network_early_update();
 
-        match Self::early_update_functions().try_read() {
-            Ok(early_update_functions) => {
-                for func in early_update_functions.iter() {
-                    func();
-                }
-            }
-            Err(e) => {
-                log_error!(format!("NetworkLoop.early_update() error: {}", e));
-            }
-        }
+        Self::invoke_functions(Self::early_update_functions(), "NetworkLoop.early_update()");
     }
 
     // 5
@@ -296,6 +320,10 @@ impl NetworkLoop {
         // NetworkManager update
         NetworkManagerStatic::network_manager_singleton().update();
 
+        // 服务器物理模拟
+        #[cfg(feature = "rapier")]
+        PhysicsWorld::step(1.0 / NetworkServerStatic::tick_rate() as f32);
+
         // NetworkBehaviour update  模拟
         NetworkServerStatic::spawned_network_identities()
             .iter()
@@ -323,16 +351,7 @@ impl NetworkLoop {
                 }
             });
 
-        match Self::update_functions().try_read() {
-            Ok(update_functions) => {
-                for func in update_functions.iter() {
-                    func();
-                }
-            }
-            Err(e) => {
-                log_error!(format!("NetworkLoop.update() error: {}", e));
-            }
-        }
+        Self::invoke_functions(Self::update_functions(), "NetworkLoop.update()");
     }
 
     // 6
@@ -371,30 +390,23 @@ impl NetworkLoop {
                 }
             });
 
-        match Self::late_update_functions().try_read() {
-            Ok(late_update_functions) => {
-                for func in late_update_functions.iter() {
-                    func();
-                }
-            }
-            Err(e) => {
-                log_error!(format!("NetworkLoop.late_update() error: {}", e));
-            }
-        }
+        Self::invoke_functions(Self::late_update_functions(), "NetworkLoop.late_update()");
+    }
+
+    // 优雅关闭
+    fn on_shutdown() {
+        // 停止接受新连接，通知客户端
+        NetworkServer::begin_shutdown(DisconnectReason::ServerShutdown, "");
+
+        Self::invoke_functions(Self::on_shutdown_functions(), "NetworkLoop.on_shutdown()");
+
+        // 等待可靠数据发送完成
+        NetworkServer::drain(NetworkServerStatic::shutdown_grace_period());
     }
 
     // 7
     fn on_disable() {
-        match Self::on_disable_functions().try_read() {
-            Ok(on_disable_functions) => {
-                for func in on_disable_functions.iter() {
-                    func();
-                }
-            }
-            Err(e) => {
-                log_error!(format!("NetworkLoop.on_disable() error: {}", e));
-            }
-        }
+        Self::invoke_functions(Self::on_disable_functions(), "NetworkLoop.on_disable()");
     }
 
     // 8
@@ -402,16 +414,7 @@ impl NetworkLoop {
         let network_manager_singleton = NetworkManagerStatic::network_manager_singleton();
         network_manager_singleton.on_destroy();
 
-        match Self::on_destroy_functions().try_read() {
-            Ok(on_destroy_functions) => {
-                for func in on_destroy_functions.iter() {
-                    func();
-                }
-            }
-            Err(e) => {
-                log_error!(format!("NetworkLoop.on_destroy() error: {}", e));
-            }
-        }
+        Self::invoke_functions(Self::on_destroy_functions(), "NetworkLoop.on_destroy()");
     }
 
     pub fn run() {
@@ -470,7 +473,55 @@ impl NetworkLoop {
             thread::sleep(sleep_time);
         }
 
+        if NetworkServerStatic::active() {
+            Self::on_shutdown();
+        }
         Self::on_disable();
         Self::on_destroy();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    lazy_static! {
+        static ref TEST_FUNCTIONS: NetworkLoopFunctions = NetworkLoopFunctions::default();
+    }
+
+    #[test]
+    fn test_add_function_while_invoking() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let outer_count = count.clone();
+        let mut added = false;
+        NetworkLoop::add_function(
+            &TEST_FUNCTIONS,
+            Box::new(move || {
+                outer_count.fetch_add(1, Ordering::Relaxed);
+                if added {
+                    return;
+                }
+                added = true;
+                // 执行中添加同一阶段的函数不会死锁
+                let inner_count = outer_count.clone();
+                NetworkLoop::add_function(
+                    &TEST_FUNCTIONS,
+                    Box::new(move || {
+                        inner_count.fetch_add(10, Ordering::Relaxed);
+                    }),
+                    "test",
+                );
+            }),
+            "test",
+        );
+
+        NetworkLoop::invoke_functions(&TEST_FUNCTIONS, "test");
+        // 新添加的函数从下一次开始执行
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+        NetworkLoop::invoke_functions(&TEST_FUNCTIONS, "test");
+        assert_eq!(count.load(Ordering::Relaxed), 12);
+        NetworkLoop::invoke_functions(&TEST_FUNCTIONS, "test");
+        assert_eq!(count.load(Ordering::Relaxed), 23);
+    }
+}
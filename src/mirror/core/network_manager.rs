@@ -13,7 +13,10 @@ use crate::mirror::core::network_behaviour::GameObject;
 use crate::mirror::core::network_connection::NetworkConnectionTrait;
 use crate::mirror::core::network_connection_to_client::NetworkConnectionToClient;
 use crate::mirror::core::network_reader::NetworkReader;
-use crate::mirror::core::network_server::{EventHandlerType, NetworkServer, NetworkServerStatic};
+use crate::mirror::core::network_server::{NetworkServer, NetworkServerStatic};
+use crate::mirror::core::server_events::{
+    EventHandlerType, EventSubscription, ServerEvent, ServerEvents,
+};
 use crate::mirror::core::transport::{Transport, TransportChannel, TransportError};
 use crate::{log_debug, log_error, log_warn};
 use atomic::Atomic;
@@ -31,6 +34,8 @@ lazy_static! {
     static ref START_POSITIONS: Arc<RwLock<Vec<Transform>>> = Arc::new(RwLock::new(Vec::new()));
     static ref START_POSITIONS_INDEX: Atomic<usize> = Atomic::new(0);
     static ref NETWORK_SCENE_NAME: RwLock<String> = RwLock::new("".to_string());
+    // NetworkManager 自己订阅的服务器事件，重复启动时先取消旧的订阅
+    static ref SERVER_EVENT_SUBSCRIPTIONS: RwLock<Vec<EventSubscription>> = RwLock::new(Vec::new());
 }
 
 // NetworkManagerStatic
@@ -87,6 +92,20 @@ impl NetworkManagerStatic {
         &START_POSITIONS
     }
 
+    pub fn set_server_event_subscriptions(subscriptions: Vec<EventSubscription>) {
+        match SERVER_EVENT_SUBSCRIPTIONS.write() {
+            Ok(mut server_event_subscriptions) => {
+                for subscription in server_event_subscriptions.drain(..) {
+                    subscription.unsubscribe();
+                }
+                *server_event_subscriptions = subscriptions;
+            }
+            Err(e) => {
+                log_error!(format!("Failed to set server event subscriptions: {:?}", e));
+            }
+        }
+    }
+
     pub fn add_start_position(start: Transform) {
         match START_POSITIONS.write() {
             Ok(mut sps) => {
@@ -201,26 +220,25 @@ impl NetworkManager {
 
     // zhuce
     fn register_server_messages() {
-        // 添加连接事件
-        NetworkServerStatic::connected_event().insert(
-            EventHandlerType::OnConnectedEvent,
-            Box::new(Self::on_server_connect_internal),
-        );
-        // 添加断开连接事件
-        NetworkServerStatic::connected_event().insert(
-            EventHandlerType::OnDisconnectedEvent,
-            Box::new(Self::on_server_disconnect),
-        );
-        // 添加错误事件
-        NetworkServerStatic::connected_event().insert(
-            EventHandlerType::OnErrorEvent,
-            Box::new(Self::on_server_error),
-        );
-        // 添加异常事件
-        NetworkServerStatic::connected_event().insert(
-            EventHandlerType::OnTransportExceptionEvent,
-            Box::new(Self::on_server_transport_exception),
-        );
+        NetworkManagerStatic::set_server_event_subscriptions(vec![
+            // 添加连接事件
+            ServerEvents::subscribe_connection(
+                EventHandlerType::OnConnectedEvent,
+                Self::on_server_connect_internal,
+            ),
+            // 添加断开连接事件
+            ServerEvents::subscribe_connection(
+                EventHandlerType::OnDisconnectedEvent,
+                Self::on_server_disconnect,
+            ),
+            // 添加错误事件
+            ServerEvents::subscribe_connection(EventHandlerType::OnErrorEvent, Self::on_server_error),
+            // 添加异常事件
+            ServerEvents::subscribe_connection(
+                EventHandlerType::OnTransportExceptionEvent,
+                Self::on_server_transport_exception,
+            ),
+        ]);
 
         // 添加 AddPlayerMessage 消息处理
         NetworkServer::register_handler::<AddPlayerMessage>(
@@ -250,7 +268,7 @@ impl NetworkManager {
         // 获取 场景名称
         let network_scene_name = NetworkManagerStatic::network_scene_name();
         // 如果 场景名称不为空 且 场景名称不等于 NetworkManager 的 offline_scene
-        if network_scene_name != "" && network_scene_name != offline_scene {
+        if !network_scene_name.is_empty() && network_scene_name != offline_scene {
             // 创建 SceneMessage 消息
             let mut scene_message = SceneMessage::new(
                 network_scene_name.to_string(),
@@ -285,7 +303,7 @@ impl NetworkManager {
         let network_manager = NetworkManagerStatic::network_manager_singleton();
 
         // 如果 NetworkManager 的 auto_create_player 为 true 且 player_obj.prefab 为空
-        if network_manager.auto_create_player() && network_manager.player_obj().prefab == "" {
+        if network_manager.auto_create_player() && network_manager.player_obj().prefab.is_empty() {
             log_error!("The PlayerPrefab is empty on the NetworkManager. Please setup a PlayerPrefab object.");
             return;
         }
@@ -296,8 +314,9 @@ impl NetworkManager {
             if let Some(asset_id) = BackendDataStatic::get_backend_data()
                 .get_asset_id_by_asset_name(network_manager.player_obj().prefab.as_str())
             {
-                if let None = BackendDataStatic::get_backend_data()
+                if BackendDataStatic::get_backend_data()
                     .get_network_identity_data_by_asset_id(asset_id)
+                    .is_none()
                 {
                     log_error!("The PlayerPrefab does not have a NetworkIdentity. Please add a NetworkIdentity to the player prefab.");
                     return;
@@ -362,11 +381,8 @@ impl NetworkManager {
     fn finish_load_scene(&mut self) {
         NetworkServerStatic::set_is_loading_scene(false);
 
-        match self.mode {
-            NetworkManagerMode::ServerOnly => {
-                self.finish_load_scene_server_only();
-            }
-            _ => {}
+        if let NetworkManagerMode::ServerOnly = self.mode {
+            self.finish_load_scene_server_only();
         }
     }
 
@@ -384,11 +400,11 @@ pub trait NetworkManagerTrait: Any {
         if NetworkManagerStatic::network_manager_singleton_exists() {
             return true;
         }
-        if NetworkManagerStatic::network_manager_singleton().dont_destroy_on_load() {
-            if NetworkManagerStatic::network_manager_singleton_exists() {
-                log_warn!("NetworkManager already exists in the scene. Deleting the new one.");
-                return false;
-            }
+        if NetworkManagerStatic::network_manager_singleton().dont_destroy_on_load()
+            && NetworkManagerStatic::network_manager_singleton_exists()
+        {
+            log_warn!("NetworkManager already exists in the scene. Deleting the new one.");
+            return false;
         }
 
         if !Transport::active_transport_exists() {
@@ -446,6 +462,8 @@ pub trait NetworkManagerTrait: Any {
 
         NetworkServer::shutdown();
 
+        NetworkManagerStatic::set_server_event_subscriptions(Vec::new());
+
         self.set_mode(NetworkManagerMode::Offline);
 
         NetworkManagerStatic::set_start_positions_index(0);
@@ -544,8 +562,6 @@ impl NetworkManagerTrait for NetworkManager {
     }
 
     fn on_validate(&mut self) {
-        self.max_connections = self.max_connections.max(0);
-
         if !self.player_obj.is_null() && !self.player_obj.is_has_component() {
             log_error!("NetworkManager - Player Prefab must have a NetworkIdentity.");
         }
@@ -597,7 +613,7 @@ impl NetworkManagerTrait for NetworkManager {
         Self: Sized,
     {
         let backend_data = BackendDataStatic::get_backend_data();
-        if backend_data.network_manager_settings.len() == 0 {
+        if backend_data.network_manager_settings.is_empty() {
             panic!("No NetworkManager settings found in the BackendData. Please add a NetworkManager setting.");
         }
         let network_manager_setting = backend_data.network_manager_settings[0].clone();
@@ -643,7 +659,7 @@ impl NetworkManagerTrait for NetworkManager {
     }
 
     fn server_change_scene(&mut self, new_scene_name: String) {
-        if new_scene_name == "" {
+        if new_scene_name.is_empty() {
             log_error!("ServerChangeScene newSceneName is empty");
             return;
         }
@@ -665,6 +681,15 @@ impl NetworkManagerTrait for NetworkManager {
             return;
         }
 
+        // 切换场景时卸载所有叠加场景
+        let additive_scenes: Vec<String> = NetworkServerStatic::additive_scenes()
+            .iter()
+            .map(|item| item.key().clone())
+            .collect();
+        for scene_name in additive_scenes {
+            NetworkServer::unload_additive_scene(scene_name.as_str());
+        }
+
         NetworkServer::set_all_clients_not_ready();
         NetworkManagerStatic::set_network_scene_name(new_scene_name.to_string());
 
@@ -680,6 +705,11 @@ impl NetworkManagerTrait for NetworkManager {
             );
         }
 
+        ServerEvents::invoke(&mut ServerEvent::SceneChanged {
+            scene_name: new_scene_name.as_str(),
+            operation: SceneOperation::Normal,
+        });
+
         NetworkManagerStatic::set_start_positions_index(0);
         NetworkManagerStatic::start_positions().write().unwrap().clear();
     }
@@ -688,8 +718,7 @@ impl NetworkManagerTrait for NetworkManager {
         if NetworkManagerStatic::start_positions()
             .read()
             .unwrap()
-            .len()
-            == 0
+            .is_empty()
         {
             return Transform::default();
         }
@@ -700,7 +729,7 @@ impl NetworkManagerTrait for NetworkManager {
                 .read()
                 .unwrap()
                 .len() as u32;
-            return NetworkManagerStatic::start_positions().read().unwrap()[index as usize].clone();
+            return NetworkManagerStatic::start_positions().read().unwrap()[index as usize];
         }
         let index = NetworkManagerStatic::start_positions_index();
         NetworkManagerStatic::set_start_positions_index(
@@ -710,15 +739,15 @@ impl NetworkManagerTrait for NetworkManager {
                 .unwrap()
                 .len(),
         );
-        NetworkManagerStatic::start_positions().read().unwrap()[index].clone()
+        NetworkManagerStatic::start_positions().read().unwrap()[index]
     }
 
     // OnServerDisconnect
-    fn on_server_disconnect(conn: &mut NetworkConnectionToClient, _transport_error: TransportError)
+    // 之后 NetworkServer 会销毁玩家，见 NetworkServerStatic::set_destroy_player_on_disconnect
+    fn on_server_disconnect(_conn: &mut NetworkConnectionToClient, _transport_error: TransportError)
     where
         Self: Sized,
     {
-        NetworkServer::destroy_player_for_connection(conn);
     }
 
     fn on_server_ready(conn_id: u64)
@@ -1,14 +1,62 @@
 use crate::log_warn;
+use atomic::Atomic;
 use half::f16;
+use lazy_static::lazy_static;
 use nalgebra::{Quaternion, Vector2, Vector3, Vector4};
 use rust_decimal::Decimal;
 use std::fmt;
+use std::sync::atomic::Ordering;
+
+lazy_static! {
+    // 字符串最大字节数
+    static ref MAX_STRING_LENGTH: Atomic<usize> = Atomic::new(1024 * 32);
+    // 字节数组最大长度
+    static ref MAX_BYTES_LENGTH: Atomic<usize> = Atomic::new(NetworkReader::ALLOCATION_LIMIT);
+}
+
+// 读取失败的原因
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadError {
+    EndOfStream { needed: usize, remaining: usize },
+    StringTooLong { length: usize, max: usize },
+    BytesTooLong { length: usize, max: usize },
+    InvalidUtf8,
+    InvalidValue(&'static str),
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::EndOfStream { needed, remaining } => write!(
+                f,
+                "end of stream: needed {} bytes, {} remaining",
+                needed, remaining
+            ),
+            ReadError::StringTooLong { length, max } => {
+                write!(f, "string too long: {} > {}", length, max)
+            }
+            ReadError::BytesTooLong { length, max } => {
+                write!(f, "bytes too long: {} > {}", length, max)
+            }
+            ReadError::InvalidUtf8 => write!(f, "invalid utf8 string"),
+            ReadError::InvalidValue(name) => write!(f, "invalid value for {}", name),
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
 
 pub struct NetworkReader {
     data: Vec<u8>,
     position: usize,
 }
 
+impl Default for NetworkReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NetworkReader {
     pub const ALLOCATION_LIMIT: usize = 1024 * 1024 * 16;
 
@@ -56,19 +104,63 @@ impl NetworkReader {
         self.data = data.to_vec();
         self.position = 0;
     }
-    pub fn read_blittable<T>(&mut self) -> T {
+    pub fn max_string_length() -> usize {
+        MAX_STRING_LENGTH.load(Ordering::Relaxed)
+    }
+    pub fn set_max_string_length(value: usize) {
+        MAX_STRING_LENGTH.store(value, Ordering::Relaxed);
+    }
+    pub fn max_bytes_length() -> usize {
+        MAX_BYTES_LENGTH.load(Ordering::Relaxed)
+    }
+    pub fn set_max_bytes_length(value: usize) {
+        MAX_BYTES_LENGTH.store(value, Ordering::Relaxed);
+    }
+    fn end_of_stream(&self, needed: usize) -> ReadError {
+        ReadError::EndOfStream {
+            needed,
+            remaining: self.remaining(),
+        }
+    }
+    // T 必须是任意位模式都合法的类型，bool / char 等需要先读整数再校验
+    pub fn try_read_blittable<T>(&mut self) -> Result<T, ReadError> {
         let size = size_of::<T>();
         if self.remaining() < size {
-            log_warn!("Not enough data to read");
-            self.position = self.data.len();
-            return unsafe { std::mem::zeroed() };
+            return Err(self.end_of_stream(size));
         }
         let value = unsafe {
             let ptr = self.data.as_ptr().add(self.position) as *const T;
             ptr.read_unaligned()
         };
         self.position += size;
-        value
+        Ok(value)
+    }
+    pub fn try_read_blittable_nullable<T>(&mut self) -> Result<Option<T>, ReadError> {
+        match self.try_read_blittable::<u8>()? {
+            0 => Ok(None),
+            _ => Ok(Some(self.try_read_blittable()?)),
+        }
+    }
+    pub fn try_read_bytes(&mut self, count: usize) -> Result<Vec<u8>, ReadError> {
+        Ok(self.try_read_array_segment(count)?.to_vec())
+    }
+    pub fn try_read_array_segment(&mut self, count: usize) -> Result<&[u8], ReadError> {
+        if self.remaining() < count {
+            return Err(self.end_of_stream(count));
+        }
+        let value = &self.data[self.position..self.position + count];
+        self.position += count;
+        Ok(value)
+    }
+    pub fn read_blittable<T>(&mut self) -> T {
+        match self.try_read_blittable() {
+            Ok(value) => value,
+            Err(e) => {
+                log_warn!(format!("Not enough data to read: {}", e));
+                self.position = self.data.len();
+                unsafe { std::mem::zeroed() }
+            }
+        }
     }
     pub fn read_blittable_nullable<T>(&mut self) -> Option<T> {
         let is_null = self.read_byte() == 0;
@@ -79,20 +171,22 @@ impl NetworkReader {
         }
     }
     pub fn read_bytes(&mut self, count: usize) -> Vec<u8> {
-        if self.remaining() < count {
-            log_warn!("Not enough data to read");
-            return Vec::new();
+        match self.try_read_bytes(count) {
+            Ok(value) => value,
+            Err(e) => {
+                log_warn!(format!("Not enough data to read: {}", e));
+                self.position = self.data.len();
+                Vec::new()
+            }
         }
-        let value = self.data[self.position..self.position + count].to_vec();
-        self.position += count;
-        value
     }
     pub fn read_remaining_bytes(&mut self) -> Vec<u8> {
         self.read_bytes(self.remaining())
     }
     pub fn read_array_segment(&mut self, count: usize) -> &[u8] {
         if self.remaining() < count {
-            log_warn!("Not enough data to read");
+            log_warn!(format!("Not enough data to read: {}", self.end_of_stream(count)));
+            self.position = self.data.len();
             return &[];
         }
         let value = &self.data[self.position..self.position + count];
@@ -189,6 +283,32 @@ pub trait NetworkReaderTrait {
     fn decompress_var_ulong(&mut self) -> u64;
 }
 
+// 可失败的读取，用于消息反序列化等处理不可信数据的地方
+pub trait NetworkReaderTryTrait {
+    fn try_read_byte(&mut self) -> Result<u8, ReadError>;
+    fn try_read_sbyte(&mut self) -> Result<i8, ReadError>;
+    fn try_read_bool(&mut self) -> Result<bool, ReadError>;
+    fn try_read_short(&mut self) -> Result<i16, ReadError>;
+    fn try_read_ushort(&mut self) -> Result<u16, ReadError>;
+    fn try_read_int(&mut self) -> Result<i32, ReadError>;
+    fn try_read_uint(&mut self) -> Result<u32, ReadError>;
+    fn try_read_uint_nullable(&mut self) -> Result<Option<u32>, ReadError>;
+    fn try_read_long(&mut self) -> Result<i64, ReadError>;
+    fn try_read_ulong(&mut self) -> Result<u64, ReadError>;
+    fn try_read_float(&mut self) -> Result<f32, ReadError>;
+    fn try_read_double(&mut self) -> Result<f64, ReadError>;
+    fn try_read_string(&mut self) -> Result<String, ReadError>;
+    fn try_read_bytes_and_size(&mut self) -> Result<Vec<u8>, ReadError>;
+    fn try_read_vector2(&mut self) -> Result<Vector2<f32>, ReadError>;
+    fn try_read_vector3(&mut self) -> Result<Vector3<f32>, ReadError>;
+    fn try_read_vector4(&mut self) -> Result<Vector4<f32>, ReadError>;
+    fn try_read_quaternion(&mut self) -> Result<Quaternion<f32>, ReadError>;
+    fn try_decompress_var_uint(&mut self) -> Result<u32, ReadError>;
+    fn try_decompress_var_ulong(&mut self) -> Result<u64, ReadError>;
+    fn try_decompress_var_int(&mut self) -> Result<i32, ReadError>;
+    fn try_decompress_var_long(&mut self) -> Result<i64, ReadError>;
+}
+
 impl fmt::Display for NetworkReader {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let hex_string = self.data.iter().map(|byte| format!("{:02X}", byte)).collect::<String>();
@@ -1,5 +1,7 @@
-use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTrait, Readable};
-use crate::{log_error, log_trace};
+use crate::mirror::core::network_reader::{
+    NetworkReader, NetworkReaderTrait, NetworkReaderTryTrait, ReadError, Readable,
+};
+use crate::{log_error, log_trace, log_warn};
 use half::f16;
 use nalgebra::{Quaternion, Vector2, Vector3, Vector4};
 use rust_decimal::Decimal;
@@ -7,17 +9,145 @@ use rust_decimal::Decimal;
 pub struct NetworkReaderExtensions;
 impl NetworkReaderExtensions {
     fn read_string(reader: &mut NetworkReader) -> String {
-        let length = reader.read_ushort() as usize;
+        match reader.try_read_string() {
+            Ok(string) => string,
+            Err(e) => {
+                log_error!(format!("NetworkReaderExtensions::read_string() failed: {}", e));
+                String::new()
+            }
+        }
+    }
+}
+impl NetworkReaderTryTrait for NetworkReader {
+    fn try_read_byte(&mut self) -> Result<u8, ReadError> {
+        self.try_read_blittable::<u8>()
+    }
+
+    fn try_read_sbyte(&mut self) -> Result<i8, ReadError> {
+        self.try_read_blittable::<i8>()
+    }
+
+    fn try_read_bool(&mut self) -> Result<bool, ReadError> {
+        Ok(self.try_read_byte()? != 0)
+    }
+
+    fn try_read_short(&mut self) -> Result<i16, ReadError> {
+        self.try_read_blittable::<i16>()
+    }
+
+    fn try_read_ushort(&mut self) -> Result<u16, ReadError> {
+        self.try_read_blittable::<u16>()
+    }
+
+    fn try_read_int(&mut self) -> Result<i32, ReadError> {
+        self.try_read_blittable::<i32>()
+    }
+
+    fn try_read_uint(&mut self) -> Result<u32, ReadError> {
+        self.try_read_blittable::<u32>()
+    }
+
+    fn try_read_uint_nullable(&mut self) -> Result<Option<u32>, ReadError> {
+        self.try_read_blittable_nullable::<u32>()
+    }
+
+    fn try_read_long(&mut self) -> Result<i64, ReadError> {
+        self.try_read_blittable::<i64>()
+    }
+
+    fn try_read_ulong(&mut self) -> Result<u64, ReadError> {
+        self.try_read_blittable::<u64>()
+    }
+
+    fn try_read_float(&mut self) -> Result<f32, ReadError> {
+        self.try_read_blittable::<f32>()
+    }
+
+    fn try_read_double(&mut self) -> Result<f64, ReadError> {
+        self.try_read_blittable::<f64>()
+    }
+
+    // 长度为 0 表示 null，否则实际长度为 length - 1
+    fn try_read_string(&mut self) -> Result<String, ReadError> {
+        let length = self.try_read_ushort()? as usize;
         if length == 0 {
-            return String::new();
+            return Ok(String::new());
         }
-        let bytes = reader.read_bytes(length - 1);
-        if let Ok(string) = String::from_utf8(bytes) {
-            string
-        } else {
-            log_error!("NetworkReaderExtensions::read_string() failed to convert bytes to string");
-            String::new()
+        let size = length - 1;
+        let max = NetworkReader::max_string_length();
+        if size > max {
+            return Err(ReadError::StringTooLong { length: size, max });
         }
+        let bytes = self.try_read_bytes(size)?;
+        String::from_utf8(bytes).map_err(|_| ReadError::InvalidUtf8)
+    }
+
+    fn try_read_bytes_and_size(&mut self) -> Result<Vec<u8>, ReadError> {
+        let count = self.try_decompress_var_ulong()?;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let size = (count - 1) as usize;
+        let max = NetworkReader::max_bytes_length();
+        if size > max {
+            return Err(ReadError::BytesTooLong { length: size, max });
+        }
+        self.try_read_bytes(size)
+    }
+
+    fn try_read_vector2(&mut self) -> Result<Vector2<f32>, ReadError> {
+        self.try_read_blittable::<Vector2<f32>>()
+    }
+
+    fn try_read_vector3(&mut self) -> Result<Vector3<f32>, ReadError> {
+        self.try_read_blittable::<Vector3<f32>>()
+    }
+
+    fn try_read_vector4(&mut self) -> Result<Vector4<f32>, ReadError> {
+        self.try_read_blittable::<Vector4<f32>>()
+    }
+
+    fn try_read_quaternion(&mut self) -> Result<Quaternion<f32>, ReadError> {
+        self.try_read_blittable::<Quaternion<f32>>()
+    }
+
+    fn try_decompress_var_uint(&mut self) -> Result<u32, ReadError> {
+        let value = self.try_decompress_var_ulong()?;
+        u32::try_from(value).map_err(|_| ReadError::InvalidValue("var uint"))
+    }
+
+    fn try_decompress_var_ulong(&mut self) -> Result<u64, ReadError> {
+        let a0 = self.try_read_byte()? as u64;
+        if a0 < 241 {
+            return Ok(a0);
+        }
+
+        let a1 = self.try_read_byte()? as u64;
+        if a0 <= 248 {
+            return Ok(240 + ((a0 - 241) << 8) + a1);
+        }
+
+        let a2 = self.try_read_byte()? as u64;
+        if a0 == 249 {
+            return Ok(2288 + (a1 << 8) + a2);
+        }
+
+        // 250 - 255: 后面是 3 - 8 字节的小端整数
+        let size = (a0 - 247) as usize;
+        let mut value = a1 + (a2 << 8);
+        for i in 2..size {
+            value += (self.try_read_byte()? as u64) << (8 * i);
+        }
+        Ok(value)
+    }
+
+    fn try_decompress_var_int(&mut self) -> Result<i32, ReadError> {
+        Ok(self.try_decompress_var_long()? as i32)
+    }
+
+    fn try_decompress_var_long(&mut self) -> Result<i64, ReadError> {
+        let data = self.try_decompress_var_ulong()? as i64;
+        Ok((data >> 1) ^ -(data & 1))
     }
 }
 impl NetworkReaderTrait for NetworkReader {
@@ -37,20 +167,22 @@ impl NetworkReaderTrait for NetworkReader {
         self.read_blittable_nullable::<i8>()
     }
 
+    // 不能直接按 char / bool 读取，非法的位模式是未定义行为
     fn read_char(&mut self) -> char {
-        self.read_blittable::<char>()
+        char::from_u32(self.read_blittable::<u32>()).unwrap_or_default()
     }
 
     fn read_char_nullable(&mut self) -> Option<char> {
-        self.read_blittable_nullable::<char>()
+        self.read_blittable_nullable::<u32>()
+            .map(|value| char::from_u32(value).unwrap_or_default())
     }
 
     fn read_bool(&mut self) -> bool {
-        self.read_blittable::<bool>()
+        self.read_byte() != 0
     }
 
     fn read_bool_nullable(&mut self) -> Option<bool> {
-        self.read_blittable_nullable::<bool>()
+        self.read_byte_nullable().map(|value| value != 0)
     }
 
     fn read_short(&mut self) -> i16 {
@@ -150,11 +282,14 @@ impl NetworkReaderTrait for NetworkReader {
     }
 
     fn read_bytes_and_size(&mut self) -> Vec<u8> {
-        let count = self.decompress_var_uint() as usize;
-        if count == 0 {
-            return Vec::new();
+        match self.try_read_bytes_and_size() {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                log_warn!(format!("NetworkReader.read_bytes_and_size failed: {}", e));
+                self.set_position(self.capacity());
+                Vec::new()
+            }
         }
-        self.read_bytes(count - 1)
     }
 
     fn read_array_segment_and_size(&mut self) -> &[u8] {
@@ -320,59 +455,14 @@ impl NetworkReaderTrait for NetworkReader {
     }
 
     fn decompress_var_ulong(&mut self) -> u64 {
-        let a0 = self.read_byte() as u64;
-        if a0 < 241 {
-            return a0;
+        match self.try_decompress_var_ulong() {
+            Ok(value) => value,
+            Err(e) => {
+                log_warn!(format!("NetworkReader.decompress_var_ulong failed: {}", e));
+                self.set_position(self.capacity());
+                0
+            }
         }
-
-        let a1 = self.read_byte() as u64;
-        if a0 <= 248 {
-            return 240 + ((a0 - 241) << 8) + a1;
-        }
-
-        let a2 = self.read_byte() as u64;
-        if a0 == 249 {
-            return 2288 + (a1 << 8) + a2;
-        }
-
-        let a3 = self.read_byte() as u64;
-        if a0 == 250 {
-            return a1 + (a2 << 8) + (a3 << 16);
-        }
-
-        let a4 = self.read_byte() as u64;
-        if a0 == 251 {
-            return a1 + (a2 << 8) + (a3 << 16) + (a4 << 24);
-        }
-
-        let a5 = self.read_byte() as u64;
-        if a0 == 252 {
-            return a1 + (a2 << 8) + (a3 << 16) + (a4 << 24) + (a5 << 32);
-        }
-
-        let a6 = self.read_byte() as u64;
-        if a0 == 253 {
-            return a1 + (a2 << 8) + (a3 << 16) + (a4 << 24) + (a5 << 32) + (a6 << 40);
-        }
-
-        let a7 = self.read_byte() as u64;
-        if a0 == 254 {
-            return a1 + (a2 << 8) + (a3 << 16) + (a4 << 24) + (a5 << 32) + (a6 << 40) + (a7 << 48);
-        }
-
-        let a8 = self.read_byte() as u64;
-        if a0 == 255 {
-            return a1
-                + (a2 << 8)
-                + (a3 << 16)
-                + (a4 << 24)
-                + (a5 << 32)
-                + (a6 << 40)
-                + (a7 << 48)
-                + (a8 << 56);
-        }
-        log_trace!("DecompressVarUInt failure: {}", a0);
-        0
     }
 }
 
@@ -403,4 +493,99 @@ mod tests {
         let value = reader.read_string();
         assert_eq!(value, "Hello, World!");
     }
+
+    #[test]
+    fn try_read_string() {
+        let mut writer = NetworkWriter::new();
+        writer.write_string("Hello".to_string());
+        let mut reader = NetworkReader::new_with_bytes(writer.to_bytes());
+        assert_eq!(reader.try_read_string(), Ok("Hello".to_string()));
+
+        // 长度超过限制时不读取内容
+        let max = NetworkReader::max_string_length();
+        let mut writer = NetworkWriter::new();
+        writer.write_ushort((max + 2) as u16);
+        let mut reader = NetworkReader::new_with_bytes(writer.to_bytes());
+        assert_eq!(
+            reader.try_read_string(),
+            Err(ReadError::StringTooLong {
+                length: max + 1,
+                max
+            })
+        );
+
+        let mut writer = NetworkWriter::new();
+        writer.write_ushort(3);
+        writer.write_array_segment_all(&[0xff, 0xfe]);
+        let mut reader = NetworkReader::new_with_bytes(writer.to_bytes());
+        assert_eq!(reader.try_read_string(), Err(ReadError::InvalidUtf8));
+
+        // 内容不完整
+        let mut writer = NetworkWriter::new();
+        writer.write_ushort(6);
+        writer.write_array_segment_all(b"abc");
+        let mut reader = NetworkReader::new_with_bytes(writer.to_bytes());
+        assert_eq!(
+            reader.try_read_string(),
+            Err(ReadError::EndOfStream {
+                needed: 5,
+                remaining: 3
+            })
+        );
+    }
+
+    #[test]
+    fn try_read_bytes_and_size() {
+        let mut writer = NetworkWriter::new();
+        writer.write_bytes_and_size(vec![1, 2, 3]);
+        writer.write_bytes_and_size(Vec::new());
+        let mut reader = NetworkReader::new_with_bytes(writer.to_bytes());
+        assert_eq!(reader.try_read_bytes_and_size(), Ok(vec![1, 2, 3]));
+        assert_eq!(reader.try_read_bytes_and_size(), Ok(Vec::new()));
+
+        // 长度超过限制时不分配内存
+        let max = NetworkReader::max_bytes_length();
+        let mut writer = NetworkWriter::new();
+        writer.compress_var_ulong(max as u64 + 2);
+        let mut reader = NetworkReader::new_with_bytes(writer.to_bytes());
+        assert_eq!(
+            reader.try_read_bytes_and_size(),
+            Err(ReadError::BytesTooLong {
+                length: max + 1,
+                max
+            })
+        );
+    }
+
+    #[test]
+    fn try_decompress_var_truncated() {
+        for value in [240u64, 2287, 67823, 1 << 24, u64::MAX] {
+            let mut writer = NetworkWriter::new();
+            writer.compress_var_ulong(value);
+            let bytes = writer.to_bytes();
+            let mut reader = NetworkReader::new_with_bytes(bytes.clone());
+            assert_eq!(reader.try_decompress_var_ulong(), Ok(value));
+
+            // 去掉最后一个字节
+            let mut reader = NetworkReader::new_with_bytes(bytes[..bytes.len() - 1].to_vec());
+            assert!(matches!(
+                reader.try_decompress_var_ulong(),
+                Err(ReadError::EndOfStream { .. })
+            ));
+        }
+
+        let mut reader = NetworkReader::new_with_bytes(Vec::new());
+        assert!(matches!(
+            reader.try_decompress_var_uint(),
+            Err(ReadError::EndOfStream { .. })
+        ));
+        // 超出 u32 范围
+        let mut writer = NetworkWriter::new();
+        writer.compress_var_ulong(u32::MAX as u64 + 1);
+        let mut reader = NetworkReader::new_with_bytes(writer.to_bytes());
+        assert_eq!(
+            reader.try_decompress_var_uint(),
+            Err(ReadError::InvalidValue("var uint"))
+        );
+    }
 }
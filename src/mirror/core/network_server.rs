@@ -1,24 +1,35 @@
+#[cfg(feature = "rapier")]
+use crate::mirror::components::network_rigidbody::physics_world::PhysicsWorld;
+use crate::mirror::components::network_transform::network_transform_base::Transform;
+use crate::mirror::core::authority_policy::AuthorityPolicyStatic;
 use crate::mirror::core::backend_data::BackendDataStatic;
 use crate::mirror::core::batching::un_batcher::UnBatcher;
+use crate::mirror::core::lag_compensation::LagCompensation;
 use crate::mirror::core::messages::{
-    ChangeOwnerMessage, CommandMessage, EntityStateMessage, NetworkMessageHandler,
-    NetworkMessageHandlerFunc, NetworkMessageTrait, NetworkPingMessage, NetworkPongMessage,
-    NotReadyMessage, ObjectDestroyMessage, ObjectHideMessage, ObjectSpawnFinishedMessage,
-    ObjectSpawnStartedMessage, ReadyMessage, SpawnMessage, TimeSnapshotMessage,
+    ChangeOwnerMessage, CommandMessage, DisconnectMessage, DisconnectReason, EntityStateMessage,
+    NetworkMessageHandler, NetworkMessageHandlerFunc, NetworkMessageTrait, NetworkPingMessage,
+    NetworkPongMessage, NotReadyMessage, ObjectDestroyMessage, ObjectHideMessage,
+    ObjectSpawnFinishedMessage, ObjectSpawnStartedMessage, ReadyMessage, RequestAuthorityMessage,
+    SceneMessage, SceneOperation, ServerRestartMessage, InputAckMessage, InputMessage, SpawnMessage,
+    StateAckMessage, TimeSnapshotMessage, UnreliableStateMessage,
 };
 use crate::mirror::core::network_behaviour::{GameObject, NetworkBehaviourTrait};
 use crate::mirror::core::network_connection::NetworkConnectionTrait;
 use crate::mirror::core::network_connection_to_client::NetworkConnectionToClient;
 use crate::mirror::core::network_identity::Visibility::ForceShown;
 use crate::mirror::core::network_identity::{NetworkIdentity, Visibility};
+use crate::mirror::core::network_loop::NetworkLoop;
 use crate::mirror::core::network_manager::NetworkManagerStatic;
 use crate::mirror::core::network_messages::NetworkMessages;
-use crate::mirror::core::network_reader::NetworkReader;
+use crate::mirror::core::network_reader::{NetworkReader, ReadError};
 use crate::mirror::core::network_reader_pool::NetworkReaderPool;
 use crate::mirror::core::network_time::NetworkTime;
 use crate::mirror::core::network_writer_pool::NetworkWriterPool;
+use crate::mirror::core::rate_limiter::{RateLimitPolicy, RateLimiter};
 use crate::mirror::core::remote_calls::{RemoteCallType, RemoteProcedureCalls};
+use crate::mirror::core::server_events::{ServerEvent, ServerEvents};
 use crate::mirror::core::snapshot_interpolation::time_snapshot::TimeSnapshot;
+use crate::mirror::core::tools::delta_compression::DeltaCompression;
 use crate::mirror::core::tools::time_sample::TimeSample;
 use crate::mirror::core::transport::{
     Transport, TransportCallback, TransportCallbackType, TransportChannel, TransportError,
@@ -29,9 +40,12 @@ use dashmap::mapref::multiple::RefMutMulti;
 use dashmap::try_result::TryResult;
 use dashmap::{DashMap, DashSet};
 use lazy_static::lazy_static;
-use std::fmt::Debug;
+use nalgebra::Vector3;
+use std::collections::HashSet;
 use std::sync::atomic::Ordering;
 use std::sync::RwLock;
+use std::thread;
+use std::time::Duration;
 
 pub enum ReplacePlayerOptions {
     KeepAuthority,
@@ -49,20 +63,8 @@ pub enum RemovePlayerOptions {
     Destroy,
 }
 
-// EventHandler 静态变量
-type EventHandler = fn(&mut NetworkConnectionToClient, TransportError);
-
-#[derive(Hash, Eq, PartialEq, Debug)]
-pub enum EventHandlerType {
-    OnConnectedEvent,
-    OnDisconnectedEvent,
-    OnErrorEvent,
-    OnTransportExceptionEvent,
-}
-
 // NetworkServer 静态变量
 lazy_static! {
-    static ref CONNECTED_EVENT: DashMap<EventHandlerType, Box<EventHandler>> = DashMap::new();
     static ref Initialized: Atomic<bool> = Atomic::new(false);
     static ref TickRate: Atomic<u32> = Atomic::new(60);
     static ref TICK_INTERVAL: Atomic<f32> =
@@ -75,18 +77,33 @@ lazy_static! {
     static ref ACTIVE: Atomic<bool> = Atomic::new(false);
     static ref IS_LOADING_SCENE: Atomic<bool> = Atomic::new(false);
     static ref EXCEPTIONS_DISCONNECT: Atomic<bool> = Atomic::new(false);
+    // 断开连接时是否销毁玩家和 owned 对象
+    static ref DESTROY_PLAYER_ON_DISCONNECT: Atomic<bool> = Atomic::new(true);
     static ref DISCONNECT_INACTIVE_CONNECTIONS: Atomic<bool> = Atomic::new(false);
     static ref DISCONNECT_INACTIVE_TIMEOUT: Atomic<f32> = Atomic::new(10.0);
     static ref ACTUAL_TICK_RATE: Atomic<u32> = Atomic::new(0);
     static ref ACTUAL_TICK_RATE_START: Atomic<f64> = Atomic::new(0.0);
     static ref ACTUAL_TICK_RATE_COUNTER: Atomic<u32> = Atomic::new(0);
     static ref MAX_CONNECTIONS: Atomic<usize> = Atomic::new(0);
+    static ref SHUTTING_DOWN: Atomic<bool> = Atomic::new(false);
+    static ref SHUTDOWN_REASON: Atomic<u8> = Atomic::new(DisconnectReason::None.to_u8());
+    static ref SHUTDOWN_GRACE_PERIOD: Atomic<f64> = Atomic::new(3.0);
+    static ref SHUTDOWN_MESSAGES: Atomic<bool> = Atomic::new(false);
+    static ref RESTART_TIME: Atomic<f64> = Atomic::new(0.0);
+    static ref RESTART_LAST_COUNTDOWN: Atomic<u32> = Atomic::new(0);
+    static ref RESTART_MESSAGE: RwLock<String> = RwLock::new(String::new());
+    static ref BANDWIDTH_BUDGET: Atomic<u32> = Atomic::new(0);
+    static ref UNRELIABLE_STATE_SYNC: Atomic<bool> = Atomic::new(false);
+    static ref PRIORITY_OWNER_MULTIPLIER: Atomic<f32> = Atomic::new(4.0);
+    static ref PRIORITY_DISTANCE_REFERENCE: Atomic<f32> = Atomic::new(20.0);
     static ref EARLY_UPDATE_DURATION: RwLock<TimeSample> = RwLock::new(TimeSample::new(0));
     static ref LATE_UPDATE_DURATION: RwLock<TimeSample> = RwLock::new(TimeSample::new(0));
     static ref FULL_UPDATE_DURATION: RwLock<TimeSample> = RwLock::new(TimeSample::new(0));
     static ref NETWORK_CONNECTIONS: DashMap<u64, NetworkConnectionToClient> = DashMap::new();
     static ref SPAWNED_NETWORK_IDS: DashSet<u32> = DashSet::new();
     static ref SPAWNED_NETWORK_IDENTITIES: DashMap<u32, NetworkIdentity> = DashMap::new();
+    // 服务器已加载的叠加场景 scene_name -> scene_hash
+    static ref ADDITIVE_SCENES: DashMap<String, u32> = DashMap::new();
     pub static ref NETWORK_BEHAVIOURS: DashMap<String, Box<dyn NetworkBehaviourTrait>> =
         DashMap::new();
     static ref NETWORK_MESSAGE_HANDLERS: DashMap<u16, NetworkMessageHandler> = DashMap::new();
@@ -139,8 +156,11 @@ impl NetworkServerStatic {
     pub fn set_exceptions_disconnect(value: bool) {
         EXCEPTIONS_DISCONNECT.store(value, Ordering::Relaxed);
     }
-    pub fn connected_event() -> &'static DashMap<EventHandlerType, Box<EventHandler>> {
-        &CONNECTED_EVENT
+    pub fn destroy_player_on_disconnect() -> bool {
+        DESTROY_PLAYER_ON_DISCONNECT.load(Ordering::Relaxed)
+    }
+    pub fn set_destroy_player_on_disconnect(value: bool) {
+        DESTROY_PLAYER_ON_DISCONNECT.store(value, Ordering::Relaxed);
     }
     pub fn initialized() -> bool {
         Initialized.load(Ordering::Relaxed)
@@ -154,6 +174,93 @@ impl NetworkServerStatic {
     pub fn set_max_connections(value: usize) {
         MAX_CONNECTIONS.store(value, Ordering::Relaxed);
     }
+    pub fn is_shutting_down() -> bool {
+        SHUTTING_DOWN.load(Ordering::Relaxed)
+    }
+    pub fn set_shutting_down(value: bool) {
+        SHUTTING_DOWN.store(value, Ordering::Relaxed);
+    }
+    pub fn shutdown_reason() -> DisconnectReason {
+        DisconnectReason::from(SHUTDOWN_REASON.load(Ordering::Relaxed))
+    }
+    pub fn set_shutdown_reason(value: DisconnectReason) {
+        SHUTDOWN_REASON.store(value.to_u8(), Ordering::Relaxed);
+    }
+    // 关闭时等待可靠数据发送完成的宽限期（秒）
+    pub fn shutdown_grace_period() -> f64 {
+        SHUTDOWN_GRACE_PERIOD.load(Ordering::Relaxed)
+    }
+    pub fn set_shutdown_grace_period(value: f64) {
+        SHUTDOWN_GRACE_PERIOD.store(value, Ordering::Relaxed);
+    }
+    // 是否发送 DisconnectMessage 和 ServerRestartMessage
+    // Mirror 客户端收到未注册的消息会断开，只有注册了对应 handler 的客户端才能打开，见 README
+    pub fn shutdown_messages() -> bool {
+        SHUTDOWN_MESSAGES.load(Ordering::Relaxed)
+    }
+    pub fn set_shutdown_messages(value: bool) {
+        SHUTDOWN_MESSAGES.store(value, Ordering::Relaxed);
+    }
+    // 计划重启的时间点 (NetworkTime::local_time)，0 表示没有计划
+    pub fn restart_time() -> f64 {
+        RESTART_TIME.load(Ordering::Relaxed)
+    }
+    pub fn set_restart_time(value: f64) {
+        RESTART_TIME.store(value, Ordering::Relaxed);
+    }
+    pub fn restart_last_countdown() -> u32 {
+        RESTART_LAST_COUNTDOWN.load(Ordering::Relaxed)
+    }
+    pub fn set_restart_last_countdown(value: u32) {
+        RESTART_LAST_COUNTDOWN.store(value, Ordering::Relaxed);
+    }
+    pub fn restart_message() -> String {
+        match RESTART_MESSAGE.try_read() {
+            Ok(restart_message) => restart_message.clone(),
+            Err(e) => {
+                log_error!(format!("Server.restart_message() error: {}", e));
+                String::new()
+            }
+        }
+    }
+    pub fn set_restart_message(value: String) {
+        match RESTART_MESSAGE.write() {
+            Ok(mut restart_message) => {
+                *restart_message = value;
+            }
+            Err(e) => {
+                log_error!(format!("Server.set_restart_message() error: {}", e));
+            }
+        }
+    }
+    // 是否使用不可靠通道 + 确认基线的差分状态同步
+    pub fn unreliable_state_sync() -> bool {
+        UNRELIABLE_STATE_SYNC.load(Ordering::Relaxed)
+    }
+    pub fn set_unreliable_state_sync(value: bool) {
+        UNRELIABLE_STATE_SYNC.store(value, Ordering::Relaxed);
+    }
+    // 每个连接每秒可发送的状态同步字节数，0 表示不限制
+    pub fn bandwidth_budget() -> u32 {
+        BANDWIDTH_BUDGET.load(Ordering::Relaxed)
+    }
+    pub fn set_bandwidth_budget(value: u32) {
+        BANDWIDTH_BUDGET.store(value, Ordering::Relaxed);
+    }
+    // 连接自己拥有的对象的优先级倍数
+    pub fn priority_owner_multiplier() -> f32 {
+        PRIORITY_OWNER_MULTIPLIER.load(Ordering::Relaxed)
+    }
+    pub fn set_priority_owner_multiplier(value: f32) {
+        PRIORITY_OWNER_MULTIPLIER.store(value, Ordering::Relaxed);
+    }
+    // 距离玩家多远时优先级减半
+    pub fn priority_distance_reference() -> f32 {
+        PRIORITY_DISTANCE_REFERENCE.load(Ordering::Relaxed)
+    }
+    pub fn set_priority_distance_reference(value: f32) {
+        PRIORITY_DISTANCE_REFERENCE.store(value, Ordering::Relaxed);
+    }
     pub fn network_connections_size() -> usize {
         NETWORK_CONNECTIONS.len()
     }
@@ -279,9 +386,25 @@ impl NetworkServerStatic {
     pub fn spawned_network_identities() -> &'static DashMap<u32, NetworkIdentity> {
         &SPAWNED_NETWORK_IDENTITIES
     }
+    pub fn additive_scenes() -> &'static DashMap<String, u32> {
+        &ADDITIVE_SCENES
+    }
+    // scene 对象所属的叠加场景，不属于叠加场景时返回 None
+    pub fn additive_scene_of(scene_id: u64) -> Option<String> {
+        if scene_id == 0 {
+            return None;
+        }
+        let scene_hash = (scene_id >> 32) as u32;
+        ADDITIVE_SCENES
+            .iter()
+            .find(|item| *item.value() == scene_hash)
+            .map(|item| item.key().clone())
+    }
     pub fn add_spawned_network_identity(identity: NetworkIdentity) {
-        Self::spawned_network_ids().insert(identity.net_id());
-        SPAWNED_NETWORK_IDENTITIES.insert(identity.net_id(), identity);
+        let net_id = identity.net_id();
+        Self::spawned_network_ids().insert(net_id);
+        SPAWNED_NETWORK_IDENTITIES.insert(net_id, identity);
+        ServerEvents::invoke(&mut ServerEvent::Spawned(net_id));
     }
     pub fn remove_spawned_network_identity(net_id: &u32) {
         if let Some((net_id, sni)) = SPAWNED_NETWORK_IDENTITIES.remove(net_id) {
@@ -377,6 +500,9 @@ impl NetworkServer {
 
         // 注册消息处理器
         Self::register_message_handlers();
+
+        // 读取每个方法的限流设置
+        RateLimiter::load_method_rate_limits(&BackendDataStatic::get_backend_data());
     }
 
     pub fn shutdown() {
@@ -390,10 +516,16 @@ impl NetworkServer {
             NetworkServerStatic::set_active(false);
             NetworkServerStatic::set_initialized(false);
         }
+        NetworkServerStatic::set_shutting_down(false);
+        NetworkServerStatic::set_restart_time(0.0);
         NETWORK_MESSAGE_HANDLERS.clear();
         NetworkServerStatic::network_connections().clear();
         NetworkServerStatic::spawned_network_ids().clear();
         NetworkServerStatic::spawned_network_identities().clear();
+        NetworkServerStatic::additive_scenes().clear();
+        LagCompensation::reset();
+        #[cfg(feature = "rapier")]
+        PhysicsWorld::reset();
         NetworkServerStatic::transport_data_un_batcher()
             .write()
             .unwrap()
@@ -406,6 +538,131 @@ impl NetworkServer {
         });
     }
 
+    // 开始关闭：不再接受新连接，打开 shutdown_messages 时通知所有客户端断开原因
+    pub fn begin_shutdown(reason: DisconnectReason, message: &str) {
+        if NetworkServerStatic::is_shutting_down() {
+            return;
+        }
+        log_info!(format!(
+            "Server.BeginShutdown: reason: {:?}, message: {}",
+            reason, message
+        ));
+        NetworkServerStatic::set_shutting_down(true);
+        NetworkServerStatic::set_shutdown_reason(reason);
+        NetworkServerStatic::set_restart_time(0.0);
+
+        if NetworkServerStatic::active() && NetworkServerStatic::shutdown_messages() {
+            let mut disconnect_message = DisconnectMessage::new(reason, message.to_string());
+            Self::send_to_all(&mut disconnect_message, TransportChannel::Reliable, false);
+        }
+    }
+
+    // 先把可靠数据发送出去，再主动断开剩余的连接，宽限期只作为上限
+    pub fn drain(grace_period: f64) {
+        if !NetworkServerStatic::active() {
+            return;
+        }
+        let deadline = NetworkTime::local_time() + grace_period;
+        let tick_interval = Duration::from_secs_f32(NetworkServerStatic::tick_interval());
+        let mut disconnected = false;
+        loop {
+            // 接收数据，处理客户端断开
+            Self::network_early_update();
+            // 发送缓存的 RPC，刷新每个连接的 batcher
+            NetworkServerStatic::for_each_network_connection(|mut connection| {
+                connection.flush_rpcs();
+                connection.update();
+            });
+            if let Some(active_transport) = Transport::active_transport() {
+                active_transport.server_late_update();
+            }
+            if NetworkServerStatic::network_connections_size() == 0 {
+                break;
+            }
+            // 客户端不会自己断开，所有连接的可靠数据发送完成后主动断开
+            if !disconnected && !Self::has_pending_reliable() {
+                disconnected = true;
+                NetworkServerStatic::for_each_network_connection(|mut connection| {
+                    connection.disconnect();
+                });
+            }
+            if NetworkTime::local_time() >= deadline {
+                log_warn!(format!(
+                    "Server.Drain: grace period of {}s elapsed with {} connections remaining.",
+                    grace_period,
+                    NetworkServerStatic::network_connections_size()
+                ));
+                break;
+            }
+            thread::sleep(tick_interval);
+        }
+    }
+
+    // 是否有连接还有没发送完成的可靠数据
+    fn has_pending_reliable() -> bool {
+        let Some(active_transport) = Transport::active_transport() else {
+            return false;
+        };
+        NetworkServerStatic::network_connections()
+            .iter()
+            .any(|connection| {
+                connection.reliable_rpcs_batch.get_position() > 0
+                    || active_transport.server_reliable_pending(connection.connection_id()) > 0
+            })
+    }
+
+    // 计划重启，在 delay 秒后关闭服务器，打开 shutdown_messages 时向客户端广播倒计时
+    pub fn schedule_restart(delay: f64, message: &str) {
+        if !NetworkServerStatic::active() {
+            log_error!("Server.ScheduleRestart: NetworkServer is not active.");
+            return;
+        }
+        let delay = delay.max(0.0);
+        NetworkServerStatic::set_restart_message(message.to_string());
+        NetworkServerStatic::set_restart_time(NetworkTime::local_time() + delay);
+        let seconds_remaining = delay.ceil() as u32;
+        NetworkServerStatic::set_restart_last_countdown(seconds_remaining);
+        if NetworkServerStatic::shutdown_messages() {
+            let mut restart_message =
+                ServerRestartMessage::new(seconds_remaining, message.to_string());
+            Self::send_to_all(&mut restart_message, TransportChannel::Reliable, false);
+        }
+    }
+
+    // 取消计划重启
+    pub fn cancel_restart() {
+        NetworkServerStatic::set_restart_time(0.0);
+    }
+
+    // 检查计划重启，广播倒计时
+    fn update_scheduled_restart() {
+        let restart_time = NetworkServerStatic::restart_time();
+        if restart_time <= 0.0 {
+            return;
+        }
+        let remaining = restart_time - NetworkTime::local_time();
+        if remaining <= 0.0 {
+            let message = NetworkServerStatic::restart_message();
+            Self::begin_shutdown(DisconnectReason::ServerRestart, message.as_str());
+            NetworkLoop::set_stop(true);
+            return;
+        }
+        // 最后 10 秒每秒广播一次，其余每 30 秒广播一次
+        let seconds_remaining = remaining.ceil() as u32;
+        if seconds_remaining != NetworkServerStatic::restart_last_countdown()
+            && (seconds_remaining <= 10 || seconds_remaining.is_multiple_of(30))
+        {
+            NetworkServerStatic::set_restart_last_countdown(seconds_remaining);
+            if NetworkServerStatic::shutdown_messages() {
+                let mut restart_message = ServerRestartMessage::new(
+                    seconds_remaining,
+                    NetworkServerStatic::restart_message(),
+                );
+                Self::send_to_all(&mut restart_message, TransportChannel::Reliable, false);
+            }
+        }
+    }
+
     // 网络早期更新
     pub fn network_early_update() {
         if NetworkServerStatic::active() {
@@ -442,6 +699,19 @@ impl NetworkServer {
             connection.update_time_interpolation();
         });
 
+        // 为拥有的对象消费客户端输入
+        if NetworkServerStatic::active() {
+            Self::process_inputs();
+        }
+
+        // 消息处理中的拥有者变化
+        NetworkIdentity::invoke_ownership_changes();
+
+        // 应用 tobackend.json 热重载后的设置
+        if BackendDataStatic::take_reloaded() {
+            Self::apply_backend_data_reload();
+        }
+
         if NetworkServerStatic::active() {
             match EARLY_UPDATE_DURATION.try_write() {
                 Ok(mut early_update_duration) => {
@@ -457,6 +727,88 @@ impl NetworkServer {
         }
     }
 
+    // 每个 tick 为每个拥有的组件消费一条输入
+    fn process_inputs() {
+        let mut inputs = Vec::new();
+        NetworkServerStatic::for_each_network_connection(|mut connection| {
+            let conn_id = connection.connection_id();
+            let mut removed = Vec::new();
+            for ((net_id, component_index), buffer) in connection.input_buffers.iter_mut() {
+                // 对象已经销毁或者所有权已经改变
+                let owned =
+                    match NetworkServerStatic::spawned_network_identities().try_get(net_id) {
+                        TryResult::Present(identity) => identity.connection_to_client() == conn_id,
+                        TryResult::Absent => false,
+                        TryResult::Locked => continue,
+                    };
+                if !owned {
+                    removed.push((*net_id, *component_index));
+                    continue;
+                }
+                if let Some((sequence, payload)) = buffer.consume() {
+                    inputs.push((*net_id, *component_index, sequence, payload));
+                }
+            }
+            for key in removed.iter() {
+                connection.input_buffers.remove(key);
+            }
+        });
+
+        // 在连接锁之外调用，on_input 中可以发送消息
+        for (net_id, component_index, sequence, payload) in inputs {
+            match NETWORK_BEHAVIOURS.try_get_mut(&format!("{}_{}", net_id, component_index)) {
+                TryResult::Present(mut network_behaviour) => {
+                    NetworkReaderPool::get_with_bytes_return(payload, |reader| {
+                        network_behaviour.on_input(sequence, reader);
+                    });
+                }
+                TryResult::Absent => {}
+                TryResult::Locked => {
+                    log_warn!(format!(
+                        "Server.ProcessInputs: NetworkBehaviour locked by net_id: {}, component_index: {}",
+                        net_id, component_index
+                    ));
+                }
+            }
+        }
+    }
+
+    // 把热重载后的组件设置应用到已生成的 NetworkBehaviour
+    fn apply_backend_data_reload() {
+        LagCompensation::on_backend_data_reload();
+        let backend_data = BackendDataStatic::get_backend_data();
+        RateLimiter::load_method_rate_limits(&backend_data);
+        NetworkServerStatic::for_each_spawned(|identity| {
+            let components = match identity.asset_id {
+                0 => backend_data
+                    .get_network_identity_data_network_behaviour_components_by_scene_id(
+                        identity.scene_id,
+                    ),
+                asset_id => backend_data
+                    .get_network_identity_data_network_behaviour_components_by_asset_id(asset_id),
+            };
+            for component in components.iter() {
+                match NETWORK_BEHAVIOURS
+                    .try_get_mut(&format!("{}_{}", identity.net_id(), component.index))
+                {
+                    TryResult::Present(mut network_behaviour) => {
+                        network_behaviour
+                            .set_sync_interval(component.network_behaviour_setting.sync_interval);
+                        network_behaviour.on_backend_data_reload(component);
+                    }
+                    TryResult::Absent => {}
+                    TryResult::Locked => {
+                        log_warn!(format!(
+                            "Server.ApplyBackendDataReload: NetworkBehaviour locked by net_id: {}, component_index: {}",
+                            identity.net_id(),
+                            component.index
+                        ));
+                    }
+                }
+            }
+        });
+    }
+
     // 网络更新
     pub fn network_late_update() {
         if NetworkServerStatic::active() {
@@ -471,6 +823,9 @@ impl NetworkServer {
                     ));
                 }
             }
+            Self::update_scheduled_restart();
+            NetworkIdentity::invoke_ownership_changes();
+            LagCompensation::record();
             Self::broadcast();
         }
         if let Some(active_transport) = Transport::active_transport() {
@@ -532,10 +887,8 @@ impl NetworkServer {
             }
 
             if connection.is_ready() {
-                connection.send_network_message(
-                    &mut TimeSnapshotMessage::default(),
-                    TransportChannel::Unreliable,
-                );
+                connection
+                    .send_network_message(&mut TimeSnapshotMessage, TransportChannel::Unreliable);
                 Self::broadcast_to_connection(&mut connection);
             }
             connection.update();
@@ -544,6 +897,22 @@ impl NetworkServer {
 
     // BroadcastToConnection(NetworkConnectionToClient connection)
     fn broadcast_to_connection(conn: &mut NetworkConnectionToClient) {
+        // 最后处理的输入和状态一起发送
+        Self::send_input_acks(conn);
+        // 有带宽预算时按优先级发送
+        let budget = NetworkServerStatic::bandwidth_budget();
+        if NetworkServerStatic::unreliable_state_sync() {
+            Self::broadcast_unreliable_state_to_connection(conn, budget as f64);
+        } else if budget > 0 {
+            Self::broadcast_to_connection_prioritized(conn, budget as f64);
+        } else {
+            Self::broadcast_all_to_connection(conn);
+        }
+        // RPC 在实体状态之后发送，客户端处理 RPC 时状态已经更新
+        conn.flush_rpcs();
+    }
+
+    fn broadcast_all_to_connection(conn: &mut NetworkConnectionToClient) {
         for net_id in conn.observing.to_vec().iter() {
             if *net_id != 0 {
                 if let Some(mut message) =
@@ -559,6 +928,261 @@ impl NetworkServer {
         }
     }
 
+    fn send_input_acks(conn: &mut NetworkConnectionToClient) {
+        let mut acks = Vec::new();
+        for ((net_id, component_index), buffer) in conn.input_buffers.iter_mut() {
+            if buffer.ack_dirty {
+                buffer.ack_dirty = false;
+                acks.push(InputAckMessage::new(
+                    *net_id,
+                    *component_index,
+                    buffer.last_processed,
+                ));
+            }
+        }
+        for mut ack in acks {
+            conn.send_network_message(&mut ack, TransportChannel::Reliable);
+        }
+    }
+
+    // 通过不可靠通道发送相对客户端最后确认基线的差分，没有基线时发送完整状态
+    // budget 大于 0 时和可靠同步一样按优先级在带宽预算内发送
+    fn broadcast_unreliable_state_to_connection(conn: &mut NetworkConnectionToClient, budget: f64) {
+        let tick = NetworkTime::frame_count();
+        let max_unreliable_size = NetworkMessages::max_message_size(TransportChannel::Unreliable);
+        let prioritized = budget > 0.0;
+        let viewer_position = match prioritized {
+            true => Self::refill_bandwidth_credit(conn, budget),
+            false => None,
+        };
+
+        let mut candidates = Vec::new();
+        for net_id in conn.observing.to_vec().iter() {
+            if *net_id == 0 {
+                log_warn!(format!("Server.broadcast_to_connection: identity is null. Removing from observing list. connectionId: {}, netId: {}", conn.connection_id(), net_id));
+                conn.observing.retain(|id| id != net_id);
+                continue;
+            }
+            let state = match NetworkServerStatic::spawned_network_identities().try_get_mut(net_id)
+            {
+                TryResult::Present(mut identity) => {
+                    let owned = identity.connection_to_client() == conn.connection_id();
+                    let serialization = identity.get_server_full_serialization_at_tick(tick);
+                    match owned {
+                        true => serialization.owner_writer.to_bytes(),
+                        false => serialization.observers_writer.to_bytes(),
+                    }
+                }
+                TryResult::Absent => {
+                    log_warn!(format!(
+                        "Server.broadcast_unreliable_state_to_connection: netId {} not found in spawned.",
+                        net_id
+                    ));
+                    continue;
+                }
+                TryResult::Locked => {
+                    log_warn!(format!(
+                        "Server.broadcast_unreliable_state_to_connection: netId {} is locked.",
+                        net_id
+                    ));
+                    continue;
+                }
+            };
+            if state.is_empty() {
+                continue;
+            }
+            if let Some(baseline) = conn.state_baselines.get(net_id) {
+                if baseline.is_synced(&state) {
+                    continue;
+                }
+            }
+            let mut priority = 0.0;
+            if prioritized {
+                priority =
+                    Self::priority_for_connection(*net_id, conn.connection_id(), viewer_position);
+                let accumulator = conn.priority_accumulators.entry(*net_id).or_insert(0.0);
+                *accumulator += priority;
+                priority = *accumulator;
+            }
+            candidates.push((*net_id, priority, state));
+        }
+
+        // 优先级高的先发送
+        if prioritized {
+            candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        }
+
+        let mut sent_any = false;
+        for (net_id, _, state) in candidates {
+            let baseline = conn.state_baselines.entry(net_id).or_default();
+            let mut message = match baseline.acked() {
+                Some((acked_tick, acked_state)) => {
+                    let mut payload = Vec::new();
+                    NetworkWriterPool::get_return(|writer| {
+                        DeltaCompression::compress_bytes(writer, acked_state, &state);
+                        payload = writer.to_bytes();
+                    });
+                    UnreliableStateMessage::new(net_id, tick, Some(*acked_tick), payload)
+                }
+                None => UnreliableStateMessage::new(net_id, tick, None, state.clone()),
+            };
+            let size = message.payload.len() + Self::UNRELIABLE_STATE_MESSAGE_OVERHEAD;
+            // 跳过的对象下次仍然相对已确认的基线发送，不会丢失状态
+            if prioritized && !Self::fits_bandwidth(conn, size, sent_any) {
+                continue;
+            }
+            if let Some(baseline) = conn.state_baselines.get_mut(&net_id) {
+                baseline.add_pending(tick, state);
+            }
+
+            // 超过不可靠通道的大小时改用可靠通道，客户端同样会确认
+            let channel = match size > max_unreliable_size {
+                true => TransportChannel::Reliable,
+                false => TransportChannel::Unreliable,
+            };
+            conn.send_network_message(&mut message, channel);
+            if prioritized {
+                conn.bandwidth_credit -= size as f64;
+                conn.priority_accumulators.insert(net_id, 0.0);
+            }
+            sent_any = true;
+        }
+    }
+
+    // 按优先级在带宽预算内发送，跳过的对象累加优先级，下次优先发送
+    fn broadcast_to_connection_prioritized(conn: &mut NetworkConnectionToClient, budget: f64) {
+        let viewer_position = Self::refill_bandwidth_credit(conn, budget);
+
+        let mut candidates = Vec::new();
+        for net_id in conn.observing.to_vec().iter() {
+            if *net_id == 0 {
+                log_warn!(format!("Server.broadcast_to_connection: identity is null. Removing from observing list. connectionId: {}, netId: {}", conn.connection_id(), net_id));
+                conn.observing.retain(|id| id != net_id);
+                continue;
+            }
+            // 每个 tick 都要序列化，保证 dirty bits 正常清理
+            let message = Self::serialize_for_connection(*net_id, conn.connection_id());
+            let stale = conn.stale_observing.contains(net_id);
+            if message.is_none() && !stale {
+                continue;
+            }
+            let priority =
+                Self::priority_for_connection(*net_id, conn.connection_id(), viewer_position);
+            let accumulator = conn.priority_accumulators.entry(*net_id).or_insert(0.0);
+            *accumulator += priority;
+            candidates.push((*net_id, *accumulator, message));
+        }
+
+        // 优先级高的先发送
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut sent_any = false;
+        for (net_id, _, message) in candidates {
+            let stale = conn.stale_observing.contains(&net_id);
+            let size = match (message, stale) {
+                (Some(mut message), false) => {
+                    let size = message.payload.len() + Self::ENTITY_STATE_MESSAGE_OVERHEAD;
+                    if !Self::fits_bandwidth(conn, size, sent_any) {
+                        // 增量已丢失，之后需要发送完整状态
+                        conn.stale_observing.insert(net_id);
+                        continue;
+                    }
+                    conn.send_network_message(&mut message, TransportChannel::Reliable);
+                    size
+                }
+                _ => {
+                    // 重新发送 SpawnMessage，客户端会对已存在的对象应用完整状态
+                    let spawn_message = match NetworkServerStatic::spawned_network_identities()
+                        .try_get_mut(&net_id)
+                    {
+                        TryResult::Present(mut identity) => {
+                            Self::create_spawn_message(&mut identity, conn)
+                        }
+                        TryResult::Absent => {
+                            log_warn!(format!(
+                                "Server.BroadcastToConnection: netId {} not found in spawned.",
+                                net_id
+                            ));
+                            None
+                        }
+                        TryResult::Locked => {
+                            log_warn!(format!(
+                                "Server.BroadcastToConnection: netId {} is locked.",
+                                net_id
+                            ));
+                            None
+                        }
+                    };
+                    let mut spawn_message = match spawn_message {
+                        None => continue,
+                        Some(spawn_message) => spawn_message,
+                    };
+                    let size = spawn_message.payload.len() + Self::SPAWN_MESSAGE_OVERHEAD;
+                    if !Self::fits_bandwidth(conn, size, sent_any) {
+                        continue;
+                    }
+                    conn.send_network_message(&mut spawn_message, TransportChannel::Reliable);
+                    conn.stale_observing.remove(&net_id);
+                    size
+                }
+            };
+            conn.bandwidth_credit -= size as f64;
+            conn.priority_accumulators.insert(net_id, 0.0);
+            sent_any = true;
+        }
+    }
+
+    // 增加本次发送的预算，返回玩家位置用于计算优先级
+    fn refill_bandwidth_credit(
+        conn: &mut NetworkConnectionToClient,
+        budget: f64,
+    ) -> Option<Vector3<f32>> {
+        // 最多累积 1 秒的预算
+        conn.bandwidth_credit = (conn.bandwidth_credit
+            + budget * NetworkServerStatic::send_interval() as f64)
+            .min(budget);
+
+        match NetworkServerStatic::spawned_network_identities().try_get(&conn.net_id()) {
+            TryResult::Present(identity) => Some(identity.game_object().transform.position),
+            _ => None,
+        }
+    }
+
+    // EntityStateMessage 的头部大小估算：消息 id + net_id + payload 长度
+    const ENTITY_STATE_MESSAGE_OVERHEAD: usize = 2 + 5 + 5;
+    // UnreliableStateMessage 的头部大小估算：消息 id + net_id + tick + 基线 tick + payload 长度
+    const UNRELIABLE_STATE_MESSAGE_OVERHEAD: usize = 16;
+    // SpawnMessage 除 payload 以外的大小估算
+    const SPAWN_MESSAGE_OVERHEAD: usize = 64;
+
+    // 预算不足时，第一个对象只要还有预算就发送，避免大对象永远发不出去
+    fn fits_bandwidth(conn: &NetworkConnectionToClient, size: usize, sent_any: bool) -> bool {
+        size as f64 <= conn.bandwidth_credit || (!sent_any && conn.bandwidth_credit > 0.0)
+    }
+
+    // 计算 identity 对连接的优先级：拥有的对象和附近的对象优先
+    fn priority_for_connection(
+        net_id: u32,
+        conn_id: u64,
+        viewer_position: Option<Vector3<f32>>,
+    ) -> f32 {
+        match NetworkServerStatic::spawned_network_identities().try_get(&net_id) {
+            TryResult::Present(identity) => {
+                let mut priority = 1.0;
+                if identity.connection_to_client() == conn_id {
+                    priority *= NetworkServerStatic::priority_owner_multiplier();
+                }
+                if let Some(viewer_position) = viewer_position {
+                    let reference = NetworkServerStatic::priority_distance_reference().max(0.001);
+                    let distance = (identity.game_object().transform.position - viewer_position).norm();
+                    priority *= reference / (reference + distance);
+                }
+                priority
+            }
+            _ => 1.0,
+        }
+    }
+
     // SerializeForConnection
     fn serialize_for_connection(net_id: u32, conn_id: u64) -> Option<EntityStateMessage> {
         match NetworkServerStatic::spawned_network_identities().try_get_mut(&net_id) {
@@ -648,15 +1272,27 @@ impl NetworkServer {
         identity: &mut NetworkIdentity,
     ) {
         if conn.is_ready() {
+            // 先发送已缓存的 RPC，客户端隐藏对象前处理完这些 RPC
+            conn.flush_rpcs();
             let mut message = ObjectHideMessage::new(identity.net_id());
             conn.send_network_message(&mut message, TransportChannel::Reliable);
         }
     }
 
     fn send_spawn_message(identity: &mut NetworkIdentity, conn: &mut NetworkConnectionToClient) {
+        if let Some(mut spawn_message) = Self::create_spawn_message(identity, conn) {
+            // 发送 SpawnMessage
+            conn.send_network_message(&mut spawn_message, TransportChannel::Reliable);
+        }
+    }
+
+    fn create_spawn_message(
+        identity: &mut NetworkIdentity,
+        conn: &NetworkConnectionToClient,
+    ) -> Option<SpawnMessage> {
         // 找到 NetworkIdentity
         if identity.server_only {
-            return;
+            return None;
         }
 
         // 是否是所有者
@@ -665,8 +1301,8 @@ impl NetworkServer {
         let is_local_player = conn.net_id() == identity.net_id();
         // 创建 SpawnMessage 的 payload
         let payload = Self::create_spawn_message_payload(is_owner, identity);
-        // 发送 SpawnMessage
-        let mut spawn_message = SpawnMessage::new(
+        // 创建 SpawnMessage
+        Some(SpawnMessage::new(
             identity.net_id(),
             is_local_player,
             is_owner,
@@ -676,9 +1312,7 @@ impl NetworkServer {
             identity.game_object().transform.local_rotation,
             identity.game_object().transform.local_scale,
             payload,
-        );
-        // 发送 SpawnMessage
-        conn.send_network_message(&mut spawn_message, TransportChannel::Reliable);
+        ))
     }
 
     fn create_spawn_message_payload(is_owner: bool, identity: &mut NetworkIdentity) -> Vec<u8> {
@@ -763,6 +1397,17 @@ impl NetworkServer {
             return;
         }
 
+        if NetworkServerStatic::is_shutting_down() {
+            log_warn!(format!(
+                "Server.HandleConnect: server is shutting down. Disconnecting connectionId: {}",
+                connection_id
+            ));
+            if let Some(transport) = Transport::active_transport() {
+                transport.server_disconnect(connection_id);
+            }
+            return;
+        }
+
         if NetworkServerStatic::network_connections_size() >= NetworkServerStatic::max_connections()
         {
             log_error!(format!(
@@ -788,6 +1433,10 @@ impl NetworkServer {
             match NetworkServerStatic::network_connections().try_get_mut(&connection_id) {
                 // 如果有连接
                 TryResult::Present(mut connection) => {
+                    // 已断开的连接不再处理数据
+                    if connection.disconnected {
+                        return;
+                    }
                     // 添加数据到 transport_data_un_batcher
                     if !transport_data_un_batcher.add_batch_with_bytes(data) {
                         if NetworkServerStatic::exceptions_disconnect() {
@@ -832,10 +1481,19 @@ impl NetworkServer {
             }
 
             // 处理消息
-            while let Some((message, remote_time_stamp)) =
-                transport_data_un_batcher.get_next_message()
-            {
-                NetworkReaderPool::get_with_array_segment_return(&message, |reader| {
+            let mut read_error = None;
+            let mut disconnected = false;
+            loop {
+                let (message, remote_time_stamp) =
+                    match transport_data_un_batcher.get_next_message() {
+                        Ok(Some(next)) => next,
+                        Ok(None) => break,
+                        Err(e) => {
+                            read_error = Some(e);
+                            break;
+                        }
+                    };
+                NetworkReaderPool::get_with_array_segment_return(message, |reader| {
                     match reader.remaining() >= NetworkMessages::ID_SIZE {
                         // 如果消息长度大于 NetworkMessages::ID_SIZE
                         true => {
@@ -844,7 +1502,22 @@ impl NetworkServer {
                                 .try_get_mut(&connection_id)
                             {
                                 TryResult::Present(mut connection) => {
+                                    // 限流或读取失败断开后不再处理剩余的消息
+                                    if connection.disconnected {
+                                        disconnected = true;
+                                        return;
+                                    }
                                     connection.set_remote_time_stamp(remote_time_stamp);
+                                    // 总消息限流
+                                    if !connection.rate_limiter.check_message()
+                                        && Self::on_rate_limited(
+                                            &mut connection,
+                                            "message rate limit exceeded",
+                                        )
+                                    {
+                                        disconnected = connection.disconnected;
+                                        return;
+                                    }
                                 }
                                 TryResult::Absent => {
                                     log_error!(format!(
@@ -870,6 +1543,7 @@ impl NetworkServer {
                                     {
                                         TryResult::Present(mut connection) => {
                                             connection.disconnect();
+                                            disconnected = true;
                                         }
                                         TryResult::Absent => {
                                             log_error!(format!(
@@ -887,7 +1561,6 @@ impl NetworkServer {
                                 } else {
                                     log_warn!(format!("Server.HandleData: connectionId: {} failed to unpack and invoke message.", connection_id));
                                 }
-                                return;
                             }
                         }
                         // 如果消息长度小于 NetworkMessages::ID_SIZE
@@ -899,6 +1572,7 @@ impl NetworkServer {
                                 {
                                     TryResult::Present(mut connection) => {
                                         connection.disconnect();
+                                        disconnected = true;
                                     }
                                     TryResult::Absent => {
                                         log_error!(format!(
@@ -919,10 +1593,25 @@ impl NetworkServer {
                                     connection_id
                                 ));
                             }
-                            return;
                         }
                     }
                 });
+                if disconnected {
+                    break;
+                }
+            }
+
+            // 限流或读取失败断开，丢弃剩余数据
+            if disconnected {
+                transport_data_un_batcher.clear();
+                return;
+            }
+
+            // 批次数据不完整，丢弃剩余数据
+            if let Some(e) = read_error {
+                transport_data_un_batcher.clear();
+                Self::on_read_error(connection_id, "batch", e);
+                return;
             }
 
             if transport_data_un_batcher.batches_count() > 0 {
@@ -935,38 +1624,115 @@ impl NetworkServer {
         }
     }
 
-    fn unpack_and_invoke(
-        connection_id: u64,
-        reader: &mut NetworkReader,
-        channel: TransportChannel,
-    ) -> bool {
-        // 解包消息id
-        let message_id = NetworkMessages::unpack_id(reader);
-        // 如果消息id在 NETWORK_MESSAGE_HANDLERS 中
-        if let Some(handler) = NETWORK_MESSAGE_HANDLERS.get(&message_id) {
-            (handler.func)(connection_id, reader, channel);
-            match NetworkServerStatic::network_connections().try_get_mut(&connection_id) {
-                TryResult::Present(mut connection) => {
-                    connection.set_last_message_time(NetworkTime::local_time());
-                }
-                TryResult::Absent => {
-                    log_error!(format!(
-                        "Server.HandleData: connectionId: {} not found.",
-                        connection_id
-                    ));
-                }
-                TryResult::Locked => {
-                    log_error!(format!(
-                        "Server.HandleData: connectionId: {} is locked.",
-                        connection_id
-                    ));
-                }
-            }
-            return true;
+    // 读取失败时按 exceptions_disconnect 断开连接
+    fn on_read_error(connection_id: u64, name: &str, error: ReadError) {
+        if !NetworkServerStatic::exceptions_disconnect() {
+            log_warn!(format!(
+                "Server.HandleData: connectionId: {} failed to read {}: {}",
+                connection_id, name, error
+            ));
+            return;
         }
-        log_warn!(format!(
-            "Server.HandleData: connectionId: {} unknown message id: {}",
-            connection_id, message_id
+        log_error!(format!(
+            "Server.HandleData: connectionId: {} failed to read {}: {}. Disconnecting.",
+            connection_id, name, error
+        ));
+        match NetworkServerStatic::network_connections().try_get_mut(&connection_id) {
+            TryResult::Present(mut connection) => {
+                connection.disconnect();
+            }
+            TryResult::Absent => {}
+            TryResult::Locked => {
+                log_error!(format!(
+                    "Server.HandleData: connectionId: {} is locked.",
+                    connection_id
+                ));
+            }
+        }
+    }
+
+    // 反序列化消息，失败时调用 on_read_error
+    pub fn read_message<T: NetworkMessageTrait>(
+        connection_id: u64,
+        reader: &mut NetworkReader,
+    ) -> Option<T> {
+        match T::deserialize(reader) {
+            Ok(message) => Some(message),
+            Err(e) => {
+                Self::on_read_error(connection_id, T::get_full_name(), e);
+                None
+            }
+        }
+    }
+
+    // 超出限流时按策略处理，返回 true 表示丢弃消息
+    fn on_rate_limited(connection: &mut NetworkConnectionToClient, reason: &str) -> bool {
+        match RateLimiter::policy() {
+            RateLimitPolicy::Drop => {
+                log_debug!(format!(
+                    "Server.RateLimit: connectionId: {} {}. Dropping.",
+                    connection.connection_id(),
+                    reason
+                ));
+                true
+            }
+            RateLimitPolicy::Warn => {
+                log_warn!(format!(
+                    "Server.RateLimit: connectionId: {} {}.",
+                    connection.connection_id(),
+                    reason
+                ));
+                false
+            }
+            RateLimitPolicy::Disconnect => {
+                log_warn!(format!(
+                    "Server.RateLimit: connectionId: {} {}. Disconnecting.",
+                    connection.connection_id(),
+                    reason
+                ));
+                RateLimiter::add_disconnect();
+                connection.rate_limiter.disconnected = true;
+                connection.disconnect();
+                true
+            }
+        }
+    }
+
+    fn unpack_and_invoke(
+        connection_id: u64,
+        reader: &mut NetworkReader,
+        channel: TransportChannel,
+    ) -> bool {
+        // 解包消息id
+        let message_id = NetworkMessages::unpack_id(reader);
+        // 如果消息id在 NETWORK_MESSAGE_HANDLERS 中
+        let func = NETWORK_MESSAGE_HANDLERS
+            .get(&message_id)
+            .map(|handler| handler.func.clone());
+        if let Some(func) = func {
+            NetworkMessageHandler::invoke(&func, connection_id, reader, channel);
+            match NetworkServerStatic::network_connections().try_get_mut(&connection_id) {
+                TryResult::Present(mut connection) => {
+                    connection.set_last_message_time(NetworkTime::local_time());
+                }
+                TryResult::Absent => {
+                    log_error!(format!(
+                        "Server.HandleData: connectionId: {} not found.",
+                        connection_id
+                    ));
+                }
+                TryResult::Locked => {
+                    log_error!(format!(
+                        "Server.HandleData: connectionId: {} is locked.",
+                        connection_id
+                    ));
+                }
+            }
+            return true;
+        }
+        log_warn!(format!(
+            "Server.HandleData: connectionId: {} unknown message id: {}",
+            connection_id, message_id
         ));
         false
     }
@@ -976,11 +1742,9 @@ impl NetworkServer {
         if let Some((_, mut connection)) =
             NetworkServerStatic::network_connections().remove(&connection_id)
         {
-            if let Some(on_disconnected_event) =
-                NetworkServerStatic::connected_event().get(&EventHandlerType::OnDisconnectedEvent)
-            {
-                on_disconnected_event(&mut connection, TransportError::None);
-            } else {
+            ServerEvents::invoke(&mut ServerEvent::Disconnected(&mut connection));
+            // 订阅者先处理，之后默认销毁玩家，不需要时调用 set_destroy_player_on_disconnect(false)
+            if NetworkServerStatic::destroy_player_on_disconnect() {
                 Self::destroy_player_for_connection(&mut connection);
             }
             connection.cleanup();
@@ -1007,9 +1771,9 @@ impl NetworkServer {
         }
 
         if identity.scene_id != 0 {
-            Self::un_spawn_internal(conn, identity, true);
+            Self::un_spawn_internal(Some(conn), identity, true);
         } else {
-            Self::un_spawn_internal(conn, identity, false);
+            Self::un_spawn_internal(Some(conn), identity, false);
             identity.destroy_called = true;
         }
     }
@@ -1115,11 +1879,16 @@ impl NetworkServer {
 
     // UnSpawn
     pub fn un_spawn(conn: &mut NetworkConnectionToClient, identity: &mut NetworkIdentity) {
-        Self::un_spawn_internal(conn, identity, true);
+        Self::un_spawn_internal(Some(conn), identity, true);
+    }
+
+    // 没有发起的连接时卸载对象，例如卸载叠加场景
+    pub fn un_spawn_identity(identity: &mut NetworkIdentity) {
+        Self::un_spawn_internal(None, identity, true);
     }
 
     fn un_spawn_internal(
-        conn: &mut NetworkConnectionToClient,
+        mut conn: Option<&mut NetworkConnectionToClient>,
         identity: &mut NetworkIdentity,
         reset_state: bool,
     ) {
@@ -1136,7 +1905,16 @@ impl NetworkServer {
         }
 
         // 移除 NetworkIdentity
-        conn.remove_owned_object(identity.net_id());
+        match conn.as_deref_mut() {
+            Some(conn) => conn.remove_owned_object(identity.net_id()),
+            None => {
+                if let TryResult::Present(mut owner) = NetworkServerStatic::network_connections()
+                    .try_get_mut(&identity.connection_to_client())
+                {
+                    owner.remove_owned_object(identity.net_id());
+                }
+            }
+        }
 
         Self::send_to_observers(
             conn,
@@ -1149,6 +1927,8 @@ impl NetworkServer {
 
         identity.on_stop_server();
 
+        ServerEvents::invoke(&mut ServerEvent::UnSpawned(identity.net_id()));
+
         if reset_state {
             identity.reset_state();
             identity.set_active(false);
@@ -1156,12 +1936,12 @@ impl NetworkServer {
     }
 
     fn send_to_observers(
-        conn: &mut NetworkConnectionToClient,
+        mut conn: Option<&mut NetworkConnectionToClient>,
         identity: &mut NetworkIdentity,
         mut message: ObjectDestroyMessage,
         channel: TransportChannel,
     ) {
-        if identity.is_null() || identity.observers().len() == 0 {
+        if identity.is_null() || identity.observers().is_empty() {
             return;
         }
 
@@ -1175,13 +1955,18 @@ impl NetworkServer {
                 return;
             }
 
+            // 先发送已缓存的 RPC，客户端销毁对象前处理完这些 RPC
             for conn_id in identity.observers().iter() {
                 match NetworkServerStatic::network_connections().try_get_mut(conn_id) {
                     TryResult::Present(mut connection) => {
+                        connection.flush_rpcs();
                         connection.send(segment, channel);
                     }
                     TryResult::Absent => {
-                        conn.send(segment, channel);
+                        if let Some(conn) = conn.as_deref_mut() {
+                            conn.flush_rpcs();
+                            conn.send(segment, channel);
+                        }
                     }
                     TryResult::Locked => {
                         log_error!("Server.SendToObservers: connection is locked.");
@@ -1222,7 +2007,7 @@ impl NetworkServer {
     }
 
     pub fn add_player_for_connection(conn_id: u64, player: &GameObject) -> bool {
-        match Self::init_identity_by_game_obj(conn_id, &player) {
+        match Self::init_identity_by_game_obj(conn_id, player) {
             None => {
                 log_warn!(format!("AddPlayer: player GameObject has no NetworkIdentity. Please add a NetworkIdentity to {:?}",1));
                 false
@@ -1407,7 +2192,7 @@ impl NetworkServer {
             // 获取活动的场景id
             let scene_id = BackendDataStatic::get_backend_data()
                 .get_scene_id_by_scene_name(NetworkManagerStatic::network_scene_name().as_str())
-                .unwrap_or_else(|| 0);
+                .unwrap_or(0);
             if identity.scene_id != 0 && identity.scene_id == scene_id {
                 identity.set_active(true);
                 let conn_id = identity.connection_to_client();
@@ -1416,6 +2201,291 @@ impl NetworkServer {
         }
     }
 
+    fn is_scene_loaded_for_connection(
+        additive_scene: &Option<String>,
+        connection: &NetworkConnectionToClient,
+    ) -> bool {
+        match additive_scene {
+            Some(scene_name) => connection.loaded_scenes.contains(scene_name),
+            None => true,
+        }
+    }
+
+    // 在服务器加载叠加场景并生成场景中的对象
+    pub fn load_additive_scene(scene_name: &str) -> bool {
+        if !NetworkServerStatic::active() {
+            log_error!(format!("LoadAdditiveScene for {}, NetworkServer is not active.", scene_name));
+            return false;
+        }
+        if NetworkServerStatic::additive_scenes().contains_key(scene_name) {
+            log_warn!(format!("LoadAdditiveScene: scene {} is already loaded", scene_name));
+            return false;
+        }
+
+        let backend_data = BackendDataStatic::get_backend_data();
+        let scene_hash = match backend_data.get_scene_hash_by_scene_name(scene_name) {
+            Some(scene_hash) => scene_hash,
+            None => {
+                log_error!(format!(
+                    "LoadAdditiveScene: scene {} not found in backend data",
+                    scene_name
+                ));
+                return false;
+            }
+        };
+        NetworkServerStatic::additive_scenes().insert(scene_name.to_string(), scene_hash);
+
+        // 逐个创建并生成，避免未分配 net_id 的组件互相覆盖
+        for scene_object_name in backend_data.get_scene_object_names_by_scene_hash(scene_hash) {
+            let mut game_object = GameObject::new_with_scene_name(scene_object_name);
+            if let Some(mut identity) = game_object.get_identity_by_scene_name() {
+                let spawned = NetworkServerStatic::spawned_network_identities()
+                    .iter()
+                    .any(|item| item.scene_id == identity.scene_id);
+                if spawned {
+                    NETWORK_BEHAVIOURS::remove_behaviour(
+                        identity.net_id(),
+                        identity.network_behaviours_count,
+                    );
+                    continue;
+                }
+                identity.set_active(true);
+                let conn_id = identity.connection_to_client();
+                Self::spawn(identity, conn_id);
+            }
+        }
+        ServerEvents::invoke(&mut ServerEvent::SceneChanged {
+            scene_name,
+            operation: SceneOperation::LoadAdditive,
+        });
+        true
+    }
+
+    // 在服务器卸载叠加场景，销毁场景中的对象并通知已加载该场景的客户端
+    pub fn unload_additive_scene(scene_name: &str) -> bool {
+        let scene_hash = match NetworkServerStatic::additive_scenes().remove(scene_name) {
+            Some((_, scene_hash)) => scene_hash,
+            None => {
+                log_warn!(format!("UnloadAdditiveScene: scene {} is not loaded", scene_name));
+                return false;
+            }
+        };
+
+        let net_ids: Vec<u32> = NetworkServerStatic::spawned_network_identities()
+            .iter()
+            .filter(|item| item.scene_id != 0 && (item.scene_id >> 32) as u32 == scene_hash)
+            .map(|item| item.net_id())
+            .collect();
+        for net_id in net_ids {
+            if let Some((_, mut identity)) =
+                NetworkServerStatic::spawned_network_identities().remove(&net_id)
+            {
+                let network_behaviours_count = identity.network_behaviours_count;
+                Self::un_spawn_identity(&mut identity);
+                NETWORK_BEHAVIOURS::remove_behaviour(net_id, network_behaviours_count);
+                NetworkServerStatic::spawned_network_ids().remove(&net_id);
+            }
+        }
+
+        NetworkServerStatic::for_each_network_connection(|mut connection| {
+            if connection.loaded_scenes.remove(scene_name) {
+                let mut scene_message = SceneMessage::new(
+                    scene_name.to_string(),
+                    SceneOperation::UnloadAdditive,
+                    false,
+                );
+                connection.send_network_message(&mut scene_message, TransportChannel::Reliable);
+            }
+        });
+        ServerEvents::invoke(&mut ServerEvent::SceneChanged {
+            scene_name,
+            operation: SceneOperation::UnloadAdditive,
+        });
+        true
+    }
+
+    // 让客户端加载叠加场景，并显示场景中的对象
+    pub fn load_additive_scene_for_connection(conn_id: u64, scene_name: &str) -> bool {
+        let scene_hash = match NetworkServerStatic::additive_scenes().get(scene_name) {
+            Some(scene_hash) => *scene_hash,
+            None => {
+                log_error!(format!(
+                    "LoadAdditiveSceneForConnection: scene {} is not loaded on server",
+                    scene_name
+                ));
+                return false;
+            }
+        };
+
+        let is_ready = match NetworkServerStatic::network_connections().try_get_mut(&conn_id) {
+            TryResult::Present(mut connection) => {
+                if !connection.loaded_scenes.insert(scene_name.to_string()) {
+                    return false;
+                }
+                let mut scene_message = SceneMessage::new(
+                    scene_name.to_string(),
+                    SceneOperation::LoadAdditive,
+                    false,
+                );
+                connection.send_network_message(&mut scene_message, TransportChannel::Reliable);
+                connection.is_ready()
+            }
+            TryResult::Absent => {
+                log_error!(format!(
+                    "LoadAdditiveSceneForConnection: connectionId {} not found in connections",
+                    conn_id
+                ));
+                return false;
+            }
+            TryResult::Locked => {
+                log_error!(format!(
+                    "LoadAdditiveSceneForConnection: connectionId {} is locked",
+                    conn_id
+                ));
+                return false;
+            }
+        };
+
+        // 未准备就绪的连接在 set_client_ready 时生成观察者
+        if is_ready {
+            NetworkServerStatic::for_each_spawned(|mut identity| {
+                if identity.scene_id != 0
+                    && (identity.scene_id >> 32) as u32 == scene_hash
+                    && identity.visibility != Visibility::ForceHidden
+                {
+                    identity.add_observer(conn_id);
+                }
+            });
+        }
+        true
+    }
+
+    // 让客户端卸载叠加场景，并隐藏场景中的对象
+    pub fn unload_additive_scene_for_connection(conn_id: u64, scene_name: &str) -> bool {
+        let scene_hash = match NetworkServerStatic::additive_scenes().get(scene_name) {
+            Some(scene_hash) => *scene_hash,
+            None => {
+                log_error!(format!(
+                    "UnloadAdditiveSceneForConnection: scene {} is not loaded on server",
+                    scene_name
+                ));
+                return false;
+            }
+        };
+
+        match NetworkServerStatic::network_connections().try_get_mut(&conn_id) {
+            TryResult::Present(mut connection) => {
+                if !connection.loaded_scenes.remove(scene_name) {
+                    return false;
+                }
+                NetworkServerStatic::for_each_spawned(|mut identity| {
+                    if identity.scene_id != 0
+                        && (identity.scene_id >> 32) as u32 == scene_hash
+                        && identity.observers().contains(&conn_id)
+                    {
+                        identity.remove_observer(conn_id);
+                        connection.remove_from_observing(&mut identity, false);
+                    }
+                });
+                let mut scene_message = SceneMessage::new(
+                    scene_name.to_string(),
+                    SceneOperation::UnloadAdditive,
+                    false,
+                );
+                connection.send_network_message(&mut scene_message, TransportChannel::Reliable);
+                true
+            }
+            TryResult::Absent => {
+                log_error!(format!(
+                    "UnloadAdditiveSceneForConnection: connectionId {} not found in connections",
+                    conn_id
+                ));
+                false
+            }
+            TryResult::Locked => {
+                log_error!(format!(
+                    "UnloadAdditiveSceneForConnection: connectionId {} is locked",
+                    conn_id
+                ));
+                false
+            }
+        }
+    }
+
+    // 按 prefab 名称生成对象，init 在第一条 SpawnMessage 之前执行，用于设置初始状态
+    pub fn spawn_prefab<F>(
+        prefab: &str,
+        transform: Transform,
+        owner: Option<u64>,
+        init: F,
+    ) -> Option<u32>
+    where
+        F: FnOnce(&mut NetworkIdentity),
+    {
+        if !NetworkServerStatic::active() {
+            log_error!(format!("SpawnPrefab for {}, NetworkServer is not active. Cannot spawn objects without an active server.", prefab));
+            return None;
+        }
+
+        let mut game_object = GameObject::new_with_prefab(prefab.to_string());
+        game_object.transform = transform;
+        game_object.set_active(true);
+
+        let mut identity = match game_object.get_identity_by_prefab() {
+            Some(identity) => identity,
+            None => {
+                log_error!(format!(
+                    "SpawnPrefab: prefab {} not found in backend data",
+                    prefab
+                ));
+                return None;
+            }
+        };
+
+        if let Some(conn_id) = owner {
+            if !NetworkServerStatic::network_connections().contains_key(&conn_id) {
+                log_error!(format!(
+                    "SpawnPrefab: owner connectionId {} not found in connections",
+                    conn_id
+                ));
+                NETWORK_BEHAVIOURS::remove_behaviour(
+                    identity.net_id(),
+                    identity.network_behaviours_count,
+                );
+                return None;
+            }
+        }
+
+        // 先分配 net_id，保证 init 中 get_component 能找到组件
+        let net_id = NetworkIdentity::get_static_next_network_id();
+        identity.set_net_id(net_id);
+
+        // 先设置拥有者，init 中可以读取 connection_to_client
+        if let Some(conn_id) = owner {
+            identity.set_connection_to_client(conn_id);
+        }
+
+        // 初始状态
+        init(&mut identity);
+
+        let network_behaviours_count = identity.network_behaviours_count;
+        Self::spawn_object(identity, owner.unwrap_or(0));
+        if NetworkServerStatic::spawned_network_ids().contains(&net_id) {
+            return Some(net_id);
+        }
+
+        // 没有生成时移除组件和拥有关系
+        NETWORK_BEHAVIOURS::remove_behaviour(net_id, network_behaviours_count);
+        if let Some(conn_id) = owner {
+            if let TryResult::Present(mut conn) =
+                NetworkServerStatic::network_connections().try_get_mut(&conn_id)
+            {
+                conn.remove_owned_object(net_id);
+            }
+        }
+        None
+    }
+
     fn spawn(identity: NetworkIdentity, conn_id: u64) {
         Self::spawn_object(identity, conn_id);
     }
@@ -1440,38 +2510,29 @@ impl NetworkServer {
             return;
         }
 
-        // 如果 identity 的 net_id 为 0
+        // 必须先分配 NetworkIdentity 的 net_id 再设置连接的 NetworkIdentity
+        // spawn_prefab 会提前分配 net_id，保证 init 中能找到组件
         if identity.net_id() == 0 {
-            // 必须先分配 NetworkIdentity 的 net_id 再设置连接的 NetworkIdentity
-            // 分配 NetworkIdentity 的 net_id
             identity.set_net_id(NetworkIdentity::get_static_next_network_id());
+        }
 
-            // 设置连接的 NetworkIdentity
-            identity.set_connection_to_client(conn_id);
-
-            // identity.on_start_server();
-            identity.on_start_server();
-
-            // 重建观察者
-            Self::rebuild_observers(&mut identity, true);
+        // 设置连接的 NetworkIdentity
+        identity.set_connection_to_client(conn_id);
 
-            // 添加到 SPAWNED 中
-            NetworkServerStatic::add_spawned_network_identity(identity);
+        identity.on_start_server();
+        // on_start_authority 需要在 on_start_server 之后
+        identity.notify_authority();
 
-            return;
-        }
-
-        // TODO aoi
+        // 重建观察者
         Self::rebuild_observers(&mut identity, true);
+
+        // 添加到 SPAWNED 中
+        NetworkServerStatic::add_spawned_network_identity(identity);
     }
 
     fn rebuild_observers(identity: &mut NetworkIdentity, initialize: bool) {
-        // TODO aoi
-        if "aoi" == "aoi" || identity.visibility == ForceShown {
-            Self::rebuild_observers_default(identity, initialize);
-        } else {
-            // TODO aoi
-        }
+        // TODO aoi，目前所有对象都按 ForceShown 处理
+        Self::rebuild_observers_default(identity, initialize);
     }
 
     fn rebuild_observers_default(identity: &mut NetworkIdentity, initialize: bool) {
@@ -1486,9 +2547,12 @@ impl NetworkServer {
     }
 
     fn add_all_ready_server_connections_to_observers(identity: &mut NetworkIdentity) {
+        let additive_scene = NetworkServerStatic::additive_scene_of(identity.scene_id);
         let mut conn_ids = Vec::new();
         NetworkServerStatic::for_each_network_connection(|connection| {
-            if connection.is_ready() {
+            if connection.is_ready()
+                && Self::is_scene_loaded_for_connection(&additive_scene, &connection)
+            {
                 conn_ids.push(connection.connection_id());
             }
         });
@@ -1530,15 +2594,10 @@ impl NetworkServer {
 
     // 处理 TransportError 消息
     fn on_transport_error(connection_id: u64, transport_error: TransportError) {
-        match NetworkServerStatic::network_connections().try_get_mut(&connection_id) {
-            TryResult::Present(mut connection) => {
-                if let Some(on_error_event) =
-                    NetworkServerStatic::connected_event().get(&EventHandlerType::OnErrorEvent)
-                {
-                    on_error_event(&mut connection, transport_error);
-                }
-            }
-            _ => {}
+        if let TryResult::Present(mut connection) =
+            NetworkServerStatic::network_connections().try_get_mut(&connection_id)
+        {
+            ServerEvents::invoke(&mut ServerEvent::Error(&mut connection, transport_error));
         }
     }
 
@@ -1548,26 +2607,20 @@ impl NetworkServer {
             "Server.HandleTransportException: connectionId: {}, error: {:?}",
             connection_id, transport_error
         ));
-        match NetworkServerStatic::network_connections().try_get_mut(&connection_id) {
-            TryResult::Present(mut connection) => {
-                if let Some(on_exception_event) = NetworkServerStatic::connected_event()
-                    .get(&EventHandlerType::OnTransportExceptionEvent)
-                {
-                    on_exception_event(&mut connection, transport_error);
-                }
-            }
-            _ => {}
+        if let TryResult::Present(mut connection) =
+            NetworkServerStatic::network_connections().try_get_mut(&connection_id)
+        {
+            ServerEvents::invoke(&mut ServerEvent::TransportException(
+                &mut connection,
+                transport_error,
+            ));
         }
     }
 
     // 处理 Connected 消息
     fn on_connected(mut conn: NetworkConnectionToClient) {
-        // 如果有 OnConnectedEvent
-        if let Some(on_connected_event) =
-            NetworkServerStatic::connected_event().get(&EventHandlerType::OnConnectedEvent)
-        {
-            on_connected_event(&mut conn, TransportError::None);
-        } else {
+        // 调用 OnConnectedEvent 的订阅者
+        if !ServerEvents::invoke(&mut ServerEvent::Connected(&mut conn)) {
             log_warn!("OnConnectedEvent is null");
         }
         // 添加连接 到 NETWORK_CONNECTIONS
@@ -1590,6 +2643,175 @@ impl NetworkServer {
         Self::register_handler::<EntityStateMessage>(Self::on_entity_state_message, true);
         // 注册 TimeSnapshotMessage 处理程序
         Self::register_handler::<TimeSnapshotMessage>(Self::on_time_snapshot_message, true);
+        // 注册 StateAckMessage 处理程序
+        Self::register_handler::<StateAckMessage>(Self::on_state_ack_message, true);
+        // 注册 InputMessage 处理程序
+        Self::register_handler::<InputMessage>(Self::on_input_message, true);
+        // 注册 RequestAuthorityMessage 处理程序
+        Self::register_handler::<RequestAuthorityMessage>(Self::on_request_authority_message, true);
+    }
+
+    // 处理 RequestAuthorityMessage 消息，由 AuthorityPolicy 决定是否转移权限
+    fn on_request_authority_message(
+        connection_id: u64,
+        reader: &mut NetworkReader,
+        _channel: TransportChannel,
+    ) {
+        let message = match Self::read_message::<RequestAuthorityMessage>(connection_id, reader) {
+            Some(message) => message,
+            None => return,
+        };
+        match NetworkServerStatic::spawned_network_identities().try_get_mut(&message.net_id) {
+            TryResult::Present(mut identity) => {
+                let owner = identity.connection_to_client();
+                if message.release {
+                    if owner != connection_id {
+                        log_warn!(format!(
+                            "RequestAuthority: connection {} tried to release {} without authority.",
+                            connection_id, message.net_id
+                        ));
+                        return;
+                    }
+                    if AuthorityPolicyStatic::release_authority(connection_id, &identity) {
+                        identity.remove_client_authority();
+                    }
+                    return;
+                }
+
+                if owner == connection_id {
+                    return;
+                }
+                // 玩家对象的权限不能转移
+                let is_player = match NetworkServerStatic::network_connections().try_get(&owner) {
+                    TryResult::Present(conn) => conn.net_id() == message.net_id,
+                    _ => false,
+                };
+                if is_player {
+                    log_warn!(format!(
+                        "RequestAuthority: connection {} tried to take player object {}.",
+                        connection_id, message.net_id
+                    ));
+                    return;
+                }
+                if !AuthorityPolicyStatic::request_authority(connection_id, &identity) {
+                    log_debug!(format!(
+                        "RequestAuthority: connection {} denied authority over {}.",
+                        connection_id, message.net_id
+                    ));
+                    return;
+                }
+                if owner != 0 {
+                    identity.remove_client_authority();
+                }
+                identity.assign_client_authority(connection_id);
+            }
+            TryResult::Absent => {
+                log_warn!(format!(
+                    "RequestAuthority: netId {} not found in spawned",
+                    message.net_id
+                ));
+            }
+            TryResult::Locked => {
+                log_error!(format!(
+                    "RequestAuthority: netId {} is locked",
+                    message.net_id
+                ));
+            }
+        }
+    }
+
+    // 处理 InputMessage 消息，把输入放入连接的输入缓冲
+    fn on_input_message(connection_id: u64, reader: &mut NetworkReader, _channel: TransportChannel) {
+        let message = match Self::read_message::<InputMessage>(connection_id, reader) {
+            Some(message) => message,
+            None => return,
+        };
+        match NetworkServerStatic::spawned_network_identities().try_get(&message.net_id) {
+            TryResult::Present(identity) => {
+                if identity.connection_to_client() != connection_id {
+                    log_warn!(format!(
+                        "InputMessage from {} for {} without authority.",
+                        connection_id,
+                        identity.net_id()
+                    ));
+                    return;
+                }
+                if message.component_index >= identity.network_behaviours_count {
+                    log_warn!(format!(
+                        "InputMessage for {} with invalid component index {}.",
+                        identity.net_id(),
+                        message.component_index
+                    ));
+                    return;
+                }
+            }
+            // 不可靠通道的输入可能比 spawn 先到
+            TryResult::Absent => return,
+            TryResult::Locked => {
+                log_error!(format!(
+                    "Server.HandleInput: netId {} is locked",
+                    message.net_id
+                ));
+                return;
+            }
+        }
+        match NetworkServerStatic::network_connections().try_get_mut(&connection_id) {
+            TryResult::Present(mut connection) => {
+                let buffer = connection
+                    .input_buffers
+                    .entry((message.net_id, message.component_index))
+                    .or_default();
+                for (i, input) in message.inputs.into_iter().enumerate() {
+                    if i as u32 > message.sequence {
+                        break;
+                    }
+                    buffer.insert(message.sequence - i as u32, input);
+                }
+            }
+            TryResult::Absent => {
+                log_error!(format!(
+                    "Server.HandleInput: connectionId {} not found.",
+                    connection_id
+                ));
+            }
+            TryResult::Locked => {
+                log_error!(format!(
+                    "Server.HandleInput: connectionId {} is locked.",
+                    connection_id
+                ));
+            }
+        }
+    }
+
+    // 处理 StateAckMessage 消息，客户端确认的状态成为新的基线
+    fn on_state_ack_message(
+        connection_id: u64,
+        reader: &mut NetworkReader,
+        _channel: TransportChannel,
+    ) {
+        let message = match Self::read_message::<StateAckMessage>(connection_id, reader) {
+            Some(message) => message,
+            None => return,
+        };
+        match NetworkServerStatic::network_connections().try_get_mut(&connection_id) {
+            TryResult::Present(mut connection) => {
+                if let Some(baseline) = connection.state_baselines.get_mut(&message.net_id) {
+                    baseline.ack(message.tick);
+                }
+            }
+            TryResult::Absent => {
+                log_error!(format!(
+                    "Server.HandleStateAck: connectionId {} not found.",
+                    connection_id
+                ));
+            }
+            TryResult::Locked => {
+                log_error!(format!(
+                    "Server.HandleStateAck: connectionId {} is locked.",
+                    connection_id
+                ));
+            }
+        }
     }
 
     // 处理 ReadyMessage 消息
@@ -1605,6 +2827,7 @@ impl NetworkServer {
         match NetworkServerStatic::network_connections().try_get_mut(&conn_id) {
             TryResult::Present(mut connection) => {
                 connection.set_ready(true);
+                ServerEvents::invoke(&mut ServerEvent::Ready(&mut connection));
             }
             TryResult::Absent => {
                 log_error!(format!(
@@ -1652,20 +2875,21 @@ impl NetworkServer {
         NetworkServerStatic::for_each_network_connection(|mut connection| {
             connection.set_ready(false);
             connection.remove_from_observings_observers();
-            connection
-                .send_network_message(&mut NotReadyMessage::default(), TransportChannel::Reliable);
+            connection.send_network_message(&mut NotReadyMessage, TransportChannel::Reliable);
         });
     }
     // 为连接生成观察者
     fn spawn_observers_for_connection(conn_id: u64) {
+        let mut loaded_scenes = HashSet::new();
         // 发送 ObjectSpawnStartedMessage 消息
         match NetworkServerStatic::network_connections().try_get_mut(&conn_id) {
             TryResult::Present(mut connection) => {
                 if !connection.is_ready() {
                     return;
                 }
+                loaded_scenes = connection.loaded_scenes.clone();
                 connection.send_network_message(
-                    &mut ObjectSpawnStartedMessage::default(),
+                    &mut ObjectSpawnStartedMessage,
                     TransportChannel::Reliable,
                 );
             }
@@ -1686,6 +2910,12 @@ impl NetworkServer {
         // add connection to each nearby NetworkIdentity's observers, which
         // internally sends a spawn message for each one to the connection.
         NetworkServerStatic::for_each_spawned(|mut identity| {
+            // 连接没有加载对象所在的叠加场景
+            if let Some(scene_name) = NetworkServerStatic::additive_scene_of(identity.scene_id) {
+                if !loaded_scenes.contains(&scene_name) {
+                    return;
+                }
+            }
             if identity.visibility == ForceShown {
                 identity.add_observer(conn_id);
             } else if identity.visibility == Visibility::ForceHidden {
@@ -1700,7 +2930,7 @@ impl NetworkServer {
         match NetworkServerStatic::network_connections().try_get_mut(&conn_id) {
             TryResult::Present(mut connection) => {
                 connection.send_network_message(
-                    &mut ObjectSpawnFinishedMessage::default(),
+                    &mut ObjectSpawnFinishedMessage,
                     TransportChannel::Reliable,
                 );
             }
@@ -1725,11 +2955,14 @@ impl NetworkServer {
         reader: &mut NetworkReader,
         channel: TransportChannel,
     ) {
-        let message = CommandMessage::deserialize(reader);
+        let message = match Self::read_message::<CommandMessage>(connection_id, reader) {
+            Some(message) => message,
+            None => return,
+        };
 
         // 如果 connection_id 在 NETWORK_CONNECTIONS 中
         match NetworkServerStatic::network_connections().try_get_mut(&connection_id) {
-            TryResult::Present(connection) => {
+            TryResult::Present(mut connection) => {
                 // connection 没有准备好
                 if !connection.is_ready() {
                     // 如果 channel 是 Reliable
@@ -1771,6 +3004,17 @@ impl NetworkServer {
                     }
                     return;
                 }
+                // 每个方法的 Command 限流
+                if !connection
+                    .rate_limiter
+                    .check_command(message.function_hash)
+                    && Self::on_rate_limited(
+                        &mut connection,
+                        format!("command {} rate limit exceeded", message.function_hash).as_str(),
+                    )
+                {
+                    return;
+                }
             }
             TryResult::Absent => {
                 log_error!(format!(
@@ -1854,7 +3098,10 @@ impl NetworkServer {
         reader: &mut NetworkReader,
         _channel: TransportChannel,
     ) {
-        let message = EntityStateMessage::deserialize(reader);
+        let message = match Self::read_message::<EntityStateMessage>(connection_id, reader) {
+            Some(message) => message,
+            None => return,
+        };
         match NetworkServerStatic::spawned_network_identities().try_get_mut(&message.net_id) {
             TryResult::Present(mut identity) => {
                 if identity.connection_to_client() == connection_id {
@@ -1946,6 +3193,17 @@ impl NetworkServer {
         require_authentication: bool,
     ) where
         T: NetworkMessageTrait + Send + Sync + 'static,
+    {
+        Self::register_closure_handler::<T>(network_message_handler, require_authentication);
+    }
+    // 注册可以携带状态的处理程序
+    pub fn register_closure_handler<T>(
+        network_message_handler: impl FnMut(u64, &mut NetworkReader, TransportChannel)
+            + Send
+            + 'static,
+        require_authentication: bool,
+    ) where
+        T: NetworkMessageTrait + Send + Sync + 'static,
     {
         let hash_code = T::get_hash_code();
 
@@ -1955,7 +3213,10 @@ impl NetworkServer {
         }
         NETWORK_MESSAGE_HANDLERS.insert(
             hash_code,
-            NetworkMessageHandler::wrap_handler(network_message_handler, require_authentication),
+            NetworkMessageHandler::wrap_closure(
+                Box::new(network_message_handler),
+                require_authentication,
+            ),
         );
     }
     // 定义一个函数来替换处理程序
@@ -1964,11 +3225,25 @@ impl NetworkServer {
         require_authentication: bool,
     ) where
         T: NetworkMessageTrait + Send + Sync + 'static,
+    {
+        Self::replace_closure_handler::<T>(network_message_handler, require_authentication);
+    }
+    // 替换为可以携带状态的处理程序
+    pub fn replace_closure_handler<T>(
+        network_message_handler: impl FnMut(u64, &mut NetworkReader, TransportChannel)
+            + Send
+            + 'static,
+        require_authentication: bool,
+    ) where
+        T: NetworkMessageTrait + Send + Sync + 'static,
     {
         let hash_code = T::get_hash_code();
         NETWORK_MESSAGE_HANDLERS.insert(
             hash_code,
-            NetworkMessageHandler::wrap_handler(network_message_handler, require_authentication),
+            NetworkMessageHandler::wrap_closure(
+                Box::new(network_message_handler),
+                require_authentication,
+            ),
         );
     }
     pub fn unregister_handler<T>()
@@ -1979,3 +3254,686 @@ impl NetworkServer {
         NETWORK_MESSAGE_HANDLERS.remove(&hash_code);
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::mirror::core::backend_data::{
+        KeyValue, NetworkBehaviourSetting, NetworkIdentityData, NetworkTransformBaseSetting,
+        NetworkTransformUnreliableSetting,
+    };
+    use crate::mirror::core::batching::batcher::Batcher;
+    use crate::mirror::core::batching::un_batcher::UnBatcher;
+    use crate::mirror::core::messages::RpcMessage;
+    use crate::mirror::core::network_identity::tests::{common_behaviour, set_sync_var};
+    use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
+    use crate::mirror::core::server_events::EventHandlerType;
+    use crate::mirror::core::transport::{TransportFunc, TransportTrait};
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::{Arc, Mutex, MutexGuard};
+    use std::time::Instant;
+
+    lazy_static! {
+        // 修改 NetworkServer 全局状态的测试不能并行
+        static ref SERVER_TEST_LOCK: Mutex<()> = Mutex::new(());
+        // TestTransport 发送的可靠数据字节数
+        static ref TEST_RELIABLE_SENT: Atomic<usize> = Atomic::new(0);
+        // 还需要多少次 server_late_update 才能发送完可靠数据
+        static ref TEST_RELIABLE_PENDING: Atomic<usize> = Atomic::new(0);
+        static ref TEST_LATE_UPDATES: Atomic<usize> = Atomic::new(0);
+        // 最后一次 server_disconnect 时的 server_late_update 次数
+        static ref TEST_DISCONNECTED_AT: Atomic<usize> = Atomic::new(0);
+        // TestTransport 发送的可靠数据
+        static ref TEST_RELIABLE_BATCHES: Mutex<Vec<Vec<u8>>> = Mutex::new(Vec::new());
+    }
+
+    pub(crate) fn lock_server() -> MutexGuard<'static, ()> {
+        SERVER_TEST_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // 断开的连接在下一次 server_early_update 中回调 OnServerDisconnected
+    #[derive(Default)]
+    pub(crate) struct TestTransport {
+        transport: Transport,
+        disconnected: Vec<u64>,
+    }
+
+    impl TestTransport {
+        pub(crate) fn install() {
+            let mut transport = Box::new(TestTransport::default());
+            transport.set_transport_cb_fn(NetworkServer::transport_callback);
+            Transport::set_active_transport(transport);
+        }
+    }
+
+    impl TransportTrait for TestTransport {
+        fn awake() {}
+        fn available(&self) -> bool {
+            true
+        }
+        fn server_active(&self) -> bool {
+            true
+        }
+        fn server_start(&mut self) {}
+        fn server_send(&mut self, _: u64, data: Vec<u8>, channel: TransportChannel) {
+            if channel == TransportChannel::Reliable {
+                TEST_RELIABLE_SENT.fetch_add(data.len(), Ordering::Relaxed);
+                TEST_RELIABLE_BATCHES.lock().unwrap().push(data);
+            }
+        }
+        fn server_disconnect(&mut self, connection_id: u64) {
+            self.disconnected.push(connection_id);
+            TEST_DISCONNECTED_AT
+                .store(TEST_LATE_UPDATES.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+        fn server_get_client_address(&self, _: u64) -> String {
+            String::new()
+        }
+        fn server_early_update(&mut self) {
+            let disconnected = std::mem::take(&mut self.disconnected);
+            if let Some(transport_cb_fn) = self.transport.transport_cb_fn {
+                for conn_id in disconnected {
+                    transport_cb_fn(TransportCallback {
+                        r#type: TransportCallbackType::OnServerDisconnected,
+                        conn_id,
+                        ..TransportCallback::default()
+                    });
+                }
+            }
+        }
+        fn server_late_update(&mut self) {
+            TEST_LATE_UPDATES.fetch_add(1, Ordering::Relaxed);
+            let pending = TEST_RELIABLE_PENDING.load(Ordering::Relaxed);
+            TEST_RELIABLE_PENDING.store(pending.saturating_sub(1), Ordering::Relaxed);
+        }
+        fn server_stop(&mut self) {}
+        fn transport_cb_fn(&self) -> Option<TransportFunc> {
+            self.transport.transport_cb_fn
+        }
+        fn set_transport_cb_fn(&mut self, func: TransportFunc) {
+            self.transport.transport_cb_fn.replace(func);
+        }
+        fn get_max_packet_size(&self, _: TransportChannel) -> usize {
+            1500
+        }
+        fn server_reliable_pending(&self, _: u64) -> usize {
+            TEST_RELIABLE_PENDING.load(Ordering::Relaxed)
+        }
+    }
+
+    #[test]
+    fn test_shutdown_drain_and_restart() {
+        let _lock = lock_server();
+        TestTransport::install();
+        NetworkServerStatic::set_active(true);
+        for conn_id in [1, 2] {
+            NetworkServerStatic::network_connections()
+                .insert(conn_id, NetworkConnectionToClient::new(conn_id));
+        }
+
+        // 默认不发送 Mirror 客户端不认识的 DisconnectMessage
+        let sent = TEST_RELIABLE_SENT.load(Ordering::Relaxed);
+        NetworkServer::begin_shutdown(DisconnectReason::ServerShutdown, "maintenance");
+        NetworkServerStatic::for_each_network_connection(|mut connection| {
+            connection.update();
+        });
+        assert_eq!(TEST_RELIABLE_SENT.load(Ordering::Relaxed), sent);
+        assert!(NetworkServerStatic::is_shutting_down());
+        assert_eq!(
+            NetworkServerStatic::shutdown_reason(),
+            DisconnectReason::ServerShutdown
+        );
+        // 重复调用不会覆盖原因
+        NetworkServer::begin_shutdown(DisconnectReason::ServerRestart, "again");
+        assert_eq!(
+            NetworkServerStatic::shutdown_reason(),
+            DisconnectReason::ServerShutdown
+        );
+
+        // 客户端不会自己断开，drain 发送完数据后主动断开，不等到宽限期结束
+        let start = Instant::now();
+        NetworkServer::drain(30.0);
+        assert_eq!(NetworkServerStatic::network_connections_size(), 0);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        NetworkServerStatic::set_shutting_down(false);
+        NetworkServerStatic::set_shutdown_reason(DisconnectReason::None);
+        NetworkServer::schedule_restart(45.0, "update");
+        assert!(NetworkServerStatic::restart_time() > NetworkTime::local_time() + 44.0);
+        assert_eq!(NetworkServerStatic::restart_last_countdown(), 45);
+        assert_eq!(NetworkServerStatic::restart_message(), "update");
+        NetworkServer::cancel_restart();
+        assert_eq!(NetworkServerStatic::restart_time(), 0.0);
+
+        // 到时间后开始关闭并停止主循环
+        NetworkServer::schedule_restart(0.0, "update");
+        NetworkServer::update_scheduled_restart();
+        assert!(NetworkServerStatic::is_shutting_down());
+        assert_eq!(
+            NetworkServerStatic::shutdown_reason(),
+            DisconnectReason::ServerRestart
+        );
+        assert!(NetworkLoop::stop().load(Ordering::Relaxed));
+
+        NetworkLoop::set_stop(false);
+        NetworkServerStatic::set_shutting_down(false);
+        NetworkServerStatic::set_shutdown_reason(DisconnectReason::None);
+        NetworkServerStatic::set_active(false);
+    }
+
+    #[test]
+    fn test_drain_waits_for_reliable_data() {
+        let _lock = lock_server();
+        TestTransport::install();
+        NetworkServerStatic::set_active(true);
+        NetworkServerStatic::network_connections().insert(1, NetworkConnectionToClient::new(1));
+
+        // 传输层还需要 5 次 late update 才能发送完可靠数据，2 个 tick 后不能断开
+        TEST_RELIABLE_PENDING.store(5, Ordering::Relaxed);
+        let start = TEST_LATE_UPDATES.load(Ordering::Relaxed);
+        NetworkServer::drain(30.0);
+        assert_eq!(NetworkServerStatic::network_connections_size(), 0);
+        assert_eq!(TEST_DISCONNECTED_AT.load(Ordering::Relaxed) - start, 5);
+
+        // 宽限期结束时不再等待
+        NetworkServerStatic::network_connections().insert(2, NetworkConnectionToClient::new(2));
+        TEST_RELIABLE_PENDING.store(usize::MAX, Ordering::Relaxed);
+        NetworkServer::drain(0.0);
+        assert_eq!(NetworkServerStatic::network_connections_size(), 1);
+
+        TEST_RELIABLE_PENDING.store(0, Ordering::Relaxed);
+        NetworkServerStatic::network_connections().clear();
+        NetworkServerStatic::set_active(false);
+    }
+
+    // 生成一个带有一个已修改 SyncVar 组件的 identity
+    fn spawn_dirty_identity(position: Vector3<f32>, owner: u64) -> u32 {
+        let mut identity = NetworkIdentity::new_with_asset_id(0);
+        let net_id = NetworkIdentity::get_static_next_network_id();
+        identity.set_net_id(net_id);
+        let mut game_object = GameObject::default();
+        game_object.transform.position = position;
+        identity.set_game_object(game_object);
+        identity.set_connection_to_client(owner);
+        identity.network_behaviours_count = 1;
+        NETWORK_BEHAVIOURS.insert(
+            format!("{}_0", net_id),
+            Box::new(common_behaviour(0, 0, &[(1, false)])),
+        );
+        set_sync_var(net_id, 0, 0, 2);
+        NetworkServerStatic::spawned_network_identities().insert(net_id, identity);
+        net_id
+    }
+
+    // 只有组件的 NetworkIdentityData，组件的设置使用默认值
+    pub(crate) fn network_identity_data(
+        asset_id: u32,
+        sub_classes: &[&str],
+    ) -> NetworkIdentityData {
+        let components = sub_classes
+            .iter()
+            .enumerate()
+            .map(|(index, sub_class)| {
+                serde_json::json!({
+                    "key": index,
+                    "value": {
+                        "componentIndex": index,
+                        "componentType": sub_class,
+                        "networkBehaviourSetting": NetworkBehaviourSetting::default(),
+                        "networkTransformBaseSetting": NetworkTransformBaseSetting::default(),
+                        "networkTransformReliableSetting": {
+                            "onlySyncOnChangeCorrectionMultiplier": 2.0,
+                            "rotationSensitivity": 0.01,
+                            "positionPrecision": 0.01,
+                            "scalePrecision": 0.01,
+                        },
+                        "networkTransformUnreliableSetting":
+                            NetworkTransformUnreliableSetting::default(),
+                        "networkAnimatorSetting": {
+                            "clientAuthority": false,
+                            "animator": { "layers": [], "parameters": [] },
+                            "animatorSpeed": 1.0,
+                            "previousSpeed": 1.0,
+                        },
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        NetworkIdentityData {
+            asset_id,
+            scene_id: "".to_string(),
+            network_behaviour_components: serde_json::from_value(serde_json::Value::Array(
+                components,
+            ))
+            .unwrap(),
+            hitboxes: Vec::new(),
+            colliders: Vec::new(),
+        }
+    }
+
+    fn remove_identities(net_ids: &[u32]) {
+        for net_id in net_ids {
+            NetworkServerStatic::spawned_network_identities().remove(net_id);
+            NETWORK_BEHAVIOURS.remove(&format!("{}_0", net_id));
+        }
+    }
+
+    #[test]
+    fn test_priority_for_connection() {
+        let _lock = lock_server();
+        let near = spawn_dirty_identity(Vector3::new(1.0, 0.0, 0.0), 0);
+        let far = spawn_dirty_identity(Vector3::new(100.0, 0.0, 0.0), 0);
+        let owned = spawn_dirty_identity(Vector3::new(100.0, 0.0, 0.0), 1);
+        let viewer = Some(Vector3::zeros());
+
+        let near_priority = NetworkServer::priority_for_connection(near, 1, viewer);
+        let far_priority = NetworkServer::priority_for_connection(far, 1, viewer);
+        let owned_priority = NetworkServer::priority_for_connection(owned, 1, viewer);
+        assert!(near_priority > far_priority);
+        // 拥有的对象乘以 priority_owner_multiplier
+        assert!((owned_priority - far_priority * 4.0).abs() < 1e-5);
+        // 没有玩家位置时不考虑距离，找不到的对象为 1
+        assert_eq!(NetworkServer::priority_for_connection(far, 1, None), 1.0);
+        assert_eq!(NetworkServer::priority_for_connection(0, 1, viewer), 1.0);
+
+        remove_identities(&[near, far, owned]);
+    }
+
+    #[test]
+    fn test_broadcast_to_connection_prioritized() {
+        let _lock = lock_server();
+        TestTransport::install();
+        // 同一个 tick 内重复序列化得到相同的增量，每个 EntityStateMessage 为 17 字节
+        let message_size = 17.0;
+        for (credit, expected_sent) in [(2.0 * message_size, 2), (message_size, 1), (1.0, 1)] {
+            NetworkTime::increment_frame_count();
+            // 优先级：owned > near > far
+            let owned = spawn_dirty_identity(Vector3::new(50.0, 0.0, 0.0), 1);
+            let near = spawn_dirty_identity(Vector3::new(1.0, 0.0, 0.0), 0);
+            let far = spawn_dirty_identity(Vector3::new(100.0, 0.0, 0.0), 0);
+            // 玩家在原点，优先级按距离计算
+            let player = spawn_dirty_identity(Vector3::zeros(), 1);
+            let mut conn = NetworkConnectionToClient::new(1);
+            conn.set_net_id(player);
+            conn.observing = vec![far, near, owned];
+            conn.bandwidth_credit = credit;
+
+            NetworkServer::broadcast_to_connection_prioritized(&mut conn, credit);
+            let sent: Vec<u32> = [owned, near, far]
+                .into_iter()
+                .filter(|net_id| !conn.stale_observing.contains(net_id))
+                .collect();
+            // 按优先级发送，预算不足时第一个对象也会发送
+            assert_eq!(sent, vec![owned, near, far][..expected_sent]);
+            for net_id in [owned, near, far] {
+                let accumulator = conn.priority_accumulators[&net_id];
+                // 发送后重置累加值，推迟的对象保留累加值
+                assert_eq!(accumulator == 0.0, sent.contains(&net_id));
+            }
+            assert_eq!(
+                conn.bandwidth_credit,
+                credit - expected_sent as f64 * message_size
+            );
+
+            remove_identities(&[owned, near, far, player]);
+        }
+    }
+
+    #[test]
+    fn test_fits_bandwidth() {
+        let mut conn = NetworkConnectionToClient::new(1);
+        conn.bandwidth_credit = 10.0;
+        assert!(NetworkServer::fits_bandwidth(&conn, 10, true));
+        assert!(!NetworkServer::fits_bandwidth(&conn, 11, true));
+        // 第一个对象只要还有预算就发送
+        assert!(NetworkServer::fits_bandwidth(&conn, 11, false));
+        conn.bandwidth_credit = 0.0;
+        assert!(!NetworkServer::fits_bandwidth(&conn, 1, false));
+    }
+
+    #[test]
+    fn test_rpc_batches_on_not_ready_and_disconnect() {
+        let _lock = lock_server();
+        TestTransport::install();
+        let mut connection = NetworkConnectionToClient::new(1);
+        connection.set_ready(true);
+
+        // 设置为未准备就绪时丢弃缓存的 RPC
+        connection.buffer_rpc(&[1, 2, 3], TransportChannel::Reliable);
+        connection.buffer_rpc(&[4], TransportChannel::Unreliable);
+        connection.set_ready(false);
+        assert_eq!(connection.reliable_rpcs_batch.get_position(), 0);
+        assert_eq!(connection.unreliable_rpcs_batch.get_position(), 0);
+
+        // 断开前发送缓存的 RPC
+        connection.set_ready(true);
+        connection.buffer_rpc(&[1, 2, 3], TransportChannel::Reliable);
+        let sent = TEST_RELIABLE_SENT.load(Ordering::Relaxed);
+        connection.disconnect();
+        assert_eq!(connection.reliable_rpcs_batch.get_position(), 0);
+        assert!(TEST_RELIABLE_SENT.load(Ordering::Relaxed) > sent);
+        assert!(!connection.is_ready());
+    }
+
+    // 连接 1 发送的可靠消息 id，按发送顺序
+    fn sent_reliable_message_ids(conn_id: u64) -> Vec<u16> {
+        if let Some(mut connection) = NetworkServerStatic::network_connections().get_mut(&conn_id) {
+            connection.update();
+        }
+        let mut un_batcher = UnBatcher::new();
+        for batch in TEST_RELIABLE_BATCHES.lock().unwrap().drain(..) {
+            un_batcher.add_batch_with_bytes(batch);
+        }
+        let mut ids = Vec::new();
+        while let Ok(Some((message, _))) = un_batcher.get_next_message() {
+            ids.push(NetworkMessages::unpack_id(
+                &mut NetworkReader::new_with_array_segment(message),
+            ));
+        }
+        ids
+    }
+
+    #[test]
+    fn test_rpcs_flushed_before_hide_and_destroy() {
+        let _lock = lock_server();
+        TestTransport::install();
+        NetworkServerStatic::set_active(true);
+        let mut connection = NetworkConnectionToClient::new(1);
+        connection.set_ready(true);
+        NetworkServerStatic::network_connections().insert(1, connection);
+
+        let net_id = spawn_dirty_identity(Vector3::zeros(), 0);
+        let (_, mut identity) = NetworkServerStatic::spawned_network_identities()
+            .remove(&net_id)
+            .unwrap();
+        let mut game_object = identity.game_object().clone();
+        game_object.prefab = "Test".to_string();
+        identity.set_game_object(game_object);
+        identity.add_observer(1);
+        // 丢弃准备阶段发送的消息
+        sent_reliable_message_ids(1);
+        let buffer_rpc = || {
+            NetworkWriterPool::get_return(|writer| {
+                NetworkMessages::pack(&mut RpcMessage::new(net_id, 0, 1, Vec::new()), writer);
+                NetworkServerStatic::network_connections()
+                    .get_mut(&1)
+                    .unwrap()
+                    .buffer_rpc(writer.to_array_segment(), TransportChannel::Reliable);
+            });
+        };
+
+        // 隐藏前先发送 RPC
+        buffer_rpc();
+        if let Some(mut connection) = NetworkServerStatic::network_connections().get_mut(&1) {
+            NetworkServer::hide_for_connection(&mut connection, &mut identity);
+        }
+        assert_eq!(
+            sent_reliable_message_ids(1),
+            vec![
+                RpcMessage::get_hash_code(),
+                ObjectHideMessage::get_hash_code()
+            ]
+        );
+
+        // 销毁前先发送 RPC
+        buffer_rpc();
+        NetworkServer::un_spawn_identity(&mut identity);
+        assert_eq!(
+            sent_reliable_message_ids(1),
+            vec![
+                RpcMessage::get_hash_code(),
+                ObjectDestroyMessage::get_hash_code()
+            ]
+        );
+
+        NETWORK_BEHAVIOURS.remove(&format!("{}_0", net_id));
+        NetworkServerStatic::network_connections().clear();
+        NetworkServerStatic::set_active(false);
+    }
+
+    #[test]
+    fn test_spawn_prefab() {
+        let _lock = lock_server();
+        TestTransport::install();
+        let backend_data = BackendDataStatic::get_backend_data();
+        let mut test_backend_data = backend_data.clone();
+        test_backend_data.assets.push(KeyValue {
+            key: 7,
+            value: "Bullet".to_string(),
+        });
+        test_backend_data
+            .network_identities
+            .push(network_identity_data(7, &["Test.Bullet"]));
+        assert!(BackendDataStatic::store(&test_backend_data));
+
+        let transform = Transform {
+            position: Vector3::new(1.0, 2.0, 3.0),
+            ..Default::default()
+        };
+
+        // 服务器未启动
+        assert_eq!(
+            NetworkServer::spawn_prefab("Bullet", transform, None, |_| {}),
+            None
+        );
+
+        NetworkServerStatic::set_active(true);
+        assert_eq!(
+            NetworkServer::spawn_prefab("Missing", transform, None, |_| {}),
+            None
+        );
+        // owner 不存在
+        assert_eq!(
+            NetworkServer::spawn_prefab("Bullet", transform, Some(9), |_| {}),
+            None
+        );
+
+        let mut connection = NetworkConnectionToClient::new(1);
+        connection.set_ready(true);
+        NetworkServerStatic::network_connections().insert(1, connection);
+
+        // init 在生成之前执行，已经分配了 net_id 并设置了拥有者
+        let mut init_net_id = 0;
+        let net_id = NetworkServer::spawn_prefab("Bullet", transform, Some(1), |identity| {
+            init_net_id = identity.net_id();
+            assert!(!NetworkServerStatic::spawned_network_ids().contains(&init_net_id));
+            assert_eq!(identity.connection_to_client(), 1);
+            match NETWORK_BEHAVIOURS.try_get(&format!("{}_0", init_net_id)) {
+                TryResult::Present(component) => assert_eq!(component.connection_to_client(), 1),
+                _ => panic!("component of net_id {} not found", init_net_id),
+            }
+        })
+        .expect("spawn_prefab failed");
+        assert_ne!(net_id, 0);
+        assert_eq!(net_id, init_net_id);
+
+        match NetworkServerStatic::spawned_network_identities().try_get(&net_id) {
+            TryResult::Present(identity) => {
+                assert_eq!(identity.asset_id, 7);
+                assert_eq!(identity.connection_to_client(), 1);
+                assert_eq!(identity.observers(), &vec![1]);
+                assert_eq!(
+                    identity.game_object().transform.position,
+                    transform.position
+                );
+            }
+            _ => panic!("net_id {} not spawned", net_id),
+        }
+        match NetworkServerStatic::network_connections().try_get_mut(&1) {
+            TryResult::Present(mut connection) => {
+                assert_eq!(connection.owned(), &vec![net_id]);
+                assert!(connection.observing.contains(&net_id));
+            }
+            _ => panic!("connection 1 not found"),
+        }
+
+        // spawn_object 没有生成时移除组件和拥有关系
+        let mut failed_net_id = 0;
+        let failed = NetworkServer::spawn_prefab("Bullet", transform, Some(1), |identity| {
+            failed_net_id = identity.net_id();
+            identity.spawned_from_instantiate = true;
+        });
+        assert_eq!(failed, None);
+        assert_ne!(failed_net_id, 0);
+        assert!(!NETWORK_BEHAVIOURS.contains_key(&format!("{}_0", failed_net_id)));
+        assert!(!NetworkServerStatic::spawned_network_ids().contains(&failed_net_id));
+        match NetworkServerStatic::network_connections().try_get_mut(&1) {
+            TryResult::Present(mut connection) => {
+                assert_eq!(connection.owned(), &vec![net_id]);
+            }
+            _ => panic!("connection 1 not found"),
+        }
+
+        NetworkServerStatic::remove_spawned_network_identity(&net_id);
+        NETWORK_BEHAVIOURS::remove_behaviour(net_id, 1);
+        NetworkServerStatic::network_connections().clear();
+        NetworkServerStatic::set_active(false);
+        assert!(BackendDataStatic::store(&backend_data));
+    }
+
+    #[test]
+    fn test_rate_limit_disconnect_discards_batch() {
+        let _lock = lock_server();
+        TestTransport::install();
+        NetworkServerStatic::set_active(true);
+        NetworkServerStatic::network_connections().insert(1, NetworkConnectionToClient::new(1));
+        RateLimiter::set_message_rate(1.0, 1.0);
+        RateLimiter::set_policy(RateLimitPolicy::Disconnect);
+        RateLimiter::reset_counters();
+
+        // 三条未知消息，第二条超出限制
+        let mut batcher = Batcher::new(1200);
+        for _ in 0..3 {
+            batcher.add_message(&[0xff, 0xff], 1.0);
+        }
+        let mut data = Vec::new();
+        NetworkWriterPool::get_return(|writer| {
+            assert!(batcher.get_batcher_writer(writer));
+            data = writer.to_bytes();
+        });
+
+        let messages_limited = || match NetworkServerStatic::network_connections().try_get(&1) {
+            TryResult::Present(connection) => connection.rate_limiter.messages_limited,
+            _ => panic!("connection 1 not found"),
+        };
+        NetworkServer::on_transport_data(1, data.clone(), TransportChannel::Reliable);
+        assert_eq!(RateLimiter::total_disconnects(), 1);
+        // 断开后剩余的消息不再检查
+        assert_eq!(messages_limited(), 1);
+        match NetworkServerStatic::transport_data_un_batcher().read() {
+            Ok(un_batcher) => assert_eq!(un_batcher.batches_count(), 0),
+            Err(e) => panic!("{}", e),
+        }
+
+        // 传输层断开之前收到的数据直接丢弃
+        NetworkServer::on_transport_data(1, data, TransportChannel::Reliable);
+        assert_eq!(RateLimiter::total_disconnects(), 1);
+        assert_eq!(messages_limited(), 1);
+
+        RateLimiter::set_message_rate(0.0, 0.0);
+        RateLimiter::set_policy(RateLimitPolicy::Drop);
+        RateLimiter::reset_counters();
+        NetworkServerStatic::network_connections().clear();
+        NetworkServerStatic::set_active(false);
+    }
+
+    #[test]
+    fn test_read_error_disconnect_discards_batch() {
+        let _lock = lock_server();
+        TestTransport::install();
+        NetworkServerStatic::set_active(true);
+        NetworkServerStatic::network_connections().insert(1, NetworkConnectionToClient::new(1));
+
+        let pings = Arc::new(AtomicUsize::new(0));
+        let handler_pings = pings.clone();
+        NetworkServer::register_closure_handler::<NetworkPingMessage>(
+            move |connection_id, reader, _| {
+                if NetworkServer::read_message::<NetworkPingMessage>(connection_id, reader)
+                    .is_some()
+                {
+                    handler_pings.fetch_add(1, Ordering::Relaxed);
+                }
+            },
+            false,
+        );
+
+        // 中间的 NetworkPingMessage 缺少 predicted_time_adjusted
+        let ping_id = NetworkPingMessage::get_hash_code();
+        let mut batcher = Batcher::new(1200);
+        for valid in [true, false, true] {
+            let mut writer = NetworkWriter::new();
+            writer.write_ushort(ping_id);
+            writer.write_double(1.0);
+            if valid {
+                writer.write_double(1.0);
+            }
+            batcher.add_message(writer.to_array_segment(), 1.0);
+        }
+        let mut data = Vec::new();
+        NetworkWriterPool::get_return(|writer| {
+            assert!(batcher.get_batcher_writer(writer));
+            data = writer.to_bytes();
+        });
+        let disconnected = || match NetworkServerStatic::network_connections().try_get(&1) {
+            TryResult::Present(connection) => connection.disconnected,
+            _ => panic!("connection 1 not found"),
+        };
+
+        // 不断开时跳过读取失败的消息
+        NetworkServer::on_transport_data(1, data.clone(), TransportChannel::Reliable);
+        assert_eq!(pings.load(Ordering::Relaxed), 2);
+        assert!(!disconnected());
+
+        // 断开后不再处理剩余的消息
+        NetworkServerStatic::set_exceptions_disconnect(true);
+        NetworkServer::on_transport_data(1, data.clone(), TransportChannel::Reliable);
+        assert_eq!(pings.load(Ordering::Relaxed), 3);
+        assert!(disconnected());
+        match NetworkServerStatic::transport_data_un_batcher().read() {
+            Ok(un_batcher) => assert_eq!(un_batcher.batches_count(), 0),
+            Err(e) => panic!("{}", e),
+        }
+
+        // 传输层断开之前收到的数据直接丢弃
+        NetworkServer::on_transport_data(1, data, TransportChannel::Reliable);
+        assert_eq!(pings.load(Ordering::Relaxed), 3);
+
+        NetworkServer::unregister_handler::<NetworkPingMessage>();
+        NetworkServerStatic::set_exceptions_disconnect(false);
+        NetworkServerStatic::network_connections().clear();
+        NetworkServerStatic::set_active(false);
+    }
+
+    #[test]
+    fn test_disconnect_destroys_player_with_subscribers() {
+        let _lock = lock_server();
+        TestTransport::install();
+        NetworkServerStatic::set_active(true);
+        // 有订阅者时也执行默认清理
+        let subscription = ServerEvents::subscribe(EventHandlerType::OnDisconnectedEvent, |_| {});
+
+        for destroy_player in [true, false] {
+            NetworkServerStatic::set_destroy_player_on_disconnect(destroy_player);
+            NetworkServerStatic::network_connections().insert(1, NetworkConnectionToClient::new(1));
+            let mut identity = NetworkIdentity::new_with_asset_id(0);
+            let net_id = NetworkIdentity::get_static_next_network_id();
+            identity.set_net_id(net_id);
+            identity.set_connection_to_client(1);
+            NetworkServerStatic::add_spawned_network_identity(identity);
+
+            NetworkServer::on_transport_disconnected(1);
+            assert!(!NetworkServerStatic::network_connections().contains_key(&1));
+            assert_eq!(
+                NetworkServerStatic::spawned_network_ids().contains(&net_id),
+                !destroy_player
+            );
+            NetworkServerStatic::remove_spawned_network_identity(&net_id);
+        }
+
+        assert!(subscription.unsubscribe());
+        NetworkServerStatic::set_destroy_player_on_disconnect(true);
+        NetworkServerStatic::set_active(false);
+    }
+}
@@ -1,7 +1,7 @@
-use crate::mirror::core::messages::{NetworkMessageTrait, NetworkPingMessage, NetworkPongMessage};
+use crate::mirror::core::messages::{NetworkPingMessage, NetworkPongMessage};
 use crate::mirror::core::network_connection::NetworkConnectionTrait;
 use crate::mirror::core::network_reader::NetworkReader;
-use crate::mirror::core::network_server::NetworkServerStatic;
+use crate::mirror::core::network_server::{NetworkServer, NetworkServerStatic};
 use crate::mirror::core::transport::TransportChannel;
 use crate::{log_error, log_warn};
 use atomic::Atomic;
@@ -102,7 +102,10 @@ impl NetworkTime {
         channel: TransportChannel,
     ) {
         let _ = channel;
-        let message = NetworkPingMessage::deserialize(un_batch);
+        let message = match NetworkServer::read_message::<NetworkPingMessage>(connection_id, un_batch) {
+            Some(message) => message,
+            None => return,
+        };
         let local_time = Self::local_time();
         let unadjusted_error = local_time - message.local_time;
         let adjusted_error = local_time - message.predicted_time_adjusted;
@@ -130,11 +133,14 @@ impl NetworkTime {
     }
 
     pub fn on_server_pong(
-        _connection_id: u64,
+        connection_id: u64,
         un_batch: &mut NetworkReader,
         _channel: TransportChannel,
     ) {
-        let message = NetworkPongMessage::deserialize(un_batch);
+        let message = match NetworkServer::read_message::<NetworkPongMessage>(connection_id, un_batch) {
+            Some(message) => message,
+            None => return,
+        };
         if message.local_time > Self::local_time() {
             return;
         }
@@ -144,6 +150,12 @@ impl NetworkTime {
         } else {
             log_warn!("NetworkTime::on_server_pong() failed to get rtt");
         }
+        // 每个连接的 rtt，延迟补偿使用
+        if let TryResult::Present(mut connection) =
+            NetworkServerStatic::network_connections().try_get_mut(&connection_id)
+        {
+            connection._rtt.add(new_rtt);
+        }
     }
 
     #[allow(dead_code)]
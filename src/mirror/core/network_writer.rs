@@ -10,6 +10,12 @@ pub struct NetworkWriter {
     position: usize,
 }
 
+impl Default for NetworkWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl NetworkWriter {
     // the limit of ushort is so we can write string size prefix as only 2 bytes.
     // -1 so we can still encode 'null' into it too.
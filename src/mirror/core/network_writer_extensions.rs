@@ -279,7 +279,7 @@ impl Writeable for String {
     where
         Self: Sized,
     {
-        Some(|writer, value| NetworkWriterExtensions::write_string(writer, value))
+        Some(NetworkWriterExtensions::write_string)
     }
 }
 
@@ -288,6 +288,6 @@ impl Writeable for &str {
     where
         Self: Sized,
     {
-        Some(|writer, value| NetworkWriterExtensions::write_string(writer, value.to_string()))
+        Some(NetworkWriterExtensions::write_string)
     }
 }
@@ -5,7 +5,8 @@ use lazy_static::lazy_static;
 use std::sync::{Arc, Mutex};
 
 lazy_static! {
-    static ref NETWORK_WRITER_POOL: Arc<Mutex<Pool<NetworkWriter>>> = Arc::new(Mutex::new(Pool::new(|| NetworkWriter::new(), 1000)));
+    static ref NETWORK_WRITER_POOL: Arc<Mutex<Pool<NetworkWriter>>> =
+        Arc::new(Mutex::new(Pool::new(NetworkWriter::new, 1000)));
 }
 
 #[derive(Clone)]
@@ -0,0 +1,246 @@
+use crate::mirror::core::backend_data::BackendData;
+use crate::mirror::core::network_time::NetworkTime;
+use atomic::Atomic;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+
+lazy_static! {
+    // 每秒允许的消息数，0 表示不限制
+    static ref MESSAGE_RATE: Atomic<f32> = Atomic::new(0.0);
+    static ref MESSAGE_BURST: Atomic<f32> = Atomic::new(0.0);
+    // 每个 Command 方法每秒允许的调用次数，0 表示不限制
+    static ref COMMAND_RATE: Atomic<f32> = Atomic::new(0.0);
+    static ref COMMAND_BURST: Atomic<f32> = Atomic::new(0.0);
+    static ref RATE_LIMIT_POLICY: Atomic<u8> = Atomic::new(RateLimitPolicy::Drop.to_u8());
+    // MethodData 中覆盖的限制 hash -> (rate, burst)
+    static ref METHOD_RATE_LIMITS: DashMap<u16, (f32, f32)> = DashMap::new();
+    // 全局计数
+    static ref TOTAL_MESSAGES_LIMITED: Atomic<u64> = Atomic::new(0);
+    static ref TOTAL_COMMANDS_LIMITED: Atomic<u64> = Atomic::new(0);
+    static ref TOTAL_RATE_LIMIT_DISCONNECTS: Atomic<u64> = Atomic::new(0);
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RateLimitPolicy {
+    // 丢弃超出限制的消息
+    Drop,
+    // 只记录警告，消息照常处理，用于调整限制
+    Warn,
+    // 丢弃并断开连接
+    Disconnect,
+}
+
+impl RateLimitPolicy {
+    pub fn from(value: u8) -> RateLimitPolicy {
+        match value {
+            1 => RateLimitPolicy::Warn,
+            2 => RateLimitPolicy::Disconnect,
+            _ => RateLimitPolicy::Drop,
+        }
+    }
+    pub fn to_u8(&self) -> u8 {
+        *self as u8
+    }
+}
+
+// 令牌桶，每秒补充 rate 个，最多 burst 个
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    rate: f32,
+    burst: f32,
+    tokens: f32,
+    last_refill_time: f64,
+}
+
+impl TokenBucket {
+    pub fn new(rate: f32, burst: f32) -> Self {
+        let burst = burst.max(rate).max(1.0);
+        Self {
+            rate,
+            burst,
+            tokens: burst,
+            last_refill_time: NetworkTime::local_time(),
+        }
+    }
+
+    pub fn set_limit(&mut self, rate: f32, burst: f32) {
+        self.rate = rate;
+        self.burst = burst.max(rate).max(1.0);
+        self.tokens = self.tokens.min(self.burst);
+    }
+
+    pub fn try_consume(&mut self, now: f64) -> bool {
+        let elapsed = (now - self.last_refill_time).max(0.0) as f32;
+        self.last_refill_time = now;
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.burst);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return true;
+        }
+        false
+    }
+}
+
+// 每个连接的限流状态和计数
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    messages: Option<TokenBucket>,
+    commands: HashMap<u16, TokenBucket>,
+    pub messages_limited: u64,
+    pub commands_limited: u64,
+    // 已按 Disconnect 策略断开，剩余的消息直接丢弃
+    pub disconnected: bool,
+}
+
+impl RateLimiter {
+    pub fn message_rate() -> f32 {
+        MESSAGE_RATE.load(Ordering::Relaxed)
+    }
+    pub fn message_burst() -> f32 {
+        MESSAGE_BURST.load(Ordering::Relaxed)
+    }
+    pub fn set_message_rate(rate: f32, burst: f32) {
+        MESSAGE_RATE.store(rate, Ordering::Relaxed);
+        MESSAGE_BURST.store(burst, Ordering::Relaxed);
+    }
+    pub fn command_rate() -> f32 {
+        COMMAND_RATE.load(Ordering::Relaxed)
+    }
+    pub fn command_burst() -> f32 {
+        COMMAND_BURST.load(Ordering::Relaxed)
+    }
+    pub fn set_command_rate(rate: f32, burst: f32) {
+        COMMAND_RATE.store(rate, Ordering::Relaxed);
+        COMMAND_BURST.store(burst, Ordering::Relaxed);
+    }
+    pub fn policy() -> RateLimitPolicy {
+        RateLimitPolicy::from(RATE_LIMIT_POLICY.load(Ordering::Relaxed))
+    }
+    pub fn set_policy(policy: RateLimitPolicy) {
+        RATE_LIMIT_POLICY.store(policy.to_u8(), Ordering::Relaxed);
+    }
+    pub fn total_messages_limited() -> u64 {
+        TOTAL_MESSAGES_LIMITED.load(Ordering::Relaxed)
+    }
+    pub fn total_commands_limited() -> u64 {
+        TOTAL_COMMANDS_LIMITED.load(Ordering::Relaxed)
+    }
+    pub fn total_disconnects() -> u64 {
+        TOTAL_RATE_LIMIT_DISCONNECTS.load(Ordering::Relaxed)
+    }
+    pub fn add_disconnect() {
+        TOTAL_RATE_LIMIT_DISCONNECTS.fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn reset_counters() {
+        TOTAL_MESSAGES_LIMITED.store(0, Ordering::Relaxed);
+        TOTAL_COMMANDS_LIMITED.store(0, Ordering::Relaxed);
+        TOTAL_RATE_LIMIT_DISCONNECTS.store(0, Ordering::Relaxed);
+    }
+
+    // 从 MethodData 读取每个方法的限制，启动和热重载时调用
+    pub fn load_method_rate_limits(backend_data: &BackendData) {
+        METHOD_RATE_LIMITS.clear();
+        for method_data in backend_data.methods.iter() {
+            if let Some(rate) = method_data.rate_limit {
+                let burst = method_data.rate_limit_burst.unwrap_or(rate);
+                METHOD_RATE_LIMITS.insert(method_data.hash_code, (rate, burst));
+            }
+        }
+    }
+
+    // 超出总消息限制时返回 false
+    pub fn check_message(&mut self) -> bool {
+        let rate = Self::message_rate();
+        if rate <= 0.0 {
+            return true;
+        }
+        let burst = Self::message_burst();
+        let bucket = self
+            .messages
+            .get_or_insert_with(|| TokenBucket::new(rate, burst));
+        bucket.set_limit(rate, burst);
+        if bucket.try_consume(NetworkTime::local_time()) {
+            return true;
+        }
+        self.messages_limited += 1;
+        TOTAL_MESSAGES_LIMITED.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+
+    // 超出方法限制时返回 false
+    pub fn check_command(&mut self, function_hash: u16) -> bool {
+        let (rate, burst) = match METHOD_RATE_LIMITS.get(&function_hash) {
+            Some(limit) => *limit,
+            None => (Self::command_rate(), Self::command_burst()),
+        };
+        if rate <= 0.0 {
+            return true;
+        }
+        let bucket = self
+            .commands
+            .entry(function_hash)
+            .or_insert_with(|| TokenBucket::new(rate, burst));
+        bucket.set_limit(rate, burst);
+        if bucket.try_consume(NetworkTime::local_time()) {
+            return true;
+        }
+        self.commands_limited += 1;
+        TOTAL_COMMANDS_LIMITED.fetch_add(1, Ordering::Relaxed);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_burst_and_refill() {
+        let mut bucket = TokenBucket::new(2.0, 4.0);
+        let now = bucket.last_refill_time;
+
+        // 开始时可以连续消耗 burst 个
+        for _ in 0..4 {
+            assert!(bucket.try_consume(now));
+        }
+        assert!(!bucket.try_consume(now));
+
+        // 每秒补充 rate 个
+        assert!(!bucket.try_consume(now + 0.25));
+        assert!(bucket.try_consume(now + 0.5));
+        assert!(!bucket.try_consume(now + 0.5));
+        assert!(bucket.try_consume(now + 1.0));
+        assert!(!bucket.try_consume(now + 1.0));
+
+        // 最多补充到 burst 个
+        let later = now + 100.0;
+        for _ in 0..4 {
+            assert!(bucket.try_consume(later));
+        }
+        assert!(!bucket.try_consume(later));
+
+        // 时间倒退时不补充
+        assert!(!bucket.try_consume(now));
+    }
+
+    #[test]
+    fn test_token_bucket_set_limit() {
+        // burst 不小于 rate，且至少为 1
+        let mut bucket = TokenBucket::new(0.5, 0.0);
+        let now = bucket.last_refill_time;
+        assert!(bucket.try_consume(now));
+        assert!(!bucket.try_consume(now));
+        assert!(bucket.try_consume(now + 2.0));
+
+        let mut bucket = TokenBucket::new(10.0, 10.0);
+        let now = bucket.last_refill_time;
+        // 降低限制时剩余的令牌不超过新的 burst
+        bucket.set_limit(1.0, 2.0);
+        assert!(bucket.try_consume(now));
+        assert!(bucket.try_consume(now));
+        assert!(!bucket.try_consume(now));
+        assert!(bucket.try_consume(now + 1.0));
+    }
+}
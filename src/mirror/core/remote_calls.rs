@@ -45,7 +45,7 @@ impl Invoker {
     ) -> bool {
         self.type_id == type_id
             && self.call_type == remote_call_type
-            && self.function.eq(invoke_function)
+            && std::ptr::fn_addr_eq(self.function, *invoke_function)
     }
 }
 
@@ -0,0 +1,206 @@
+use crate::mirror::core::messages::SceneOperation;
+use crate::mirror::core::network_connection_to_client::NetworkConnectionToClient;
+use crate::mirror::core::transport::TransportError;
+use atomic::Atomic;
+use dashmap::DashMap;
+use lazy_static::lazy_static;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
+pub enum EventHandlerType {
+    OnConnectedEvent,
+    OnAuthenticatedEvent,
+    OnReadyEvent,
+    OnDisconnectedEvent,
+    OnErrorEvent,
+    OnTransportExceptionEvent,
+    OnSpawnEvent,
+    OnUnSpawnEvent,
+    OnOwnershipChangedEvent,
+    OnSceneChangedEvent,
+}
+
+// 服务器事件，连接相关事件携带连接的可变引用
+pub enum ServerEvent<'a> {
+    Connected(&'a mut NetworkConnectionToClient),
+    Authenticated(&'a mut NetworkConnectionToClient),
+    Ready(&'a mut NetworkConnectionToClient),
+    Disconnected(&'a mut NetworkConnectionToClient),
+    Error(&'a mut NetworkConnectionToClient, TransportError),
+    TransportException(&'a mut NetworkConnectionToClient, TransportError),
+    // identity 可能正被调用方持有，处理程序中 try_get 会返回 Locked
+    Spawned(u32),
+    UnSpawned(u32),
+    // 在 NetworkServer 的 update 中通知，此时没有持有 identity 和连接的锁
+    OwnershipChanged {
+        net_id: u32,
+        old_conn_id: u64,
+        new_conn_id: u64,
+    },
+    SceneChanged {
+        scene_name: &'a str,
+        operation: SceneOperation,
+    },
+}
+
+impl ServerEvent<'_> {
+    pub fn event_type(&self) -> EventHandlerType {
+        match self {
+            ServerEvent::Connected(_) => EventHandlerType::OnConnectedEvent,
+            ServerEvent::Authenticated(_) => EventHandlerType::OnAuthenticatedEvent,
+            ServerEvent::Ready(_) => EventHandlerType::OnReadyEvent,
+            ServerEvent::Disconnected(_) => EventHandlerType::OnDisconnectedEvent,
+            ServerEvent::Error(_, _) => EventHandlerType::OnErrorEvent,
+            ServerEvent::TransportException(_, _) => EventHandlerType::OnTransportExceptionEvent,
+            ServerEvent::Spawned(_) => EventHandlerType::OnSpawnEvent,
+            ServerEvent::UnSpawned(_) => EventHandlerType::OnUnSpawnEvent,
+            ServerEvent::OwnershipChanged { .. } => EventHandlerType::OnOwnershipChangedEvent,
+            ServerEvent::SceneChanged { .. } => EventHandlerType::OnSceneChangedEvent,
+        }
+    }
+
+    pub fn connection(&mut self) -> Option<&mut NetworkConnectionToClient> {
+        match self {
+            ServerEvent::Connected(conn)
+            | ServerEvent::Authenticated(conn)
+            | ServerEvent::Ready(conn)
+            | ServerEvent::Disconnected(conn)
+            | ServerEvent::Error(conn, _)
+            | ServerEvent::TransportException(conn, _) => Some(&mut **conn),
+            _ => None,
+        }
+    }
+
+    pub fn transport_error(&self) -> TransportError {
+        match self {
+            ServerEvent::Error(_, error) | ServerEvent::TransportException(_, error) => *error,
+            _ => TransportError::None,
+        }
+    }
+}
+
+type ServerEventHandler = dyn Fn(&mut ServerEvent) + Send + Sync;
+
+lazy_static! {
+    static ref SERVER_EVENT_HANDLERS: DashMap<EventHandlerType, Vec<(u64, Arc<ServerEventHandler>)>> =
+        DashMap::new();
+    static ref NEXT_SUBSCRIPTION_ID: Atomic<u64> = Atomic::new(1);
+}
+
+// 订阅句柄，调用 unsubscribe 取消订阅
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EventSubscription {
+    event_type: EventHandlerType,
+    id: u64,
+}
+
+impl EventSubscription {
+    pub fn event_type(&self) -> EventHandlerType {
+        self.event_type
+    }
+    pub fn unsubscribe(self) -> bool {
+        ServerEvents::unsubscribe(self)
+    }
+}
+
+// 服务器事件总线，每个事件可以有任意多个订阅者，按订阅顺序调用
+pub struct ServerEvents;
+
+impl ServerEvents {
+    pub fn subscribe<F>(event_type: EventHandlerType, handler: F) -> EventSubscription
+    where
+        F: Fn(&mut ServerEvent) + Send + Sync + 'static,
+    {
+        let id = NEXT_SUBSCRIPTION_ID.fetch_add(1, Ordering::Relaxed);
+        SERVER_EVENT_HANDLERS
+            .entry(event_type)
+            .or_default()
+            .push((id, Arc::new(handler)));
+        EventSubscription { event_type, id }
+    }
+
+    // 连接事件的简便订阅，处理程序签名与旧的 EventHandler 一致
+    pub fn subscribe_connection<F>(event_type: EventHandlerType, handler: F) -> EventSubscription
+    where
+        F: Fn(&mut NetworkConnectionToClient, TransportError) + Send + Sync + 'static,
+    {
+        Self::subscribe(event_type, move |event| {
+            let transport_error = event.transport_error();
+            if let Some(conn) = event.connection() {
+                handler(conn, transport_error);
+            }
+        })
+    }
+
+    pub fn unsubscribe(subscription: EventSubscription) -> bool {
+        match SERVER_EVENT_HANDLERS.get_mut(&subscription.event_type) {
+            Some(mut handlers) => {
+                let len = handlers.len();
+                handlers.retain(|(id, _)| *id != subscription.id);
+                handlers.len() != len
+            }
+            None => false,
+        }
+    }
+
+    pub fn has_subscribers(event_type: EventHandlerType) -> bool {
+        match SERVER_EVENT_HANDLERS.get(&event_type) {
+            Some(handlers) => !handlers.is_empty(),
+            None => false,
+        }
+    }
+
+    pub fn clear() {
+        SERVER_EVENT_HANDLERS.clear();
+    }
+
+    // 调用所有订阅者，返回是否有订阅者
+    pub fn invoke(event: &mut ServerEvent) -> bool {
+        // 先复制出处理程序，允许处理程序内部订阅或取消订阅
+        let handlers: Vec<Arc<ServerEventHandler>> =
+            match SERVER_EVENT_HANDLERS.get(&event.event_type()) {
+                Some(handlers) => handlers
+                    .iter()
+                    .map(|(_, handler)| handler.clone())
+                    .collect(),
+                None => return false,
+            };
+        for handler in handlers.iter() {
+            handler(event);
+        }
+        !handlers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_events_subscribe_unsubscribe() {
+        let counter = Arc::new(Atomic::new(0u32));
+        let first_counter = counter.clone();
+        let first = ServerEvents::subscribe(EventHandlerType::OnSpawnEvent, move |_| {
+            first_counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let second_counter = counter.clone();
+        let second = ServerEvents::subscribe(EventHandlerType::OnSpawnEvent, move |event| {
+            if let ServerEvent::Spawned(net_id) = event {
+                second_counter.fetch_add(*net_id, Ordering::Relaxed);
+            }
+        });
+
+        assert!(ServerEvents::invoke(&mut ServerEvent::Spawned(10)));
+        assert_eq!(counter.load(Ordering::Relaxed), 11);
+
+        assert!(first.unsubscribe());
+        assert!(!first.unsubscribe());
+        ServerEvents::invoke(&mut ServerEvent::Spawned(10));
+        assert_eq!(counter.load(Ordering::Relaxed), 21);
+
+        assert!(second.unsubscribe());
+        assert!(!ServerEvents::invoke(&mut ServerEvent::Spawned(10)));
+        assert_eq!(counter.load(Ordering::Relaxed), 21);
+    }
+}
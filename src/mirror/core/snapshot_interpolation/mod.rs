@@ -1,4 +1,5 @@
 pub mod snapshot;
 pub mod time_snapshot;
+#[allow(clippy::module_inception)]
 pub mod snapshot_interpolation;
 pub mod snapshot_interpolation_settings;
\ No newline at end of file
@@ -54,8 +54,7 @@ impl SnapshotInterpolation {
         // we want to convert to buffer_time_multiplier later.
         let multiples = interval_with_jitter / send_interval;
         // add the tolerance
-        let safe_zone = multiples + dynamic_adjustment_tolerance;
-        safe_zone
+        multiples + dynamic_adjustment_tolerance
     }
 
     pub fn insert_if_not_exists<T>(
@@ -88,6 +87,7 @@ impl SnapshotInterpolation {
         local_timeline.max(lower_bound).min(upper_bound)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn insert_and_adjust<T>(
         buffer: &mut BTreeMap<OrderedFloat<f64>, T>,
         buffer_limit: usize,
@@ -105,11 +105,11 @@ impl SnapshotInterpolation {
     ) where
         T: Snapshot,
     {
-        if buffer.len() == 0 {
+        if buffer.is_empty() {
             *local_timeline = snapshot.remote_time() - buffer_time;
         }
 
-        if Self::insert_if_not_exists(buffer, buffer_limit, snapshot.clone()) {
+        if Self::insert_if_not_exists(buffer, buffer_limit, snapshot) {
             if buffer.len() >= 2 {
                 // 拿到倒数第二个和最后一个
                 let previous_local_time = buffer.iter().rev().nth(1).unwrap().1.local_time();
@@ -186,7 +186,7 @@ impl SnapshotInterpolation {
         let (from, to, t) = Self::sample(buffer, local_timeline);
         if from == to {
             let snapshot = buffer.remove(&from).unwrap();
-            return (snapshot.clone(), snapshot.clone(), t);
+            return (snapshot, snapshot, t);
         }
         let from_snapshot = buffer.remove(&from).unwrap();
         let to_snapshot = buffer.get(&to).unwrap();
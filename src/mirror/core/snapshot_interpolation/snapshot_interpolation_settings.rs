@@ -11,8 +11,8 @@ pub struct SnapshotInterpolationSettings {
     pub delivery_time_ema_duration: i32,
 }
 
-impl SnapshotInterpolationSettings {
-    pub fn default() -> Self {
+impl Default for SnapshotInterpolationSettings {
+    fn default() -> Self {
         SnapshotInterpolationSettings {
             buffer_time_multiplier: 2.0,
             buffer_limit: 32,
@@ -1,7 +1,7 @@
 use crate::log_warn;
 use crate::mirror::core::snapshot_interpolation::snapshot::Snapshot;
 
-#[derive(Debug, Clone, PartialEq, PartialOrd, Copy)]
+#[derive(Debug, Clone, PartialEq, Copy)]
 pub struct TimeSnapshot {
     pub remote_time: f64,
     pub local_time: f64,
@@ -27,6 +27,11 @@ impl Ord for TimeSnapshot {
         }
     }
 }
+impl PartialOrd for TimeSnapshot {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
 impl Snapshot for TimeSnapshot {
     fn local_time(&self) -> f64 {
@@ -0,0 +1,105 @@
+use std::collections::VecDeque;
+
+// 已发送但还未确认的状态最多保存的数量
+const MAX_PENDING_STATES: usize = 32;
+
+// 每个连接对每个 identity 的状态基线
+#[derive(Debug, Default)]
+pub struct StateBaseline {
+    // 客户端已确认的 tick 和完整状态
+    acked: Option<(u32, Vec<u8>)>,
+    // 已发送但还未确认的 tick 和完整状态
+    pending: VecDeque<(u32, Vec<u8>)>,
+}
+
+impl StateBaseline {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn acked(&self) -> Option<&(u32, Vec<u8>)> {
+        self.acked.as_ref()
+    }
+
+    // 客户端已确认的状态和当前状态相同时不需要再发送
+    pub fn is_synced(&self, state: &[u8]) -> bool {
+        match &self.acked {
+            Some((_, acked_state)) => acked_state.as_slice() == state,
+            None => false,
+        }
+    }
+
+    pub fn add_pending(&mut self, tick: u32, state: Vec<u8>) {
+        if let Some((last_tick, _)) = self.pending.back() {
+            if *last_tick >= tick {
+                return;
+            }
+        }
+        self.pending.push_back((tick, state));
+        while self.pending.len() > MAX_PENDING_STATES {
+            self.pending.pop_front();
+        }
+    }
+
+    // 确认 tick，成为新的基线，并丢弃更早的未确认状态
+    pub fn ack(&mut self, tick: u32) -> bool {
+        if let Some((acked_tick, _)) = &self.acked {
+            if tick <= *acked_tick {
+                return false;
+            }
+        }
+        match self.pending.iter().position(|(pending_tick, _)| *pending_tick == tick) {
+            Some(index) => {
+                self.acked = self.pending.remove(index);
+                self.pending.retain(|(pending_tick, _)| *pending_tick > tick);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_baseline_ack() {
+        let mut baseline = StateBaseline::new();
+        assert!(baseline.acked().is_none());
+        assert!(!baseline.is_synced(&[1]));
+
+        baseline.add_pending(1, vec![1]);
+        baseline.add_pending(2, vec![2]);
+        baseline.add_pending(3, vec![3]);
+        // tick 没有增加时忽略
+        baseline.add_pending(3, vec![4]);
+
+        // 确认 2 后丢弃更早的 1
+        assert!(baseline.ack(2));
+        assert_eq!(baseline.acked(), Some(&(2, vec![2])));
+        assert!(baseline.is_synced(&[2]));
+        assert!(!baseline.ack(1));
+        // 重复和过期的确认
+        assert!(!baseline.ack(2));
+        // 没有发送过的 tick
+        assert!(!baseline.ack(4));
+
+        assert!(baseline.ack(3));
+        assert_eq!(baseline.acked(), Some(&(3, vec![3])));
+        assert!(baseline.pending.is_empty());
+    }
+
+    #[test]
+    fn test_state_baseline_pending_limit() {
+        let mut baseline = StateBaseline::new();
+        for tick in 1..=(MAX_PENDING_STATES as u32 + 1) {
+            baseline.add_pending(tick, vec![tick as u8]);
+        }
+        assert_eq!(baseline.pending.len(), MAX_PENDING_STATES);
+        // 最早的状态已经被丢弃
+        assert!(!baseline.ack(1));
+        assert!(baseline.ack(2));
+    }
+}
@@ -0,0 +1,362 @@
+use crate::log_error;
+use crate::mirror::core::network_field::NetworkField;
+use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTryTrait};
+use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
+use crate::mirror::core::sync_object::SyncObject;
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
+
+// 与 Mirror SyncIDictionary<TKey, TValue>.Operation 顺序一致
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SyncDictionaryOperation {
+    Add = 0,
+    Clear = 1,
+    Remove = 2,
+    Set = 3,
+}
+
+impl SyncDictionaryOperation {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(SyncDictionaryOperation::Add),
+            1 => Some(SyncDictionaryOperation::Clear),
+            2 => Some(SyncDictionaryOperation::Remove),
+            3 => Some(SyncDictionaryOperation::Set),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Change<K, V> {
+    operation: SyncDictionaryOperation,
+    // Clear 没有键值
+    entry: Option<(K, V)>,
+}
+
+// 参数: 操作, 键, 值（Remove 时为旧值，Clear 时为 None）
+pub type SyncDictionaryCallback<K, V> =
+    Box<dyn Fn(SyncDictionaryOperation, Option<&K>, Option<&V>) + Send + Sync>;
+
+pub struct SyncDictionary<K, V> {
+    objects: HashMap<K, V>,
+    changes: Vec<Change<K, V>>,
+    // 完整状态之后还要跳过的修改数
+    changes_ahead: u32,
+    recording: bool,
+    callback: Option<SyncDictionaryCallback<K, V>>,
+}
+
+impl<K: Debug, V: Debug> Debug for SyncDictionary<K, V> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncDictionary")
+            .field("objects", &self.objects)
+            .field("changes", &self.changes.len())
+            .field("changes_ahead", &self.changes_ahead)
+            .field("recording", &self.recording)
+            .finish()
+    }
+}
+
+impl<K, V> Default for SyncDictionary<K, V> {
+    fn default() -> Self {
+        Self {
+            objects: HashMap::new(),
+            changes: Vec::new(),
+            changes_ahead: 0,
+            recording: true,
+            callback: None,
+        }
+    }
+}
+
+impl<K, V> SyncDictionary<K, V>
+where
+    K: NetworkField + Clone + Eq + Hash + Debug + Send + Sync + 'static,
+    V: NetworkField + Clone + PartialEq + Debug + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_callback(&mut self, callback: SyncDictionaryCallback<K, V>) {
+        self.callback = Some(callback);
+    }
+
+    fn add_operation(&mut self, operation: SyncDictionaryOperation, entry: Option<(K, V)>) {
+        if let Some(callback) = self.callback.as_ref() {
+            match entry.as_ref() {
+                None => callback(operation, None, None),
+                Some((key, value)) => callback(operation, Some(key), Some(value)),
+            }
+        }
+        if self.recording {
+            self.changes.push(Change { operation, entry });
+        }
+    }
+
+    // 键已存在时为 OP_SET，否则为 OP_ADD
+    pub fn insert(&mut self, key: K, value: V) {
+        let operation = match self.objects.insert(key.clone(), value.clone()) {
+            None => SyncDictionaryOperation::Add,
+            Some(old_value) if old_value == value => return,
+            Some(_) => SyncDictionaryOperation::Set,
+        };
+        self.add_operation(operation, Some((key, value)));
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let value = self.objects.remove(key)?;
+        self.add_operation(
+            SyncDictionaryOperation::Remove,
+            Some((key.clone(), value.clone())),
+        );
+        Some(value)
+    }
+
+    pub fn clear(&mut self) {
+        self.objects.clear();
+        self.add_operation(SyncDictionaryOperation::Clear, None);
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.objects.get(key)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.objects.contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn iter(&self) -> std::collections::hash_map::Iter<'_, K, V> {
+        self.objects.iter()
+    }
+
+    fn read_entry(reader: &mut NetworkReader) -> Option<(K, V)> {
+        match (K::read_field(reader), V::read_field(reader)) {
+            (Ok(key), Ok(value)) => Some((key, value)),
+            (Err(err), _) | (_, Err(err)) => {
+                log_error!("Failed to deserialize SyncDictionary entry: {}", err);
+                None
+            }
+        }
+    }
+}
+
+impl<K, V> SyncObject for SyncDictionary<K, V>
+where
+    K: NetworkField + Clone + Eq + Hash + Debug + Send + Sync + 'static,
+    V: NetworkField + Clone + PartialEq + Debug + Send + Sync + 'static,
+{
+    fn sub_class_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        "Mirror.SyncDictionary`2"
+    }
+
+    fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    fn set_recording(&mut self, value: bool) {
+        self.recording = value;
+    }
+
+    fn is_dirty(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    fn clear_changes(&mut self) {
+        self.changes.clear();
+    }
+
+    fn on_serialize_all(&self, writer: &mut NetworkWriter) {
+        (self.objects.len() as u32).write_field(writer);
+        for (key, value) in self.objects.iter() {
+            key.write_field(writer);
+            value.write_field(writer);
+        }
+        // 客户端收到完整状态后需要跳过的修改数
+        (self.changes.len() as u32).write_field(writer);
+    }
+
+    fn on_serialize_delta(&self, writer: &mut NetworkWriter) {
+        (self.changes.len() as u32).write_field(writer);
+        for change in self.changes.iter() {
+            writer.write_byte(change.operation as u8);
+            if let Some((key, value)) = change.entry.as_ref() {
+                key.write_field(writer);
+                value.write_field(writer);
+            }
+        }
+    }
+
+    fn on_deserialize_all(&mut self, reader: &mut NetworkReader) -> bool {
+        self.objects.clear();
+        self.changes.clear();
+        let count = match u32::read_field(reader) {
+            Ok(count) => count,
+            Err(err) => {
+                log_error!("Failed to deserialize SyncDictionary count: {}", err);
+                return false;
+            }
+        };
+        for _ in 0..count {
+            match Self::read_entry(reader) {
+                None => return false,
+                Some((key, value)) => {
+                    self.objects.insert(key, value);
+                }
+            }
+        }
+        match u32::read_field(reader) {
+            Ok(changes_ahead) => self.changes_ahead = changes_ahead,
+            Err(err) => {
+                log_error!(
+                    "Failed to deserialize SyncDictionary changes ahead: {}",
+                    err
+                );
+                return false;
+            }
+        }
+        true
+    }
+
+    fn on_deserialize_delta(&mut self, reader: &mut NetworkReader) -> bool {
+        let changes_count = match u32::read_field(reader) {
+            Ok(changes_count) => changes_count,
+            Err(err) => {
+                log_error!("Failed to deserialize SyncDictionary changes: {}", err);
+                return false;
+            }
+        };
+        for _ in 0..changes_count {
+            let operation = match reader.try_read_byte() {
+                Ok(operation) => operation,
+                Err(err) => {
+                    log_error!("Failed to deserialize SyncDictionary operation: {}", err);
+                    return false;
+                }
+            };
+            // 完整状态已经包含了这些修改
+            let apply = self.changes_ahead == 0;
+            match SyncDictionaryOperation::from_u8(operation) {
+                Some(SyncDictionaryOperation::Add) | Some(SyncDictionaryOperation::Set) => {
+                    let Some((key, value)) = Self::read_entry(reader) else {
+                        return false;
+                    };
+                    if apply {
+                        self.insert(key, value);
+                    }
+                }
+                Some(SyncDictionaryOperation::Remove) => {
+                    let Some((key, _)) = Self::read_entry(reader) else {
+                        return false;
+                    };
+                    if apply {
+                        self.remove(&key);
+                    }
+                }
+                Some(SyncDictionaryOperation::Clear) => {
+                    if apply {
+                        self.clear();
+                    }
+                }
+                None => {
+                    log_error!("Unknown SyncDictionary operation: {}", operation);
+                    return false;
+                }
+            }
+            if !apply {
+                self.changes_ahead -= 1;
+            }
+        }
+        true
+    }
+
+    fn reset(&mut self) {
+        self.objects.clear();
+        self.changes.clear();
+        self.changes_ahead = 0;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(dictionary: &SyncDictionary<i32, i32>) -> NetworkReader {
+        let mut writer = NetworkWriter::new();
+        dictionary.on_serialize_delta(&mut writer);
+        NetworkReader::new_with_array_segment(writer.to_array_segment())
+    }
+
+    fn sorted(dictionary: &SyncDictionary<i32, i32>) -> Vec<(i32, i32)> {
+        let mut entries = dictionary
+            .iter()
+            .map(|(key, value)| (*key, *value))
+            .collect::<Vec<_>>();
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn test_sync_dictionary_full_state_round_trip() {
+        let mut server = SyncDictionary::<i32, i32>::new();
+        server.insert(1, 10);
+        server.insert(2, 20);
+        server.clear_changes();
+
+        let mut writer = NetworkWriter::new();
+        server.on_serialize_all(&mut writer);
+        let mut client = SyncDictionary::<i32, i32>::new();
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        assert!(client.on_deserialize_all(&mut reader));
+        assert_eq!(sorted(&client), vec![(1, 10), (2, 20)]);
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn test_sync_dictionary_delta_round_trip() {
+        let mut server = SyncDictionary::<i32, i32>::new();
+        let mut client = SyncDictionary::<i32, i32>::new();
+        server.insert(1, 10);
+        server.insert(2, 20);
+        server.insert(2, 30);
+        server.remove(&1);
+        assert!(server.is_dirty());
+
+        let mut writer = NetworkWriter::new();
+        server.on_serialize_delta(&mut writer);
+        // changes=4, OP_ADD 1 10, OP_ADD 2 20, OP_SET 2 30, OP_REMOVE 1 10
+        assert_eq!(
+            writer.to_array_segment(),
+            vec![4, 0, 2, 20, 0, 4, 40, 3, 4, 60, 2, 2, 20]
+        );
+        assert!(client.on_deserialize_delta(&mut delta(&server)));
+        assert_eq!(sorted(&client), vec![(2, 30)]);
+
+        server.clear_changes();
+        // 值相同不产生修改
+        server.insert(2, 30);
+        assert!(!server.is_dirty());
+        server.clear();
+        assert!(client.on_deserialize_delta(&mut delta(&server)));
+        assert!(client.is_empty());
+    }
+}
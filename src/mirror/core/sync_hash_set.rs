@@ -0,0 +1,335 @@
+use crate::log_error;
+use crate::mirror::core::network_field::NetworkField;
+use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTryTrait};
+use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
+use crate::mirror::core::sync_object::SyncObject;
+use std::any::Any;
+use std::collections::HashSet;
+use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
+
+// 与 Mirror SyncSet<T>.Operation 顺序一致
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SyncHashSetOperation {
+    Add = 0,
+    Remove = 1,
+    Clear = 2,
+}
+
+impl SyncHashSetOperation {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(SyncHashSetOperation::Add),
+            1 => Some(SyncHashSetOperation::Remove),
+            2 => Some(SyncHashSetOperation::Clear),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Change<T> {
+    operation: SyncHashSetOperation,
+    item: Option<T>,
+}
+
+// 参数: 操作, 元素（Clear 时为 None）
+pub type SyncHashSetCallback<T> = Box<dyn Fn(SyncHashSetOperation, Option<&T>) + Send + Sync>;
+
+pub struct SyncHashSet<T> {
+    objects: HashSet<T>,
+    changes: Vec<Change<T>>,
+    // 完整状态之后还要跳过的修改数
+    changes_ahead: u32,
+    recording: bool,
+    callback: Option<SyncHashSetCallback<T>>,
+}
+
+impl<T: Debug> Debug for SyncHashSet<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncHashSet")
+            .field("objects", &self.objects)
+            .field("changes", &self.changes.len())
+            .field("changes_ahead", &self.changes_ahead)
+            .field("recording", &self.recording)
+            .finish()
+    }
+}
+
+impl<T> Default for SyncHashSet<T> {
+    fn default() -> Self {
+        Self {
+            objects: HashSet::new(),
+            changes: Vec::new(),
+            changes_ahead: 0,
+            recording: true,
+            callback: None,
+        }
+    }
+}
+
+impl<T> SyncHashSet<T>
+where
+    T: NetworkField + Clone + Eq + Hash + Debug + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_callback(&mut self, callback: SyncHashSetCallback<T>) {
+        self.callback = Some(callback);
+    }
+
+    fn add_operation(&mut self, operation: SyncHashSetOperation, item: Option<T>) {
+        if let Some(callback) = self.callback.as_ref() {
+            callback(operation, item.as_ref());
+        }
+        if self.recording {
+            self.changes.push(Change { operation, item });
+        }
+    }
+
+    pub fn add(&mut self, item: T) -> bool {
+        if !self.objects.insert(item.clone()) {
+            return false;
+        }
+        self.add_operation(SyncHashSetOperation::Add, Some(item));
+        true
+    }
+
+    pub fn remove(&mut self, item: &T) -> bool {
+        if !self.objects.remove(item) {
+            return false;
+        }
+        self.add_operation(SyncHashSetOperation::Remove, Some(item.clone()));
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.objects.clear();
+        self.add_operation(SyncHashSetOperation::Clear, None);
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        self.objects.contains(item)
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn iter(&self) -> std::collections::hash_set::Iter<'_, T> {
+        self.objects.iter()
+    }
+
+    fn read_item(reader: &mut NetworkReader) -> Option<T> {
+        match T::read_field(reader) {
+            Ok(item) => Some(item),
+            Err(err) => {
+                log_error!("Failed to deserialize SyncHashSet item: {}", err);
+                None
+            }
+        }
+    }
+}
+
+impl<T> SyncObject for SyncHashSet<T>
+where
+    T: NetworkField + Clone + Eq + Hash + Debug + Send + Sync + 'static,
+{
+    fn sub_class_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        "Mirror.SyncHashSet`1"
+    }
+
+    fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    fn set_recording(&mut self, value: bool) {
+        self.recording = value;
+    }
+
+    fn is_dirty(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    fn clear_changes(&mut self) {
+        self.changes.clear();
+    }
+
+    fn on_serialize_all(&self, writer: &mut NetworkWriter) {
+        (self.objects.len() as u32).write_field(writer);
+        for object in self.objects.iter() {
+            object.write_field(writer);
+        }
+        // 客户端收到完整状态后需要跳过的修改数
+        (self.changes.len() as u32).write_field(writer);
+    }
+
+    fn on_serialize_delta(&self, writer: &mut NetworkWriter) {
+        (self.changes.len() as u32).write_field(writer);
+        for change in self.changes.iter() {
+            writer.write_byte(change.operation as u8);
+            if let Some(item) = change.item.as_ref() {
+                item.write_field(writer);
+            }
+        }
+    }
+
+    fn on_deserialize_all(&mut self, reader: &mut NetworkReader) -> bool {
+        self.objects.clear();
+        self.changes.clear();
+        let count = match u32::read_field(reader) {
+            Ok(count) => count,
+            Err(err) => {
+                log_error!("Failed to deserialize SyncHashSet count: {}", err);
+                return false;
+            }
+        };
+        for _ in 0..count {
+            match Self::read_item(reader) {
+                None => return false,
+                Some(item) => {
+                    self.objects.insert(item);
+                }
+            }
+        }
+        match u32::read_field(reader) {
+            Ok(changes_ahead) => self.changes_ahead = changes_ahead,
+            Err(err) => {
+                log_error!("Failed to deserialize SyncHashSet changes ahead: {}", err);
+                return false;
+            }
+        }
+        true
+    }
+
+    fn on_deserialize_delta(&mut self, reader: &mut NetworkReader) -> bool {
+        let changes_count = match u32::read_field(reader) {
+            Ok(changes_count) => changes_count,
+            Err(err) => {
+                log_error!("Failed to deserialize SyncHashSet changes: {}", err);
+                return false;
+            }
+        };
+        for _ in 0..changes_count {
+            let operation = match reader.try_read_byte() {
+                Ok(operation) => operation,
+                Err(err) => {
+                    log_error!("Failed to deserialize SyncHashSet operation: {}", err);
+                    return false;
+                }
+            };
+            // 完整状态已经包含了这些修改
+            let apply = self.changes_ahead == 0;
+            match SyncHashSetOperation::from_u8(operation) {
+                Some(SyncHashSetOperation::Add) => {
+                    let Some(item) = Self::read_item(reader) else {
+                        return false;
+                    };
+                    if apply {
+                        self.add(item);
+                    }
+                }
+                Some(SyncHashSetOperation::Remove) => {
+                    let Some(item) = Self::read_item(reader) else {
+                        return false;
+                    };
+                    if apply {
+                        self.remove(&item);
+                    }
+                }
+                Some(SyncHashSetOperation::Clear) => {
+                    if apply {
+                        self.clear();
+                    }
+                }
+                None => {
+                    log_error!("Unknown SyncHashSet operation: {}", operation);
+                    return false;
+                }
+            }
+            if !apply {
+                self.changes_ahead -= 1;
+            }
+        }
+        true
+    }
+
+    fn reset(&mut self) {
+        self.objects.clear();
+        self.changes.clear();
+        self.changes_ahead = 0;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(set: &SyncHashSet<i32>) -> NetworkReader {
+        let mut writer = NetworkWriter::new();
+        set.on_serialize_delta(&mut writer);
+        NetworkReader::new_with_array_segment(writer.to_array_segment())
+    }
+
+    fn sorted(set: &SyncHashSet<i32>) -> Vec<i32> {
+        let mut items = set.iter().copied().collect::<Vec<_>>();
+        items.sort();
+        items
+    }
+
+    #[test]
+    fn test_sync_hash_set_full_state_round_trip() {
+        let mut server = SyncHashSet::<i32>::new();
+        server.add(1);
+        server.add(2);
+        server.clear_changes();
+
+        let mut writer = NetworkWriter::new();
+        server.on_serialize_all(&mut writer);
+        let mut client = SyncHashSet::<i32>::new();
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        assert!(client.on_deserialize_all(&mut reader));
+        assert_eq!(sorted(&client), vec![1, 2]);
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn test_sync_hash_set_delta_round_trip() {
+        let mut server = SyncHashSet::<i32>::new();
+        let mut client = SyncHashSet::<i32>::new();
+        server.add(1);
+        server.add(2);
+        server.remove(&1);
+        assert!(server.is_dirty());
+
+        let mut writer = NetworkWriter::new();
+        server.on_serialize_delta(&mut writer);
+        // changes=3, OP_ADD 1, OP_ADD 2, OP_REMOVE 1
+        assert_eq!(writer.to_array_segment(), vec![3, 0, 2, 0, 4, 1, 2]);
+        assert!(client.on_deserialize_delta(&mut delta(&server)));
+        assert_eq!(sorted(&client), vec![2]);
+
+        server.clear_changes();
+        assert!(!server.is_dirty());
+        // 重复添加不产生修改
+        assert!(!server.add(2));
+        assert!(!server.is_dirty());
+        server.clear();
+        assert!(client.on_deserialize_delta(&mut delta(&server)));
+        assert!(client.is_empty());
+    }
+}
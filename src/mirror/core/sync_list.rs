@@ -0,0 +1,430 @@
+use crate::log_error;
+use crate::mirror::core::network_field::NetworkField;
+use crate::mirror::core::network_reader::{NetworkReader, NetworkReaderTryTrait};
+use crate::mirror::core::network_writer::{NetworkWriter, NetworkWriterTrait};
+use crate::mirror::core::sync_object::SyncObject;
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+
+// 与 Mirror SyncList<T>.Operation 顺序一致
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SyncListOperation {
+    Add = 0,
+    Set = 1,
+    Insert = 2,
+    RemoveAt = 3,
+    Clear = 4,
+}
+
+impl SyncListOperation {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(SyncListOperation::Add),
+            1 => Some(SyncListOperation::Set),
+            2 => Some(SyncListOperation::Insert),
+            3 => Some(SyncListOperation::RemoveAt),
+            4 => Some(SyncListOperation::Clear),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Change<T> {
+    operation: SyncListOperation,
+    index: usize,
+    item: Option<T>,
+}
+
+// 参数: 操作, 下标, 旧值, 新值
+pub type SyncListCallback<T> =
+    Box<dyn Fn(SyncListOperation, usize, Option<&T>, Option<&T>) + Send + Sync>;
+
+pub struct SyncList<T> {
+    objects: Vec<T>,
+    changes: Vec<Change<T>>,
+    // 完整状态之后还要跳过的修改数
+    changes_ahead: u32,
+    recording: bool,
+    callback: Option<SyncListCallback<T>>,
+}
+
+impl<T: Debug> Debug for SyncList<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncList")
+            .field("objects", &self.objects)
+            .field("changes", &self.changes.len())
+            .field("changes_ahead", &self.changes_ahead)
+            .field("recording", &self.recording)
+            .finish()
+    }
+}
+
+impl<T> Default for SyncList<T> {
+    fn default() -> Self {
+        Self {
+            objects: Vec::new(),
+            changes: Vec::new(),
+            changes_ahead: 0,
+            recording: true,
+            callback: None,
+        }
+    }
+}
+
+impl<T> SyncList<T>
+where
+    T: NetworkField + Clone + PartialEq + Debug + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_callback(&mut self, callback: SyncListCallback<T>) {
+        self.callback = Some(callback);
+    }
+
+    fn add_operation(
+        &mut self,
+        operation: SyncListOperation,
+        index: usize,
+        old_item: Option<T>,
+        new_item: Option<T>,
+    ) {
+        if self.recording {
+            self.changes.push(Change {
+                operation,
+                index,
+                item: new_item.clone(),
+            });
+        }
+        if let Some(callback) = self.callback.as_ref() {
+            callback(operation, index, old_item.as_ref(), new_item.as_ref());
+        }
+    }
+
+    pub fn add(&mut self, item: T) {
+        self.objects.push(item.clone());
+        self.add_operation(
+            SyncListOperation::Add,
+            self.objects.len() - 1,
+            None,
+            Some(item),
+        );
+    }
+
+    pub fn insert(&mut self, index: usize, item: T) -> bool {
+        if index > self.objects.len() {
+            return false;
+        }
+        self.objects.insert(index, item.clone());
+        self.add_operation(SyncListOperation::Insert, index, None, Some(item));
+        true
+    }
+
+    pub fn set(&mut self, index: usize, item: T) -> bool {
+        match self.objects.get_mut(index) {
+            None => false,
+            Some(current) => {
+                // 值没有变化时不产生修改
+                if *current == item {
+                    return true;
+                }
+                let old_item = std::mem::replace(current, item.clone());
+                self.add_operation(SyncListOperation::Set, index, Some(old_item), Some(item));
+                true
+            }
+        }
+    }
+
+    pub fn remove_at(&mut self, index: usize) -> Option<T> {
+        if index >= self.objects.len() {
+            return None;
+        }
+        let old_item = self.objects.remove(index);
+        self.add_operation(
+            SyncListOperation::RemoveAt,
+            index,
+            Some(old_item.clone()),
+            None,
+        );
+        Some(old_item)
+    }
+
+    pub fn remove(&mut self, item: &T) -> bool {
+        match self.objects.iter().position(|object| object == item) {
+            None => false,
+            Some(index) => self.remove_at(index).is_some(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.objects.clear();
+        self.add_operation(SyncListOperation::Clear, 0, None, None);
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.objects.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn contains(&self, item: &T) -> bool {
+        self.objects.contains(item)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.objects.iter()
+    }
+
+    fn read_item(reader: &mut NetworkReader) -> Option<T> {
+        match T::read_field(reader) {
+            Ok(item) => Some(item),
+            Err(err) => {
+                log_error!("Failed to deserialize SyncList item: {}", err);
+                None
+            }
+        }
+    }
+
+    fn read_index(reader: &mut NetworkReader) -> Option<usize> {
+        match u32::read_field(reader) {
+            Ok(index) => Some(index as usize),
+            Err(err) => {
+                log_error!("Failed to deserialize SyncList index: {}", err);
+                None
+            }
+        }
+    }
+}
+
+impl<T> SyncObject for SyncList<T>
+where
+    T: NetworkField + Clone + PartialEq + Debug + Send + Sync + 'static,
+{
+    fn sub_class_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        "Mirror.SyncList`1"
+    }
+
+    fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    fn set_recording(&mut self, value: bool) {
+        self.recording = value;
+    }
+
+    fn is_dirty(&self) -> bool {
+        !self.changes.is_empty()
+    }
+
+    fn clear_changes(&mut self) {
+        self.changes.clear();
+    }
+
+    fn on_serialize_all(&self, writer: &mut NetworkWriter) {
+        (self.objects.len() as u32).write_field(writer);
+        for object in self.objects.iter() {
+            object.write_field(writer);
+        }
+        // 客户端收到完整状态后需要跳过的修改数
+        (self.changes.len() as u32).write_field(writer);
+    }
+
+    fn on_serialize_delta(&self, writer: &mut NetworkWriter) {
+        (self.changes.len() as u32).write_field(writer);
+        for change in self.changes.iter() {
+            writer.write_byte(change.operation as u8);
+            match change.operation {
+                SyncListOperation::Add => {
+                    if let Some(item) = change.item.as_ref() {
+                        item.write_field(writer);
+                    }
+                }
+                SyncListOperation::Set | SyncListOperation::Insert => {
+                    (change.index as u32).write_field(writer);
+                    if let Some(item) = change.item.as_ref() {
+                        item.write_field(writer);
+                    }
+                }
+                SyncListOperation::RemoveAt => {
+                    (change.index as u32).write_field(writer);
+                }
+                SyncListOperation::Clear => {}
+            }
+        }
+    }
+
+    fn on_deserialize_all(&mut self, reader: &mut NetworkReader) -> bool {
+        self.objects.clear();
+        self.changes.clear();
+        let count = match Self::read_index(reader) {
+            None => return false,
+            Some(count) => count,
+        };
+        for _ in 0..count {
+            match Self::read_item(reader) {
+                None => return false,
+                Some(item) => self.objects.push(item),
+            }
+        }
+        match u32::read_field(reader) {
+            Ok(changes_ahead) => self.changes_ahead = changes_ahead,
+            Err(err) => {
+                log_error!("Failed to deserialize SyncList changes ahead: {}", err);
+                return false;
+            }
+        }
+        true
+    }
+
+    fn on_deserialize_delta(&mut self, reader: &mut NetworkReader) -> bool {
+        let changes_count = match Self::read_index(reader) {
+            None => return false,
+            Some(changes_count) => changes_count,
+        };
+        for _ in 0..changes_count {
+            let operation = match reader.try_read_byte() {
+                Ok(operation) => operation,
+                Err(err) => {
+                    log_error!("Failed to deserialize SyncList operation: {}", err);
+                    return false;
+                }
+            };
+            // 完整状态已经包含了这些修改
+            let apply = self.changes_ahead == 0;
+            match SyncListOperation::from_u8(operation) {
+                Some(SyncListOperation::Add) => {
+                    let Some(item) = Self::read_item(reader) else {
+                        return false;
+                    };
+                    if apply {
+                        self.add(item);
+                    }
+                }
+                Some(SyncListOperation::Set) => {
+                    let Some(index) = Self::read_index(reader) else {
+                        return false;
+                    };
+                    let Some(item) = Self::read_item(reader) else {
+                        return false;
+                    };
+                    if apply && !self.set(index, item) {
+                        log_error!("SyncList set index {} out of range", index);
+                        return false;
+                    }
+                }
+                Some(SyncListOperation::Insert) => {
+                    let Some(index) = Self::read_index(reader) else {
+                        return false;
+                    };
+                    let Some(item) = Self::read_item(reader) else {
+                        return false;
+                    };
+                    if apply && !self.insert(index, item) {
+                        log_error!("SyncList insert index {} out of range", index);
+                        return false;
+                    }
+                }
+                Some(SyncListOperation::RemoveAt) => {
+                    let Some(index) = Self::read_index(reader) else {
+                        return false;
+                    };
+                    if apply && self.remove_at(index).is_none() {
+                        log_error!("SyncList remove index {} out of range", index);
+                        return false;
+                    }
+                }
+                Some(SyncListOperation::Clear) => {
+                    if apply {
+                        self.clear();
+                    }
+                }
+                None => {
+                    log_error!("Unknown SyncList operation: {}", operation);
+                    return false;
+                }
+            }
+            if !apply {
+                self.changes_ahead -= 1;
+            }
+        }
+        true
+    }
+
+    fn reset(&mut self) {
+        self.objects.clear();
+        self.changes.clear();
+        self.changes_ahead = 0;
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delta(list: &SyncList<i32>) -> NetworkReader {
+        let mut writer = NetworkWriter::new();
+        list.on_serialize_delta(&mut writer);
+        NetworkReader::new_with_array_segment(writer.to_array_segment())
+    }
+
+    #[test]
+    fn test_sync_list_delta_round_trip() {
+        let mut server = SyncList::<i32>::new();
+        let mut client = SyncList::<i32>::new();
+        server.add(1);
+        server.add(2);
+        server.insert(0, 0);
+        server.set(2, 20);
+        server.remove_at(1);
+
+        let mut writer = NetworkWriter::new();
+        server.on_serialize_delta(&mut writer);
+        // changes=5, OP_ADD 1, OP_ADD 2, OP_INSERT 0 0, OP_SET 2 20, OP_REMOVEAT 1
+        assert_eq!(
+            writer.to_array_segment(),
+            vec![5, 0, 2, 0, 4, 2, 0, 0, 1, 2, 40, 3, 1]
+        );
+
+        assert!(client.on_deserialize_delta(&mut delta(&server)));
+        assert_eq!(
+            client.iter().collect::<Vec<_>>(),
+            server.iter().collect::<Vec<_>>()
+        );
+        server.clear_changes();
+        server.clear();
+        assert!(client.on_deserialize_delta(&mut delta(&server)));
+        assert!(client.is_empty());
+    }
+
+    #[test]
+    fn test_sync_list_skips_changes_ahead() {
+        let mut server = SyncList::<i32>::new();
+        server.add(1);
+        server.add(2);
+
+        // 新客户端先收到完整状态，再收到同一帧的修改
+        let mut writer = NetworkWriter::new();
+        server.on_serialize_all(&mut writer);
+        let mut client = SyncList::<i32>::new();
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        assert!(client.on_deserialize_all(&mut reader));
+        assert!(client.on_deserialize_delta(&mut delta(&server)));
+        assert_eq!(client.len(), 2);
+    }
+}
@@ -1,7 +1,15 @@
+use crate::log_error;
+use crate::mirror::core::backend_data::SyncObjectData;
+use crate::mirror::core::network_field::NetworkField;
 use crate::mirror::core::network_reader::NetworkReader;
 use crate::mirror::core::network_writer::NetworkWriter;
+use crate::mirror::core::sync_dictionary::SyncDictionary;
+use crate::mirror::core::sync_hash_set::SyncHashSet;
+use crate::mirror::core::sync_list::SyncList;
+use nalgebra::{Quaternion, Vector2, Vector3, Vector4};
 use std::any::Any;
 use std::fmt::Debug;
+use std::hash::Hash;
 
 pub trait SyncObject: Any + Send + Sync + Debug {
     fn sub_class_name() -> &'static str
@@ -10,13 +18,195 @@ pub trait SyncObject: Any + Send + Sync + Debug {
     fn is_recording(&self) -> bool {
         true
     }
+    // 没有观察者时不需要记录修改，新的观察者会收到完整状态
+    fn set_recording(&mut self, _value: bool) {}
     fn is_writable(&self) -> bool {
         true
     }
+    // 有未发送的修改时为 true，对应 Mirror 的 OnDirty
+    fn is_dirty(&self) -> bool;
     fn clear_changes(&mut self);
     fn on_serialize_all(&self, writer: &mut NetworkWriter);
     fn on_serialize_delta(&self, writer: &mut NetworkWriter);
     fn on_deserialize_all(&mut self, reader: &mut NetworkReader) -> bool;
     fn on_deserialize_delta(&mut self, reader: &mut NetworkReader) -> bool;
     fn reset(&mut self);
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+// 不支持的 SyncObject 的占位，始终按空集合同步，保持后续 SyncObject 的脏位顺序
+#[derive(Debug, Default)]
+pub struct UnsupportedSyncObject;
+
+impl UnsupportedSyncObject {
+    // 空集合的数量和修改数都是 0
+    fn read_empty(reader: &mut NetworkReader) -> bool {
+        match u32::read_field(reader) {
+            Ok(0) => true,
+            Ok(count) => {
+                log_error!("Unsupported SyncObject received {} entries", count);
+                false
+            }
+            Err(err) => {
+                log_error!("Failed to deserialize unsupported SyncObject: {}", err);
+                false
+            }
+        }
+    }
+}
+
+impl SyncObject for UnsupportedSyncObject {
+    fn sub_class_name() -> &'static str
+    where
+        Self: Sized,
+    {
+        ""
+    }
+
+    fn is_dirty(&self) -> bool {
+        false
+    }
+
+    fn clear_changes(&mut self) {}
+
+    fn on_serialize_all(&self, writer: &mut NetworkWriter) {
+        0u32.write_field(writer);
+        0u32.write_field(writer);
+    }
+
+    fn on_serialize_delta(&self, writer: &mut NetworkWriter) {
+        0u32.write_field(writer);
+    }
+
+    fn on_deserialize_all(&mut self, reader: &mut NetworkReader) -> bool {
+        Self::read_empty(reader) && Self::read_empty(reader)
+    }
+
+    fn on_deserialize_delta(&mut self, reader: &mut NetworkReader) -> bool {
+        Self::read_empty(reader)
+    }
+
+    fn reset(&mut self) {}
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// 可以作为 SyncHashSet 元素和 SyncDictionary 键的 C# 类型
+macro_rules! match_sync_key_type {
+    ($type_name:expr, $t:ident => $body:expr) => {
+        match $type_name {
+            "System.Boolean" => {
+                type $t = bool;
+                $body
+            }
+            "System.Byte" => {
+                type $t = u8;
+                $body
+            }
+            "System.SByte" => {
+                type $t = i8;
+                $body
+            }
+            "System.Int16" => {
+                type $t = i16;
+                $body
+            }
+            "System.UInt16" => {
+                type $t = u16;
+                $body
+            }
+            "System.Int32" => {
+                type $t = i32;
+                $body
+            }
+            "System.UInt32" => {
+                type $t = u32;
+                $body
+            }
+            "System.Int64" => {
+                type $t = i64;
+                $body
+            }
+            "System.UInt64" => {
+                type $t = u64;
+                $body
+            }
+            "System.String" => {
+                type $t = String;
+                $body
+            }
+            _ => None,
+        }
+    };
+}
+
+// 可以作为 SyncList 元素和 SyncDictionary 值的 C# 类型
+macro_rules! match_sync_item_type {
+    ($type_name:expr, $t:ident => $body:expr) => {
+        match $type_name {
+            "System.Single" => {
+                type $t = f32;
+                $body
+            }
+            "System.Double" => {
+                type $t = f64;
+                $body
+            }
+            "UnityEngine.Vector2" => {
+                type $t = Vector2<f32>;
+                $body
+            }
+            "UnityEngine.Vector3" => {
+                type $t = Vector3<f32>;
+                $body
+            }
+            "UnityEngine.Vector4" => {
+                type $t = Vector4<f32>;
+                $body
+            }
+            "UnityEngine.Quaternion" => {
+                type $t = Quaternion<f32>;
+                $body
+            }
+            type_name => match_sync_key_type!(type_name, $t => $body),
+        }
+    };
+}
+
+fn create_sync_dictionary<K>(value_type: &str) -> Option<Box<dyn SyncObject>>
+where
+    K: NetworkField + Clone + Eq + Hash + Debug + Send + Sync + 'static,
+{
+    match_sync_item_type!(value_type, V => Some(Box::new(SyncDictionary::<K, V>::new())))
+}
+
+pub struct SyncObjectFactory;
+
+impl SyncObjectFactory {
+    // 根据 BackendData 中的类型名创建 SyncObject，不支持的类型返回 None
+    pub fn create_sync_object(data: &SyncObjectData) -> Option<Box<dyn SyncObject>> {
+        let generic_argument = |index: usize| {
+            data.generic_arguments
+                .get(index)
+                .map(|argument| argument.as_str())
+                .unwrap_or_default()
+        };
+        match data.r#type.as_str() {
+            "Mirror.SyncList`1" => match_sync_item_type!(
+                generic_argument(0),
+                T => Some(Box::new(SyncList::<T>::new()))
+            ),
+            "Mirror.SyncHashSet`1" => match_sync_key_type!(
+                generic_argument(0),
+                T => Some(Box::new(SyncHashSet::<T>::new()))
+            ),
+            "Mirror.SyncDictionary`2" => match_sync_key_type!(
+                generic_argument(0),
+                K => create_sync_dictionary::<K>(generic_argument(1))
+            ),
+            _ => None,
+        }
+    }
 }
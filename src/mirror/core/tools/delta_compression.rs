@@ -37,4 +37,92 @@ impl DeltaCompression {
             Self::decompress_long(reader, last.w),
         )
     }
-}
\ No newline at end of file
+
+    // 相同字节少于这个数量时不拆分，避免产生太多小段
+    const MIN_SAME_RUN: usize = 4;
+
+    // 按字节和 last 做差分：当前长度，然后是 (相同字节数, 不同字节数, 不同字节) 的序列
+    pub fn compress_bytes(writer: &mut NetworkWriter, last: &[u8], current: &[u8]) {
+        writer.compress_var_uint(current.len() as u32);
+        let same_at = |i: usize| i < last.len() && current[i] == last[i];
+        let mut i = 0;
+        while i < current.len() {
+            let same_start = i;
+            while i < current.len() && same_at(i) {
+                i += 1;
+            }
+            let diff_start = i;
+            while i < current.len() {
+                if same_at(i) {
+                    let mut run = 0;
+                    while i + run < current.len() && same_at(i + run) {
+                        run += 1;
+                    }
+                    if run >= Self::MIN_SAME_RUN || i + run == current.len() {
+                        break;
+                    }
+                    i += run;
+                } else {
+                    i += 1;
+                }
+            }
+            writer.compress_var_uint((diff_start - same_start) as u32);
+            writer.compress_var_uint((i - diff_start) as u32);
+            writer.write_array_segment(current, diff_start, i - diff_start);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 客户端按 compress_bytes 的格式还原
+    fn decompress_bytes(reader: &mut NetworkReader, last: &[u8]) -> Vec<u8> {
+        let length = reader.decompress_var_uint() as usize;
+        let mut current = Vec::new();
+        while current.len() < length {
+            let same = reader.decompress_var_uint() as usize;
+            let diff = reader.decompress_var_uint() as usize;
+            let start = current.len();
+            current.extend_from_slice(&last[start..start + same]);
+            current.extend_from_slice(reader.read_array_segment(diff));
+        }
+        current
+    }
+
+    fn round_trip(last: &[u8], current: &[u8]) -> Vec<u8> {
+        let mut writer = NetworkWriter::new();
+        DeltaCompression::compress_bytes(&mut writer, last, current);
+        let mut reader = NetworkReader::new_with_array_segment(writer.to_array_segment());
+        let result = decompress_bytes(&mut reader, last);
+        assert_eq!(reader.remaining(), 0);
+        result
+    }
+
+    #[test]
+    fn test_compress_bytes_round_trip() {
+        let last: Vec<u8> = (0..64).collect();
+        let mut current = last.clone();
+        current[3] = 200;
+        current[40..44].copy_from_slice(&[1, 2, 3, 4]);
+        for current in [
+            current.clone(),
+            last.clone(),
+            Vec::new(),
+            current[..20].to_vec(),
+            [current.as_slice(), &[9, 9, 9]].concat(),
+        ] {
+            assert_eq!(round_trip(&last, &current), current);
+        }
+        // 没有基线
+        assert_eq!(round_trip(&[], &current), current);
+
+        // 只有一个字节不同时差分很小
+        let mut writer = NetworkWriter::new();
+        let mut changed = last.clone();
+        changed[10] = 0;
+        DeltaCompression::compress_bytes(&mut writer, &last, &changed);
+        assert!(writer.get_position() < 10);
+    }
+}
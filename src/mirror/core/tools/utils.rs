@@ -58,11 +58,7 @@ pub fn to_hex_string(data: &[u8]) -> String {
 #[allow(dead_code)]
 pub fn to_vec_u8(hex_string: &str) -> Vec<u8> {
     let hex_string = hex_string.trim();
-    let hex_string = if hex_string.starts_with("0x") {
-        &hex_string[2..]
-    } else {
-        hex_string
-    };
+    let hex_string = hex_string.strip_prefix("0x").unwrap_or(hex_string);
     let hex_string = if hex_string.len() % 2 == 1 {
         format!("0{}", hex_string)
     } else {
@@ -81,11 +77,7 @@ pub fn to_vec_u8(hex_string: &str) -> Vec<u8> {
 #[allow(dead_code)]
 pub fn hex_string_to_f64(hex_string: &str) -> f64 {
     let hex_string = hex_string.trim();
-    let hex_string = if hex_string.starts_with("0x") {
-        &hex_string[2..]
-    } else {
-        hex_string
-    };
+    let hex_string = hex_string.strip_prefix("0x").unwrap_or(hex_string);
     let hex_string = if hex_string.len() % 2 == 1 {
         format!("0{}", hex_string)
     } else {
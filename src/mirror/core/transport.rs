@@ -102,4 +102,8 @@ pub trait TransportTrait {
     fn get_batcher_threshold(&self, channel: TransportChannel) -> usize {
         self.get_max_packet_size(channel)
     }
+    // 传输层中还没有发送完成的可靠数据字节数，drain 等待它变为 0，不支持时返回 0
+    fn server_reliable_pending(&self, _connection_id: u64) -> usize {
+        0
+    }
 }
@@ -105,7 +105,6 @@ impl Kcp2kTransport {
             data: cb.data.to_vec(),
             channel: Self::from_kcp2k_channel(cb.channel),
             error: Self::from_kcp2k_error_code(cb.error_code),
-            ..TransportCallback::default()
         };
         match Transport::active_transport() {
             None => {